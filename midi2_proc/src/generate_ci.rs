@@ -410,12 +410,18 @@ fn ci_version_impls(root_ident: &syn::Ident, args: &GenerateCiArgs) -> TokenStre
     let mut ret = TokenStream::new();
 
     for version in args.supported_versions.iter().map(|v| v.version) {
-        for compatible_version in args
+        let mut compatible_versions: Vec<u8> = args
             .supported_versions
             .iter()
             .filter(|v| v.version <= version)
             .map(|v| v.version)
-        {
+            .collect();
+        // the common header fields are readable on every version,
+        // including messages which were introduced after version 1
+        if !compatible_versions.contains(&0x1) {
+            compatible_versions.push(0x1);
+        }
+        for compatible_version in compatible_versions {
             ret.extend(quote!{
                 impl<B: crate::buffer::Bytes> crate::ci::version::CiVersion<#compatible_version> for #root_ident<#version, B> {}
             });
@@ -522,7 +528,17 @@ fn ci_impl(root_ident: &syn::Ident) -> TokenStream {
     }
 }
 
-fn try_from_slice_impl(root_ident: &syn::Ident, properties: &[Property]) -> TokenStream {
+fn try_from_slice_impl(
+    root_ident: &syn::Ident,
+    args: &GenerateCiArgs,
+    properties: &[Property],
+) -> TokenStream {
+    let latest_version = args
+        .supported_versions
+        .iter()
+        .map(|v| v.version)
+        .max()
+        .expect("Must support at least one version");
     let mut validation_steps = TokenStream::new();
     for property in properties.iter().filter(|p| !p.writeonly) {
         let meta_type = &property.meta_type;
@@ -544,7 +560,9 @@ fn try_from_slice_impl(root_ident: &syn::Ident, properties: &[Property]) -> Toke
                 if buffer.len() < <Self as crate::traits::MinSize<&[u8]>>::MIN_SIZE {
                     return Err(crate::error::InvalidData(crate::detail::common_err_strings::ERR_SLICE_TOO_SHORT));
                 }
-                if buffer[5] != VERSION {
+                // newer versions of a message extend the older ones,
+                // so they are read through the latest supported version
+                if buffer[5] != VERSION && !(VERSION == #latest_version && buffer[5] > VERSION) {
                     return Err(crate::error::InvalidData("Incorrect CI version"));
                 }
                 let sysex7 = crate::sysex7::Sysex7::try_from(buffer)?;
@@ -608,7 +626,7 @@ pub fn generate_ci(attrs: TokenStream1, item: TokenStream1) -> TokenStream1 {
     let deref_sysex7_impl = deref_sysex7_impl(root_ident);
     let message_impl = message_impl(root_ident, &properties);
     let ci_impl = ci_impl(root_ident);
-    let try_from_slice_impl = try_from_slice_impl(root_ident, &properties);
+    let try_from_slice_impl = try_from_slice_impl(root_ident, &args, &properties);
     let rebuffer_from_impl = rebuffer_from_impl(root_ident);
     let try_rebuffer_from_impl = try_rebuffer_from_impl(root_ident);
 
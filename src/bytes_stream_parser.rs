@@ -0,0 +1,484 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Bytes},
+    error::{BufferOverflow, Error, InvalidData},
+    BytesMessage,
+};
+
+const ERR_UNEXPECTED_DATA_BYTE: &str = "Data byte received without a preceding status byte";
+const ERR_UNEXPECTED_END_OF_SYSEX: &str = "End of sysex byte received outside of a sysex message";
+const ERR_SYSEX_INTERRUPTED: &str = "Sysex message interrupted by a status byte";
+
+const SYSEX_START: u8 = 0xF0;
+const SYSEX_END: u8 = 0xF7;
+
+/// Push-style parser for a MIDI 1.0 byte stream.
+///
+/// Bytes are fed to the parser one at a time, and each complete message is
+/// returned as a [BytesMessage] borrowing from the parser.
+///
+/// - Running status is supported for channel voice messages.
+///   Any system common message cancels the running status.
+/// - Realtime messages (0xF8 – 0xFF) are returned immediately, even when they
+///   arrive in the middle of another message. The interrupted message continues
+///   to be parsed as normal.
+/// - Sysex messages are built into the buffer owned by the parser.
+///   A status byte other than a realtime message or 0xF7 abandons the sysex message
+///   in progress, which is reported as an [InvalidData](crate::error::InvalidData) error.
+///   The one exception is the tune request (0xF6): as it is complete in a single byte,
+///   the tune request is returned instead of the error.
+///   Every abandoned sysex message, including those interrupted by a tune request,
+///   is counted by [abandoned_sysex_count](BytesStreamParser::abandoned_sysex_count).
+///
+/// Parsers backed by a resizable buffer accept sysex messages of any length
+/// via [push](BytesStreamParser::push).
+/// Parsers backed by a fixed size buffer use [try_push](BytesStreamParser::try_push),
+/// which reports a [BufferOverflow](crate::error::Error::BufferOverflow) when a
+/// sysex message doesn't fit. The remainder of that sysex message is discarded.
+///
+/// ## Basic Usage
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let mut parser = BytesStreamParser::<Vec<u8>>::new();
+///
+/// let mut messages = Vec::new();
+/// for byte in [0x90, 0x3C, 0x7F, 0x3E, 0xF8, 0x7F, 0xF0, 0x01, 0x02, 0xF7] {
+///     if let Some(message) = parser.push(byte) {
+///         messages.push(message.unwrap().rebuffer_into());
+///     }
+/// }
+///
+/// let messages: Vec<BytesMessage<Vec<u8>>> = messages;
+/// assert_eq!(messages.len(), 4);
+/// assert_eq!(messages[0].data(), &[0x90, 0x3C, 0x7F]);
+/// assert_eq!(messages[1].data(), &[0xF8]);
+/// assert_eq!(messages[2].data(), &[0x90, 0x3E, 0x7F]);
+/// assert_eq!(messages[3].data(), &[0xF0, 0x01, 0x02, 0xF7]);
+/// ```
+///
+/// ## Fixed Size Buffers
+///
+/// ```rust
+/// use midi2::{prelude::*, error::Error};
+///
+/// let mut parser = BytesStreamParser::<[u8; 4]>::new();
+///
+/// assert_eq!(parser.try_push(0xF0), None);
+/// assert_eq!(parser.try_push(0x01), None);
+/// assert_eq!(parser.try_push(0x02), None);
+/// assert_eq!(parser.try_push(0x03), None);
+/// assert_eq!(parser.try_push(0x04), Some(Err(Error::BufferOverflow)));
+/// assert_eq!(parser.try_push(0x05), None);
+/// assert_eq!(parser.try_push(0xF7), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BytesStreamParser<B: Bytes + BufferMut> {
+    sysex: B,
+    sysex_size: usize,
+    sysex_state: SysexState,
+    running_status: Option<u8>,
+    message: [u8; 3],
+    message_size: usize,
+    realtime: [u8; 3],
+    abandoned_sysex_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SysexState {
+    Idle,
+    InProgress,
+    Overflowed,
+}
+
+impl<B: Bytes + BufferMut + BufferDefault> BytesStreamParser<B> {
+    /// Create a parser backed by a default constructed buffer.
+    pub fn new() -> Self {
+        Self::new_with_buffer(B::default())
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferDefault> core::default::Default for BytesStreamParser<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Bytes + BufferMut> BytesStreamParser<B> {
+    /// Create a parser which builds sysex messages into the provided buffer.
+    pub fn new_with_buffer(buffer: B) -> Self {
+        BytesStreamParser {
+            sysex: buffer,
+            sysex_size: 0,
+            sysex_state: SysexState::Idle,
+            running_status: None,
+            message: [0x0; 3],
+            message_size: 0,
+            realtime: [0x0; 3],
+            abandoned_sysex_count: 0,
+        }
+    }
+
+    /// The number of sysex messages abandoned because a status byte
+    /// arrived before their end.
+    pub fn abandoned_sysex_count(&self) -> usize {
+        self.abandoned_sysex_count
+    }
+
+    /// Discard any partially received message and the running status.
+    ///
+    /// The discarded sysex message isn't counted as abandoned.
+    pub fn reset(&mut self) {
+        self.sysex_size = 0;
+        self.sysex_state = SysexState::Idle;
+        self.running_status = None;
+        self.message_size = 0;
+    }
+
+    fn push_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        byte: u8,
+        resize: R,
+    ) -> Option<Result<BytesMessage<&[u8]>, Error>> {
+        match byte {
+            0xF8..=0xFF => {
+                self.realtime = [byte, 0x0, 0x0];
+                Some(BytesMessage::try_from(&self.realtime[..]).map_err(Error::from))
+            }
+            SYSEX_START => {
+                let interrupted = self.interrupt();
+                self.running_status = None;
+                self.sysex_state = SysexState::InProgress;
+                self.sysex_size = 0;
+                if let Err(e) = self.write_sysex_byte(byte, &resize) {
+                    return Some(Err(e.into()));
+                }
+                interrupted.map(Err)
+            }
+            SYSEX_END => match self.sysex_state {
+                SysexState::Idle => {
+                    self.running_status = None;
+                    self.message_size = 0;
+                    Some(Err(InvalidData(ERR_UNEXPECTED_END_OF_SYSEX).into()))
+                }
+                SysexState::Overflowed => {
+                    self.sysex_state = SysexState::Idle;
+                    None
+                }
+                SysexState::InProgress => {
+                    self.sysex_state = SysexState::Idle;
+                    if let Err(e) = self.write_sysex_byte(byte, &resize) {
+                        return Some(Err(e.into()));
+                    }
+                    Some(
+                        BytesMessage::try_from(&self.sysex.buffer()[..self.sysex_size])
+                            .map_err(Error::from),
+                    )
+                }
+            },
+            0x80..=0xEF | 0xF1..=0xF6 => {
+                let interrupted = self.interrupt();
+                self.running_status = if byte < 0xF0 { Some(byte) } else { None };
+                self.message = [byte, 0x0, 0x0];
+                self.message_size = 1;
+                // a single byte message wins over the interruption error,
+                // which is then only recorded in the abandoned sysex count
+                self.complete_message().or(interrupted.map(Err))
+            }
+            _ => match self.sysex_state {
+                SysexState::Overflowed => None,
+                SysexState::InProgress => {
+                    if let Err(e) = self.write_sysex_byte(byte, &resize) {
+                        self.sysex_state = SysexState::Overflowed;
+                        return Some(Err(e.into()));
+                    }
+                    None
+                }
+                SysexState::Idle => {
+                    if self.message_size == 0 {
+                        let Some(status) = self.running_status else {
+                            return Some(Err(InvalidData(ERR_UNEXPECTED_DATA_BYTE).into()));
+                        };
+                        self.message = [status, 0x0, 0x0];
+                        self.message_size = 1;
+                    }
+                    self.message[self.message_size] = byte;
+                    self.message_size += 1;
+                    self.complete_message()
+                }
+            },
+        }
+    }
+
+    // abandon any message in progress, reporting an error for unfinished sysex
+    fn interrupt(&mut self) -> Option<Error> {
+        self.message_size = 0;
+        let sysex_state = core::mem::replace(&mut self.sysex_state, SysexState::Idle);
+        match sysex_state {
+            SysexState::InProgress => {
+                self.abandoned_sysex_count = self.abandoned_sysex_count.saturating_add(1);
+                Some(InvalidData(ERR_SYSEX_INTERRUPTED).into())
+            }
+            _ => None,
+        }
+    }
+
+    fn complete_message(&mut self) -> Option<Result<BytesMessage<&[u8]>, Error>> {
+        if self.message_size < message_size(self.message[0]) {
+            return None;
+        }
+        self.message_size = 0;
+        Some(BytesMessage::try_from(&self.message[..]).map_err(Error::from))
+    }
+
+    fn write_sysex_byte<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        byte: u8,
+        resize: &R,
+    ) -> Result<(), BufferOverflow> {
+        resize(&mut self.sysex, self.sysex_size + 1)?;
+        self.sysex.buffer_mut()[self.sysex_size] = byte;
+        self.sysex_size += 1;
+        Ok(())
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferResize> BytesStreamParser<B> {
+    /// Feed the next byte of the stream to the parser.
+    ///
+    /// Returns the message completed by this byte, if any.
+    pub fn push(&mut self, byte: u8) -> Option<Result<BytesMessage<&[u8]>, InvalidData>> {
+        self.push_impl(byte, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .map(|result| {
+            result.map_err(|e| match e {
+                Error::InvalidData(e) => e,
+                Error::BufferOverflow => unreachable!("Resizable buffers should not overflow"),
+            })
+        })
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferTryResize> BytesStreamParser<B> {
+    /// Feed the next byte of the stream to the parser.
+    ///
+    /// Returns the message completed by this byte, if any.
+    /// Fails with [BufferOverflow](crate::error::Error::BufferOverflow)
+    /// when a sysex message exceeds the capacity of the buffer.
+    pub fn try_push(&mut self, byte: u8) -> Option<Result<BytesMessage<&[u8]>, Error>> {
+        self.push_impl(byte, |buffer, size| buffer.try_resize(size))
+    }
+}
+
+// the number of bytes in a message with the given status, including the status byte
+pub(crate) fn message_size(status: u8) -> usize {
+    match status {
+        0x80..=0xBF | 0xE0..=0xEF | 0xF2 => 3,
+        0xC0..=0xDF | 0xF1 | 0xF3 => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn parse(bytes: &[u8]) -> std::vec::Vec<Result<std::vec::Vec<u8>, InvalidData>> {
+        let mut parser = BytesStreamParser::<std::vec::Vec<u8>>::new();
+        bytes
+            .iter()
+            .filter_map(|b| parser.push(*b).map(|r| r.map(|m| m.data().to_vec())))
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn channel_voice_messages() {
+        assert_eq!(
+            parse(&[0x92, 0x3C, 0x7F, 0xC3, 0x05, 0xE0, 0x00, 0x40]),
+            std::vec![
+                Ok(std::vec![0x92, 0x3C, 0x7F]),
+                Ok(std::vec![0xC3, 0x05]),
+                Ok(std::vec![0xE0, 0x00, 0x40]),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn running_status() {
+        assert_eq!(
+            parse(&[0x92, 0x3C, 0x7F, 0x3E, 0x7F, 0x40, 0x00, 0xD0, 0x01, 0x02]),
+            std::vec![
+                Ok(std::vec![0x92, 0x3C, 0x7F]),
+                Ok(std::vec![0x92, 0x3E, 0x7F]),
+                Ok(std::vec![0x92, 0x40, 0x00]),
+                Ok(std::vec![0xD0, 0x01]),
+                Ok(std::vec![0xD0, 0x02]),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn system_common_cancels_running_status() {
+        assert_eq!(
+            parse(&[0x92, 0x3C, 0x7F, 0xF3, 0x01, 0x3E, 0x7F]),
+            std::vec![
+                Ok(std::vec![0x92, 0x3C, 0x7F]),
+                Ok(std::vec![0xF3, 0x01]),
+                Err(InvalidData(ERR_UNEXPECTED_DATA_BYTE)),
+                Err(InvalidData(ERR_UNEXPECTED_DATA_BYTE)),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn realtime_within_channel_voice_message() {
+        assert_eq!(
+            parse(&[0x92, 0x3C, 0xF8, 0x7F, 0x3E, 0xFE, 0x7F]),
+            std::vec![
+                Ok(std::vec![0xF8]),
+                Ok(std::vec![0x92, 0x3C, 0x7F]),
+                Ok(std::vec![0xFE]),
+                Ok(std::vec![0x92, 0x3E, 0x7F]),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn system_common_messages_have_no_padding() {
+        assert_eq!(
+            parse(&[0xF1, 0x23, 0xF6, 0xF3, 0x05, 0xFF]),
+            std::vec![
+                Ok(std::vec![0xF1, 0x23]),
+                Ok(std::vec![0xF6]),
+                Ok(std::vec![0xF3, 0x05]),
+                Ok(std::vec![0xFF]),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "system-common"))]
+    fn realtime_within_sysex() {
+        assert_eq!(
+            parse(&[0xF0, 0x01, 0xF8, 0x02, 0xFA, 0xF7]),
+            std::vec![
+                Ok(std::vec![0xF8]),
+                Ok(std::vec![0xFA]),
+                Ok(std::vec![0xF0, 0x01, 0x02, 0xF7]),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn long_sysex() {
+        let mut bytes = std::vec![0xF0];
+        bytes.extend((0..1000).map(|i| (i % 0x80) as u8));
+        bytes.push(0xF7);
+        assert_eq!(parse(&bytes), std::vec![Ok(bytes.clone())]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "channel-voice1"))]
+    fn interrupted_sysex() {
+        assert_eq!(
+            parse(&[0xF0, 0x01, 0x02, 0x90, 0x3C, 0x7F]),
+            std::vec![
+                Err(InvalidData(ERR_SYSEX_INTERRUPTED)),
+                Ok(std::vec![0x90, 0x3C, 0x7F]),
+            ]
+        );
+
+        let mut parser = BytesStreamParser::<std::vec::Vec<u8>>::new();
+        for byte in [0xF0, 0x01, 0xF0, 0x02, 0x90] {
+            parser.push(byte);
+        }
+        assert_eq!(parser.abandoned_sysex_count(), 2);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "system-common"))]
+    fn tune_request_within_sysex() {
+        assert_eq!(
+            parse(&[0xF0, 0x01, 0xF6, 0x02, 0xF7]),
+            std::vec![
+                Ok(std::vec![0xF6]),
+                Err(InvalidData(ERR_UNEXPECTED_DATA_BYTE)),
+                Err(InvalidData(ERR_UNEXPECTED_END_OF_SYSEX)),
+            ]
+        );
+
+        let mut parser = BytesStreamParser::<std::vec::Vec<u8>>::new();
+        parser.push(0xF0);
+        parser.push(0x01);
+        assert_eq!(parser.abandoned_sysex_count(), 0);
+        assert_eq!(parser.push(0xF6).unwrap().unwrap().data(), &[0xF6]);
+        assert_eq!(parser.abandoned_sysex_count(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn unexpected_end_of_sysex() {
+        assert_eq!(
+            parse(&[0xF7, 0xF6]),
+            std::vec![
+                Err(InvalidData(ERR_UNEXPECTED_END_OF_SYSEX)),
+                Ok(std::vec![0xF6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn data_without_status() {
+        assert_eq!(
+            parse(&[0x3C]),
+            std::vec![Err(InvalidData(ERR_UNEXPECTED_DATA_BYTE))]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "system-common"))]
+    fn fixed_size_buffer() {
+        let mut parser = BytesStreamParser::<[u8; 4]>::new();
+        assert_eq!(parser.try_push(0xF0), None);
+        assert_eq!(parser.try_push(0x01), None);
+        assert_eq!(parser.try_push(0x02), None);
+        assert_eq!(
+            parser.try_push(0xF7).map(|r| r.map(|m| m.data().to_vec())),
+            Some(Ok(std::vec![0xF0, 0x01, 0x02, 0xF7]))
+        );
+        assert_eq!(parser.try_push(0xF0), None);
+        assert_eq!(parser.try_push(0x01), None);
+        assert_eq!(parser.try_push(0x02), None);
+        assert_eq!(parser.try_push(0x03), None);
+        assert_eq!(parser.try_push(0x04), Some(Err(Error::BufferOverflow)));
+        assert_eq!(parser.try_push(0x05), None);
+        assert_eq!(parser.try_push(0xF7), None);
+        assert_eq!(
+            parser.try_push(0xF6).map(|r| r.map(|m| m.data().to_vec())),
+            Some(Ok(std::vec![0xF6]))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn borrowed_buffer() {
+        let mut storage = [0x0_u8; 16];
+        let mut parser = BytesStreamParser::new_with_buffer(&mut storage[..]);
+        assert_eq!(parser.try_push(0xF0), None);
+        assert_eq!(parser.try_push(0x7F), None);
+        assert_eq!(
+            parser.try_push(0xF7).map(|r| r.map(|m| m.data().to_vec())),
+            Some(Ok(std::vec![0xF0, 0x7F, 0xF7]))
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Bytes},
+    bytes_stream_parser::message_size,
+    error::BufferOverflow,
+    Data,
+};
+
+const SYSEX_START: u8 = 0xF0;
+
+/// Serialises MIDI 1.0 messages into a byte stream.
+///
+/// Accepts any message represented with bytes, e.g. [BytesMessage](crate::BytesMessage),
+/// `ChannelVoice1<B>` or `SystemCommon<B>`, and appends the shortest valid
+/// byte sequence for the message to the buffer owned by the writer.
+///
+/// When running status is enabled the status byte of a channel voice message
+/// is omitted if it matches the status of the previous channel voice message.
+/// Sysex and system common messages cancel the running status.
+/// Realtime messages leave the running status untouched.
+///
+/// ## Basic Usage
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let mut writer = BytesStreamWriter::<Vec<u8>>::new();
+/// writer.set_running_status(true);
+///
+/// let mut note_on = channel_voice1::NoteOn::<[u8; 3]>::new();
+/// note_on.set_note_number(u7::new(0x3C));
+/// note_on.set_velocity(u7::new(0x7F));
+/// writer.write(&note_on);
+///
+/// note_on.set_note_number(u7::new(0x3E));
+/// writer.write(&note_on);
+///
+/// writer.write(&system_common::TimingClock::<[u8; 3]>::new());
+///
+/// note_on.set_note_number(u7::new(0x40));
+/// writer.write(&note_on);
+///
+/// assert_eq!(
+///     writer.data(),
+///     &[0x90, 0x3C, 0x7F, 0x3E, 0x7F, 0xF8, 0x40, 0x7F],
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct BytesStreamWriter<B: Bytes + BufferMut> {
+    buffer: B,
+    size: usize,
+    running_status_enabled: bool,
+    running_status: Option<u8>,
+}
+
+impl<B: Bytes + BufferMut + BufferDefault> BytesStreamWriter<B> {
+    /// Create a writer backed by a default constructed buffer.
+    ///
+    /// Running status is disabled by default.
+    pub fn new() -> Self {
+        Self::new_with_buffer(B::default())
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferDefault> core::default::Default for BytesStreamWriter<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Bytes + BufferMut> BytesStreamWriter<B> {
+    /// Create a writer which appends to the provided buffer.
+    ///
+    /// Running status is disabled by default.
+    pub fn new_with_buffer(buffer: B) -> Self {
+        BytesStreamWriter {
+            buffer,
+            size: 0,
+            running_status_enabled: false,
+            running_status: None,
+        }
+    }
+
+    /// The bytes written so far.
+    pub fn data(&self) -> &[u8] {
+        &self.buffer.buffer()[..self.size]
+    }
+
+    /// Discard the bytes written so far.
+    ///
+    /// The running status is kept, because the receiver on the other end of
+    /// the stream will still remember it.
+    pub fn clear(&mut self) {
+        self.size = 0;
+    }
+
+    pub fn running_status(&self) -> bool {
+        self.running_status_enabled
+    }
+
+    pub fn set_running_status(&mut self, enabled: bool) {
+        self.running_status_enabled = enabled;
+        self.running_status = None;
+    }
+
+    /// Forget the running status, so that the next channel voice message
+    /// is written with its status byte.
+    pub fn reset_running_status(&mut self) {
+        self.running_status = None;
+    }
+
+    fn write_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        data: &[u8],
+        resize: R,
+    ) -> Result<(), BufferOverflow> {
+        let Some(&status) = data.first() else {
+            return Ok(());
+        };
+
+        let (bytes, running_status) = match status {
+            SYSEX_START => (data, None),
+            0xF8..=0xFF => (&data[..1], self.running_status),
+            0xF1..=0xF7 => (&data[..message_size(status)], None),
+            _ => {
+                let bytes = &data[..message_size(status)];
+                if self.running_status_enabled && self.running_status == Some(status) {
+                    (&bytes[1..], Some(status))
+                } else {
+                    (bytes, Some(status))
+                }
+            }
+        };
+
+        resize(&mut self.buffer, self.size + bytes.len())?;
+        self.buffer.buffer_mut()[self.size..self.size + bytes.len()].copy_from_slice(bytes);
+        self.size += bytes.len();
+        self.running_status = running_status;
+
+        Ok(())
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferResize> BytesStreamWriter<B> {
+    /// Append the message to the stream.
+    pub fn write<U: Bytes, M: Data<U>>(&mut self, message: &M) {
+        self.write_impl(message.data(), |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .expect("Resizable buffers should not fail here")
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferTryResize> BytesStreamWriter<B> {
+    /// Append the message to the stream.
+    ///
+    /// Fails if the message doesn't fit in the remaining space of the buffer,
+    /// in which case nothing is written.
+    pub fn try_write<U: Bytes, M: Data<U>>(&mut self, message: &M) -> Result<(), BufferOverflow> {
+        self.write_impl(message.data(), |buffer, size| buffer.try_resize(size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn without_running_status() {
+        use crate::channel_voice1::{ChannelPressure, NoteOn};
+        let mut writer = BytesStreamWriter::<std::vec::Vec<u8>>::new();
+        writer.write(&NoteOn::try_from(&[0x92, 0x3C, 0x7F][..]).unwrap());
+        writer.write(&NoteOn::try_from(&[0x92, 0x3E, 0x7F][..]).unwrap());
+        writer.write(&ChannelPressure::try_from(&[0xD2, 0x10][..]).unwrap());
+        assert_eq!(
+            writer.data(),
+            &[0x92, 0x3C, 0x7F, 0x92, 0x3E, 0x7F, 0xD2, 0x10]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn running_status() {
+        use crate::channel_voice1::{ChannelPressure, NoteOn};
+        let mut writer = BytesStreamWriter::<std::vec::Vec<u8>>::new();
+        writer.set_running_status(true);
+        writer.write(&NoteOn::try_from(&[0x92, 0x3C, 0x7F][..]).unwrap());
+        writer.write(&NoteOn::try_from(&[0x92, 0x3E, 0x7F][..]).unwrap());
+        writer.write(&ChannelPressure::try_from(&[0xD2, 0x10][..]).unwrap());
+        writer.write(&ChannelPressure::try_from(&[0xD2, 0x11][..]).unwrap());
+        writer.write(&NoteOn::try_from(&[0x93, 0x3C, 0x7F][..]).unwrap());
+        assert_eq!(
+            writer.data(),
+            &[0x92, 0x3C, 0x7F, 0x3E, 0x7F, 0xD2, 0x10, 0x11, 0x93, 0x3C, 0x7F]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn system_common_resets_running_status() {
+        use crate::{channel_voice1::NoteOn, system_common::SongSelect};
+        let mut writer = BytesStreamWriter::<std::vec::Vec<u8>>::new();
+        writer.set_running_status(true);
+        writer.write(&NoteOn::try_from(&[0x92, 0x3C, 0x7F][..]).unwrap());
+        writer.write(&SongSelect::try_from(&[0xF3, 0x01][..]).unwrap());
+        writer.write(&NoteOn::try_from(&[0x92, 0x3E, 0x7F][..]).unwrap());
+        assert_eq!(
+            writer.data(),
+            &[0x92, 0x3C, 0x7F, 0xF3, 0x01, 0x92, 0x3E, 0x7F]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "system-common"))]
+    fn realtime_keeps_running_status() {
+        use crate::{channel_voice1::NoteOn, system_common::TimingClock};
+        let mut writer = BytesStreamWriter::<std::vec::Vec<u8>>::new();
+        writer.set_running_status(true);
+        writer.write(&NoteOn::try_from(&[0x92, 0x3C, 0x7F][..]).unwrap());
+        writer.write(&TimingClock::<[u8; 3]>::new());
+        writer.write(&NoteOn::try_from(&[0x92, 0x3E, 0x7F][..]).unwrap());
+        assert_eq!(writer.data(), &[0x92, 0x3C, 0x7F, 0xF8, 0x3E, 0x7F]);
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn shortest_system_common_messages() {
+        use crate::system_common::{SongPositionPointer, TimeCode, TuneRequest};
+        let mut writer = BytesStreamWriter::<std::vec::Vec<u8>>::new();
+        writer.write(&TuneRequest::<[u8; 3]>::new());
+        writer.write(&TimeCode::try_from(&[0xF1, 0x12, 0x00][..]).unwrap());
+        writer.write(&SongPositionPointer::try_from(&[0xF2, 0x7D, 0x6C][..]).unwrap());
+        assert_eq!(writer.data(), &[0xF6, 0xF1, 0x12, 0xF2, 0x7D, 0x6C]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "sysex7"))]
+    fn bytes_messages() {
+        use crate::BytesMessage;
+        let mut writer = BytesStreamWriter::<std::vec::Vec<u8>>::new();
+        writer.set_running_status(true);
+        writer.write(&BytesMessage::try_from(&[0x80, 0x3C, 0x00][..]).unwrap());
+        writer.write(&BytesMessage::try_from(&[0xF0, 0x01, 0x02, 0xF7][..]).unwrap());
+        writer.write(&BytesMessage::try_from(&[0x80, 0x3C, 0x00][..]).unwrap());
+        assert_eq!(
+            writer.data(),
+            &[0x80, 0x3C, 0x00, 0xF0, 0x01, 0x02, 0xF7, 0x80, 0x3C, 0x00]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn fixed_size_buffer() {
+        use crate::channel_voice1::NoteOn;
+        let mut writer = BytesStreamWriter::<[u8; 4]>::new();
+        assert_eq!(
+            writer.try_write(&NoteOn::try_from(&[0x92, 0x3C, 0x7F][..]).unwrap()),
+            Ok(())
+        );
+        assert_eq!(
+            writer.try_write(&NoteOn::try_from(&[0x92, 0x3E, 0x7F][..]).unwrap()),
+            Err(BufferOverflow)
+        );
+        assert_eq!(writer.data(), &[0x92, 0x3C, 0x7F]);
+        writer.clear();
+        assert_eq!(
+            writer.try_write(&NoteOn::try_from(&[0x92, 0x3E, 0x7F][..]).unwrap()),
+            Ok(())
+        );
+        assert_eq!(writer.data(), &[0x92, 0x3E, 0x7F]);
+    }
+}
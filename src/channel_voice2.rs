@@ -5,11 +5,13 @@ mod assignable_per_note_controller;
 mod attribute;
 mod channel_pitch_bend;
 mod channel_pressure;
+mod channel_state;
 mod control_change;
 mod controller;
 mod key_pressure;
 mod note_off;
 mod note_on;
+mod note_tracker;
 mod packet;
 mod per_note_management;
 mod per_note_pitch_bend;
@@ -24,11 +26,13 @@ pub use assignable_per_note_controller::*;
 pub use attribute::Attribute as NoteAttribute;
 pub use channel_pitch_bend::*;
 pub use channel_pressure::*;
+pub use channel_state::*;
 pub use control_change::*;
 pub use controller::Controller;
 pub use key_pressure::*;
 pub use note_off::*;
 pub use note_on::*;
+pub use note_tracker::*;
 pub use packet::Packet;
 pub use per_note_management::*;
 pub use per_note_pitch_bend::*;
@@ -0,0 +1,659 @@
+use crate::{
+    buffer::Ump,
+    channel_voice2::{
+        AssignableController, ChannelPitchBend, ChannelPressure, ChannelVoice2, ControlChange,
+        ProgramChange, RegisteredController, RelativeAssignableController,
+        RelativeRegisteredController,
+    },
+    error::BufferOverflow,
+    traits::{Channeled, Grouped},
+    ux::{u14, u4, u7},
+};
+
+/// The controller number of the reset all controllers channel mode message.
+const RESET_ALL_CONTROLLERS: u8 = 121;
+
+/// The first controller number of the channel mode messages,
+/// which change the mode of a channel rather than its state.
+const CHANNEL_MODE_MESSAGES: u8 = 120;
+
+/// The controllers which keep their value on reset all controllers,
+/// as listed by the MIDI association recommended practice RP-015:
+/// bank select, volume, pan, sound controllers and effect depths.
+fn retained_on_reset(control: u8) -> bool {
+    matches!(control, 0 | 32 | 7 | 10 | 70..=79 | 91..=95)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ControllerValue {
+    Absolute(u32),
+    /// The sum of the relative changes to a controller whose value is unknown.
+    Relative(i32),
+}
+
+type ControllerSlot = Option<((u7, u7), ControllerValue)>;
+
+/// The controller state of one group and channel of a MIDI 2.0 stream.
+///
+/// Apply every [ChannelVoice2] message of the stream with
+/// [apply](ChannelState::apply): messages on other groups and channels are ignored.
+/// The state keeps the latest value of each control change, registered
+/// controller and assignable controller, of the channel pressure, the channel pitch
+/// bend and the program. Relative controllers are added to the current value of
+/// their controller, saturating at the bounds of the controller range.
+/// The value of a controller which was never set is unknown: relative changes
+/// to it are summed up and kept as a pending change instead.
+///
+/// [replay](ChannelState::replay) produces the messages which bring a freshly
+/// connected device into the same state. Only values which have been received
+/// are sent, and pending changes are sent as relative controllers.
+///
+/// Up to `N` registered and `N` assignable controllers are tracked.
+///
+/// ```rust
+/// use midi2::{prelude::*, channel_voice2::*};
+///
+/// let mut state = ChannelState::<8>::new(u4::new(0x0), u4::new(0x3));
+///
+/// let mut program = ProgramChange::<[u32; 4]>::new();
+/// program.set_channel(u4::new(0x3));
+/// program.set_program(u7::new(0x12));
+/// state.apply(&program.into()).unwrap();
+///
+/// let mut volume = ControlChange::<[u32; 4]>::new();
+/// volume.set_channel(u4::new(0x3));
+/// volume.set_control(u7::new(7));
+/// volume.set_control_change_data(0x8000_0000);
+/// state.apply(&volume.into()).unwrap();
+///
+/// // raise the pitch bend sensitivity, whose value is unknown
+/// let mut relative = RelativeRegisteredController::<[u32; 4]>::new();
+/// relative.set_channel(u4::new(0x3));
+/// relative.set_index(u7::new(0x0));
+/// relative.set_controller_data(0x10);
+/// state.apply(&relative.into()).unwrap();
+///
+/// assert_eq!(state.control_change(u7::new(7)), Some(0x8000_0000));
+/// assert_eq!(state.registered_controller(u7::new(0x0), u7::new(0x0)), None);
+///
+/// let replay: Vec<_> = state.replay().collect();
+/// assert_eq!(replay.len(), 3);
+/// assert!(matches!(replay[0], ChannelVoice2::ProgramChange(_)));
+/// // the change is replayed relative to the value of the receiving device
+/// assert!(matches!(replay[1], ChannelVoice2::RelativeRegisteredController(_)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelState<const N: usize> {
+    group: u4,
+    channel: u4,
+    control_changes: [Option<u32>; 128],
+    registered_controllers: [ControllerSlot; N],
+    assignable_controllers: [ControllerSlot; N],
+    channel_pressure: Option<u32>,
+    pitch_bend: Option<u32>,
+    program: Option<(u7, Option<u14>)>,
+}
+
+impl<const N: usize> ChannelState<N> {
+    /// An empty state for the given group and channel.
+    pub fn new(group: u4, channel: u4) -> Self {
+        ChannelState {
+            group,
+            channel,
+            control_changes: [None; 128],
+            registered_controllers: [None; N],
+            assignable_controllers: [None; N],
+            channel_pressure: None,
+            pitch_bend: None,
+            program: None,
+        }
+    }
+
+    pub fn group(&self) -> u4 {
+        self.group
+    }
+
+    pub fn channel(&self) -> u4 {
+        self.channel
+    }
+
+    /// Update the state with a message.
+    ///
+    /// Fails when a registered or assignable controller arrives while `N`
+    /// controllers of that kind are already tracked. The message is then ignored.
+    ///
+    /// The reset all controllers message returns the controllers to their default
+    /// values, except those which the recommended practice RP-015
+    /// asks to keep. Other channel mode messages are ignored.
+    pub fn apply<B: Ump>(&mut self, message: &ChannelVoice2<B>) -> Result<(), BufferOverflow> {
+        if message.group() != self.group || message.channel() != self.channel {
+            return Ok(());
+        }
+        match message {
+            ChannelVoice2::ControlChange(m) => {
+                let control = u8::from(m.control());
+                if control == RESET_ALL_CONTROLLERS {
+                    self.reset_controllers();
+                } else if control < CHANNEL_MODE_MESSAGES {
+                    self.control_changes[usize::from(control)] = Some(m.control_change_data());
+                }
+            }
+            ChannelVoice2::RegisteredController(m) => {
+                let slot = slot(&mut self.registered_controllers, (m.bank(), m.index()))?;
+                *slot = Some((
+                    (m.bank(), m.index()),
+                    ControllerValue::Absolute(m.controller_data()),
+                ));
+            }
+            ChannelVoice2::AssignableController(m) => {
+                let slot = slot(&mut self.assignable_controllers, (m.bank(), m.index()))?;
+                *slot = Some((
+                    (m.bank(), m.index()),
+                    ControllerValue::Absolute(m.controller_data()),
+                ));
+            }
+            ChannelVoice2::RelativeRegisteredController(m) => {
+                let slot = slot(&mut self.registered_controllers, (m.bank(), m.index()))?;
+                let value = increment(slot.map(|(_, value)| value), m.controller_data());
+                *slot = Some(((m.bank(), m.index()), value));
+            }
+            ChannelVoice2::RelativeAssignableController(m) => {
+                let slot = slot(&mut self.assignable_controllers, (m.bank(), m.index()))?;
+                let value = increment(slot.map(|(_, value)| value), m.controller_data());
+                *slot = Some(((m.bank(), m.index()), value));
+            }
+            ChannelVoice2::ChannelPressure(m) => {
+                self.channel_pressure = Some(m.channel_pressure_data());
+            }
+            ChannelVoice2::ChannelPitchBend(m) => {
+                self.pitch_bend = Some(m.pitch_bend_data());
+            }
+            ChannelVoice2::ProgramChange(m) => {
+                // a program change without bank keeps the current bank of the device
+                let bank = m.bank().or_else(|| self.program.and_then(|(_, bank)| bank));
+                self.program = Some((m.program(), bank));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn control_change(&self, control: u7) -> Option<u32> {
+        self.control_changes[usize::from(u8::from(control))]
+    }
+
+    /// The value of the registered controller,
+    /// or `None` when it was never set, even if it has pending relative changes.
+    pub fn registered_controller(&self, bank: u7, index: u7) -> Option<u32> {
+        find(&self.registered_controllers, (bank, index))
+    }
+
+    /// The value of the assignable controller,
+    /// or `None` when it was never set, even if it has pending relative changes.
+    pub fn assignable_controller(&self, bank: u7, index: u7) -> Option<u32> {
+        find(&self.assignable_controllers, (bank, index))
+    }
+
+    pub fn channel_pressure(&self) -> Option<u32> {
+        self.channel_pressure
+    }
+
+    pub fn pitch_bend(&self) -> Option<u32> {
+        self.pitch_bend
+    }
+
+    pub fn program(&self) -> Option<u7> {
+        self.program.map(|(program, _)| program)
+    }
+
+    pub fn bank(&self) -> Option<u14> {
+        self.program.and_then(|(_, bank)| bank)
+    }
+
+    /// The messages recreating the state on another device.
+    ///
+    /// The program change comes first, as it may reset the sound of the device,
+    /// followed by the registered, assignable and control change controllers,
+    /// the channel pressure and the pitch bend.
+    pub fn replay(&self) -> impl Iterator<Item = ChannelVoice2<[u32; 4]>> + '_ {
+        let program = self.program.map(|(program, bank)| {
+            let mut message = ProgramChange::<[u32; 4]>::new();
+            message.set_program(program);
+            message.set_bank(bank);
+            self.address(message.into())
+        });
+        let registered_controllers =
+            self.registered_controllers
+                .iter()
+                .flatten()
+                .map(|((bank, index), value)| {
+                    let message: ChannelVoice2<[u32; 4]> = match value {
+                        ControllerValue::Absolute(data) => {
+                            let mut message = RegisteredController::<[u32; 4]>::new();
+                            message.set_bank(*bank);
+                            message.set_index(*index);
+                            message.set_controller_data(*data);
+                            message.into()
+                        }
+                        ControllerValue::Relative(data) => {
+                            let mut message = RelativeRegisteredController::<[u32; 4]>::new();
+                            message.set_bank(*bank);
+                            message.set_index(*index);
+                            message.set_controller_data(*data as u32);
+                            message.into()
+                        }
+                    };
+                    self.address(message)
+                });
+        let assignable_controllers =
+            self.assignable_controllers
+                .iter()
+                .flatten()
+                .map(|((bank, index), value)| {
+                    let message: ChannelVoice2<[u32; 4]> = match value {
+                        ControllerValue::Absolute(data) => {
+                            let mut message = AssignableController::<[u32; 4]>::new();
+                            message.set_bank(*bank);
+                            message.set_index(*index);
+                            message.set_controller_data(*data);
+                            message.into()
+                        }
+                        ControllerValue::Relative(data) => {
+                            let mut message = RelativeAssignableController::<[u32; 4]>::new();
+                            message.set_bank(*bank);
+                            message.set_index(*index);
+                            message.set_controller_data(*data as u32);
+                            message.into()
+                        }
+                    };
+                    self.address(message)
+                });
+        let control_changes = self
+            .control_changes
+            .iter()
+            .enumerate()
+            .filter_map(|(control, data)| data.map(|data| (control, data)))
+            .map(|(control, data)| {
+                let mut message = ControlChange::<[u32; 4]>::new();
+                message.set_control(u7::new(control as u8));
+                message.set_control_change_data(data);
+                self.address(message.into())
+            });
+        let channel_pressure = self.channel_pressure.map(|data| {
+            let mut message = ChannelPressure::<[u32; 4]>::new();
+            message.set_channel_pressure_data(data);
+            self.address(message.into())
+        });
+        let pitch_bend = self.pitch_bend.map(|data| {
+            let mut message = ChannelPitchBend::<[u32; 4]>::new();
+            message.set_pitch_bend_data(data);
+            self.address(message.into())
+        });
+
+        program
+            .into_iter()
+            .chain(registered_controllers)
+            .chain(assignable_controllers)
+            .chain(control_changes)
+            .chain(channel_pressure)
+            .chain(pitch_bend)
+    }
+
+    /// Forget every value.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.group, self.channel);
+    }
+
+    fn reset_controllers(&mut self) {
+        for (control, data) in self.control_changes.iter_mut().enumerate() {
+            if !retained_on_reset(control as u8) {
+                *data = None;
+            }
+        }
+        self.channel_pressure = None;
+        self.pitch_bend = None;
+    }
+
+    fn address(&self, mut message: ChannelVoice2<[u32; 4]>) -> ChannelVoice2<[u32; 4]> {
+        message.set_group(self.group);
+        message.set_channel(self.channel);
+        message
+    }
+}
+
+// the slot holding the controller, or the first free one
+fn slot(
+    slots: &mut [ControllerSlot],
+    key: (u7, u7),
+) -> Result<&mut ControllerSlot, BufferOverflow> {
+    let position = slots
+        .iter()
+        .position(|slot| slot.is_some_and(|(k, _)| k == key))
+        .or_else(|| slots.iter().position(Option::is_none))
+        .ok_or(BufferOverflow)?;
+    Ok(&mut slots[position])
+}
+
+fn find(slots: &[ControllerSlot], key: (u7, u7)) -> Option<u32> {
+    slots
+        .iter()
+        .flatten()
+        .find(|(k, _)| *k == key)
+        .and_then(|(_, value)| match value {
+            ControllerValue::Absolute(value) => Some(*value),
+            ControllerValue::Relative(_) => None,
+        })
+}
+
+// relative controller data is a two's complement increment
+fn increment(value: Option<ControllerValue>, data: u32) -> ControllerValue {
+    let data = data as i32;
+    match value {
+        Some(ControllerValue::Absolute(value)) => ControllerValue::Absolute(
+            (i64::from(value) + i64::from(data)).clamp(0, i64::from(u32::MAX)) as u32,
+        ),
+        Some(ControllerValue::Relative(pending)) => {
+            ControllerValue::Relative(pending.saturating_add(data))
+        }
+        None => ControllerValue::Relative(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+    use std::vec::Vec;
+
+    fn state() -> ChannelState<2> {
+        ChannelState::new(u4::new(0x1), u4::new(0x2))
+    }
+
+    fn address(mut message: ChannelVoice2<[u32; 4]>) -> ChannelVoice2<[u32; 4]> {
+        message.set_group(u4::new(0x1));
+        message.set_channel(u4::new(0x2));
+        message
+    }
+
+    fn control_change(control: u8, data: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = ControlChange::<[u32; 4]>::new();
+        message.set_control(u7::new(control));
+        message.set_control_change_data(data);
+        address(message.into())
+    }
+
+    fn registered_controller(bank: u8, index: u8, data: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = RegisteredController::<[u32; 4]>::new();
+        message.set_bank(u7::new(bank));
+        message.set_index(u7::new(index));
+        message.set_controller_data(data);
+        address(message.into())
+    }
+
+    fn relative_registered_controller(bank: u8, index: u8, data: i32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = RelativeRegisteredController::<[u32; 4]>::new();
+        message.set_bank(u7::new(bank));
+        message.set_index(u7::new(index));
+        message.set_controller_data(data as u32);
+        address(message.into())
+    }
+
+    fn pitch_bend(data: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = ChannelPitchBend::<[u32; 4]>::new();
+        message.set_pitch_bend_data(data);
+        address(message.into())
+    }
+
+    fn program_change(program: u8, bank: Option<u16>) -> ChannelVoice2<[u32; 4]> {
+        let mut message = ProgramChange::<[u32; 4]>::new();
+        message.set_program(u7::new(program));
+        message.set_bank(bank.map(u14::new));
+        address(message.into())
+    }
+
+    #[test]
+    fn control_changes() {
+        let mut state = state();
+        state.apply(&control_change(7, 0x1234_5678)).unwrap();
+        state.apply(&control_change(7, 0x8765_4321)).unwrap();
+        assert_eq!(state.control_change(u7::new(7)), Some(0x8765_4321));
+        assert_eq!(state.control_change(u7::new(8)), None);
+    }
+
+    #[test]
+    fn other_channels_are_ignored() {
+        let mut state = state();
+        let mut message = control_change(7, 0x1234_5678);
+        message.set_channel(u4::new(0x3));
+        state.apply(&message).unwrap();
+        let mut message = control_change(7, 0x1234_5678);
+        message.set_group(u4::new(0x0));
+        state.apply(&message).unwrap();
+        assert_eq!(state, self::state());
+    }
+
+    #[test]
+    fn registered_controllers() {
+        let mut state = state();
+        state
+            .apply(&registered_controller(0x0, 0x1, 0x1000))
+            .unwrap();
+        state
+            .apply(&registered_controller(0x0, 0x1, 0x2000))
+            .unwrap();
+        state
+            .apply(&registered_controller(0x1, 0x1, 0x3000))
+            .unwrap();
+        assert_eq!(
+            state.registered_controller(u7::new(0x0), u7::new(0x1)),
+            Some(0x2000)
+        );
+        assert_eq!(
+            state.registered_controller(u7::new(0x1), u7::new(0x1)),
+            Some(0x3000)
+        );
+        assert_eq!(
+            state.assignable_controller(u7::new(0x0), u7::new(0x1)),
+            None
+        );
+    }
+
+    #[test]
+    fn assignable_controllers() {
+        let mut state = state();
+        let mut message = AssignableController::<[u32; 4]>::new();
+        message.set_bank(u7::new(0x5));
+        message.set_index(u7::new(0x6));
+        message.set_controller_data(0xABCD);
+        state.apply(&address(message.into())).unwrap();
+        assert_eq!(
+            state.assignable_controller(u7::new(0x5), u7::new(0x6)),
+            Some(0xABCD)
+        );
+    }
+
+    #[test]
+    fn too_many_controllers() {
+        let mut state = state();
+        state.apply(&registered_controller(0x0, 0x1, 0x1)).unwrap();
+        state.apply(&registered_controller(0x0, 0x2, 0x1)).unwrap();
+        assert_eq!(
+            state.apply(&registered_controller(0x0, 0x3, 0x1)),
+            Err(BufferOverflow)
+        );
+        assert_eq!(state.apply(&registered_controller(0x0, 0x2, 0x2)), Ok(()));
+    }
+
+    #[test]
+    fn relative_controllers() {
+        let mut state = state();
+        state
+            .apply(&registered_controller(0x0, 0x1, 0x1000))
+            .unwrap();
+        state
+            .apply(&relative_registered_controller(0x0, 0x1, 0x10))
+            .unwrap();
+        state
+            .apply(&relative_registered_controller(0x0, 0x1, -0x20))
+            .unwrap();
+        assert_eq!(
+            state.registered_controller(u7::new(0x0), u7::new(0x1)),
+            Some(0x0FF0)
+        );
+    }
+
+    #[test]
+    fn relative_controllers_saturate() {
+        let mut state = state();
+        state.apply(&registered_controller(0x0, 0x1, 0x8)).unwrap();
+        state
+            .apply(&relative_registered_controller(0x0, 0x1, -0x10))
+            .unwrap();
+        assert_eq!(
+            state.registered_controller(u7::new(0x0), u7::new(0x1)),
+            Some(0x0)
+        );
+
+        let mut message = AssignableController::<[u32; 4]>::new();
+        message.set_controller_data(0xFFFF_FFF0);
+        state.apply(&address(message.into())).unwrap();
+        let mut message = RelativeAssignableController::<[u32; 4]>::new();
+        message.set_controller_data(0x100);
+        state.apply(&address(message.into())).unwrap();
+        assert_eq!(
+            state.assignable_controller(u7::new(0x0), u7::new(0x0)),
+            Some(0xFFFF_FFFF)
+        );
+    }
+
+    #[test]
+    fn relative_changes_to_unknown_controllers() {
+        let mut state = state();
+        state
+            .apply(&relative_registered_controller(0x0, 0x0, 0x10))
+            .unwrap();
+        state
+            .apply(&relative_registered_controller(0x0, 0x0, 0x8))
+            .unwrap();
+        assert_eq!(
+            state.registered_controller(u7::new(0x0), u7::new(0x0)),
+            None
+        );
+        assert_eq!(
+            state
+                .replay()
+                .map(|m| m.data().to_vec())
+                .collect::<Vec<_>>(),
+            std::vec![std::vec![0x4142_0000, 0x0000_0018]],
+        );
+
+        // an absolute value replaces the pending changes
+        state
+            .apply(&registered_controller(0x0, 0x0, 0x1000))
+            .unwrap();
+        state
+            .apply(&relative_registered_controller(0x0, 0x0, 0x10))
+            .unwrap();
+        assert_eq!(
+            state.registered_controller(u7::new(0x0), u7::new(0x0)),
+            Some(0x1010)
+        );
+    }
+
+    #[test]
+    fn channel_pressure_and_pitch_bend() {
+        let mut state = state();
+        let mut message = ChannelPressure::<[u32; 4]>::new();
+        message.set_channel_pressure_data(0x4000_0000);
+        state.apply(&address(message.into())).unwrap();
+        state.apply(&pitch_bend(0x9000_0000)).unwrap();
+        assert_eq!(state.channel_pressure(), Some(0x4000_0000));
+        assert_eq!(state.pitch_bend(), Some(0x9000_0000));
+    }
+
+    #[test]
+    fn program_change_keeps_bank() {
+        let mut state = state();
+        state.apply(&program_change(0x10, Some(0x1234))).unwrap();
+        state.apply(&program_change(0x11, None)).unwrap();
+        assert_eq!(state.program(), Some(u7::new(0x11)));
+        assert_eq!(state.bank(), Some(u14::new(0x1234)));
+    }
+
+    #[test]
+    fn reset_all_controllers() {
+        let mut state = state();
+        state.apply(&control_change(1, 0x1)).unwrap();
+        state.apply(&control_change(7, 0x2)).unwrap();
+        state.apply(&pitch_bend(0x9000_0000)).unwrap();
+        state.apply(&program_change(0x10, None)).unwrap();
+        state.apply(&control_change(121, 0x0)).unwrap();
+        assert_eq!(state.control_change(u7::new(1)), None);
+        assert_eq!(state.control_change(u7::new(7)), Some(0x2));
+        assert_eq!(state.control_change(u7::new(121)), None);
+        assert_eq!(state.pitch_bend(), None);
+        assert_eq!(state.program(), Some(u7::new(0x10)));
+    }
+
+    #[test]
+    fn channel_mode_messages_are_ignored() {
+        let mut state = state();
+        state.apply(&control_change(123, 0x0)).unwrap();
+        assert_eq!(state, self::state());
+    }
+
+    #[test]
+    fn replay_empty() {
+        assert_eq!(state().replay().count(), 0);
+    }
+
+    #[test]
+    fn replay() {
+        let mut state = state();
+        state.apply(&pitch_bend(0x9000_0000)).unwrap();
+        state.apply(&control_change(7, 0x2)).unwrap();
+        state.apply(&control_change(1, 0x1)).unwrap();
+        state
+            .apply(&registered_controller(0x0, 0x0, 0x1000))
+            .unwrap();
+        state.apply(&program_change(0x10, Some(0x0081))).unwrap();
+        assert_eq!(
+            state
+                .replay()
+                .map(|m| m.data().to_vec())
+                .collect::<Vec<_>>(),
+            std::vec![
+                std::vec![0x41C2_0001, 0x1000_0101],
+                std::vec![0x4122_0000, 0x0000_1000],
+                std::vec![0x41B2_0100, 0x0000_0001],
+                std::vec![0x41B2_0700, 0x0000_0002],
+                std::vec![0x41E2_0000, 0x9000_0000],
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_recreates_state() {
+        let mut state = state();
+        state.apply(&control_change(74, 0x5)).unwrap();
+        state
+            .apply(&relative_registered_controller(0x0, 0x3, 0x40))
+            .unwrap();
+        state.apply(&program_change(0x7F, None)).unwrap();
+
+        let mut copy = self::state();
+        for message in state.replay() {
+            copy.apply(&message).unwrap();
+        }
+        assert_eq!(copy, state);
+    }
+
+    #[test]
+    fn clear() {
+        let mut state = state();
+        state.apply(&control_change(7, 0x2)).unwrap();
+        state.clear();
+        assert_eq!(state, self::state());
+    }
+}
@@ -0,0 +1,625 @@
+use crate::{
+    buffer::Ump,
+    channel_voice2::{attribute::Attribute, controller, ChannelVoice2, Controller, NoteOff},
+    error::BufferOverflow,
+    traits::{Channeled, Grouped},
+    ux::{u4, u7},
+};
+
+/// The maximum number of registered and of assignable per note controllers
+/// tracked for each note.
+pub const MAX_PER_NOTE_CONTROLLERS: usize = 8;
+
+/// The centre value of per note pitch bend.
+const PITCH_BEND_CENTRE: u32 = 0x8000_0000;
+
+/// The state of a sounding note, as tracked by a [NoteTracker].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteState {
+    group: u4,
+    channel: u4,
+    note_number: u7,
+    velocity: u16,
+    attribute: Option<Attribute>,
+    pitch_bend: u32,
+    pressure: u32,
+    registered_controllers: [Option<Controller>; MAX_PER_NOTE_CONTROLLERS],
+    assignable_controllers: [Option<(u8, u32)>; MAX_PER_NOTE_CONTROLLERS],
+    detached: bool,
+}
+
+impl NoteState {
+    fn new(group: u4, channel: u4, note_number: u7) -> Self {
+        NoteState {
+            group,
+            channel,
+            note_number,
+            velocity: 0,
+            attribute: None,
+            pitch_bend: PITCH_BEND_CENTRE,
+            pressure: 0,
+            registered_controllers: [None; MAX_PER_NOTE_CONTROLLERS],
+            assignable_controllers: [None; MAX_PER_NOTE_CONTROLLERS],
+            detached: false,
+        }
+    }
+
+    pub fn group(&self) -> u4 {
+        self.group
+    }
+
+    pub fn channel(&self) -> u4 {
+        self.channel
+    }
+
+    pub fn note_number(&self) -> u7 {
+        self.note_number
+    }
+
+    pub fn velocity(&self) -> u16 {
+        self.velocity
+    }
+
+    pub fn attribute(&self) -> Option<Attribute> {
+        self.attribute
+    }
+
+    /// The per note pitch bend, centred at 0x8000_0000.
+    pub fn pitch_bend(&self) -> u32 {
+        self.pitch_bend
+    }
+
+    /// The polyphonic key pressure.
+    pub fn pressure(&self) -> u32 {
+        self.pressure
+    }
+
+    /// The value of the registered per note controller with the given index,
+    /// or `None` when the controller still has its default value.
+    pub fn registered_controller(&self, index: u8) -> Option<Controller> {
+        self.registered_controllers
+            .iter()
+            .flatten()
+            .find(|c| controller::to_index_and_data(**c).0 == index)
+            .copied()
+    }
+
+    /// The registered per note controllers which have been set.
+    pub fn registered_controllers(&self) -> impl Iterator<Item = Controller> + '_ {
+        self.registered_controllers.iter().flatten().copied()
+    }
+
+    /// The value of the assignable per note controller with the given index,
+    /// or `None` when the controller still has its default value.
+    pub fn assignable_controller(&self, index: u8) -> Option<u32> {
+        self.assignable_controllers
+            .iter()
+            .flatten()
+            .find(|(i, _)| *i == index)
+            .map(|(_, data)| *data)
+    }
+
+    /// The assignable per note controllers which have been set,
+    /// as index and data pairs.
+    pub fn assignable_controllers(&self) -> impl Iterator<Item = (u8, u32)> + '_ {
+        self.assignable_controllers.iter().flatten().copied()
+    }
+
+    /// Whether the note has been detached from its per note controllers by a
+    /// [PerNoteManagement](crate::channel_voice2::PerNoteManagement) message.
+    ///
+    /// Detached notes keep sounding with the controller values they had
+    /// when detached, and ignore subsequent per note messages.
+    pub fn is_detached(&self) -> bool {
+        self.detached
+    }
+
+    fn is(&self, group: u4, channel: u4, note_number: u7) -> bool {
+        self.group == group && self.channel == channel && self.note_number == note_number
+    }
+
+    fn reset_controllers(&mut self) {
+        self.pitch_bend = PITCH_BEND_CENTRE;
+        self.registered_controllers = [None; MAX_PER_NOTE_CONTROLLERS];
+        self.assignable_controllers = [None; MAX_PER_NOTE_CONTROLLERS];
+    }
+
+    fn set_registered_controller(&mut self, value: Controller) -> Result<(), BufferOverflow> {
+        let index = controller::to_index_and_data(value).0;
+        set_slot(&mut self.registered_controllers, value, |c| {
+            controller::to_index_and_data(*c).0 == index
+        })
+    }
+
+    fn set_assignable_controller(&mut self, index: u8, data: u32) -> Result<(), BufferOverflow> {
+        set_slot(&mut self.assignable_controllers, (index, data), |(i, _)| {
+            *i == index
+        })
+    }
+}
+
+// overwrite the matching slot, or take the first free one
+fn set_slot<T: Copy, F: Fn(&T) -> bool>(
+    slots: &mut [Option<T>],
+    value: T,
+    matches: F,
+) -> Result<(), BufferOverflow> {
+    let position = slots
+        .iter()
+        .position(|slot| slot.as_ref().is_some_and(&matches))
+        .or_else(|| slots.iter().position(Option::is_none))
+        .ok_or(BufferOverflow)?;
+    slots[position] = Some(value);
+    Ok(())
+}
+
+/// Tracks the sounding notes of a MIDI 2.0 stream and their per note state.
+///
+/// Apply every [ChannelVoice2] message of the stream with
+/// [apply](NoteTracker::apply). The tracker follows note on and note off
+/// messages, per note pitch bend, key pressure and per note controllers,
+/// as well as the detach and reset flags of per note management messages:
+///
+/// - detaching a note number detaches its sounding notes from their per note
+///   controllers. They keep their current state until they are released,
+///   while a new note on the same number starts with default controllers.
+/// - resetting a note number returns the per note controllers of its attached
+///   note to their default values.
+///
+/// Up to `N` notes are tracked at once.
+/// After a panic or a disconnect, [note_offs](NoteTracker::note_offs) produces
+/// the messages releasing every sounding note.
+///
+/// ```rust
+/// use midi2::{prelude::*, channel_voice2::*};
+///
+/// let mut tracker = NoteTracker::<16>::new();
+///
+/// let mut note_on = NoteOn::<[u32; 4]>::new();
+/// note_on.set_channel(u4::new(0x2));
+/// note_on.set_note_number(u7::new(0x3C));
+/// note_on.set_velocity(0xC000);
+/// tracker.apply(&note_on.into()).unwrap();
+///
+/// let mut pitch_bend = PerNotePitchBend::<[u32; 4]>::new();
+/// pitch_bend.set_channel(u4::new(0x2));
+/// pitch_bend.set_note_number(u7::new(0x3C));
+/// pitch_bend.set_pitch_bend_data(0x9000_0000);
+/// tracker.apply(&pitch_bend.into()).unwrap();
+///
+/// let note = tracker.note(u4::new(0x0), u4::new(0x2), u7::new(0x3C)).unwrap();
+/// assert_eq!(note.velocity(), 0xC000);
+/// assert_eq!(note.pitch_bend(), 0x9000_0000);
+///
+/// let note_offs: Vec<_> = tracker.note_offs().collect();
+/// assert_eq!(note_offs.len(), 1);
+/// assert_eq!(note_offs[0].note_number(), u7::new(0x3C));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteTracker<const N: usize> {
+    notes: [Option<NoteState>; N],
+}
+
+impl<const N: usize> NoteTracker<N> {
+    pub fn new() -> Self {
+        NoteTracker { notes: [None; N] }
+    }
+
+    /// Update the tracked notes with a message.
+    ///
+    /// Fails when a note on arrives while `N` notes are already sounding,
+    /// or when a note has no room left for another per note controller.
+    /// The message is then ignored.
+    /// Per note messages addressing a note which isn't sounding are ignored.
+    pub fn apply<B: Ump>(&mut self, message: &ChannelVoice2<B>) -> Result<(), BufferOverflow> {
+        let group = message.group();
+        let channel = message.channel();
+        match message {
+            ChannelVoice2::NoteOn(m) => {
+                let note_number = m.note_number();
+                let note = match self.attached_note_mut(group, channel, note_number) {
+                    Some(note) => note,
+                    None => {
+                        let slot = self
+                            .notes
+                            .iter_mut()
+                            .find(|n| n.is_none())
+                            .ok_or(BufferOverflow)?;
+                        slot.insert(NoteState::new(group, channel, note_number))
+                    }
+                };
+                note.velocity = m.velocity();
+                note.attribute = m.attribute();
+            }
+            ChannelVoice2::NoteOff(m) => {
+                let note_number = m.note_number();
+                // the attached note is released first, then the oldest detached one
+                let position = self
+                    .position(|n| n.is(group, channel, note_number) && !n.detached)
+                    .or_else(|| self.position(|n| n.is(group, channel, note_number)));
+                if let Some(position) = position {
+                    self.notes[position] = None;
+                }
+            }
+            ChannelVoice2::PerNotePitchBend(m) => {
+                if let Some(note) = self.attached_note_mut(group, channel, m.note_number()) {
+                    note.pitch_bend = m.pitch_bend_data();
+                }
+            }
+            ChannelVoice2::KeyPressure(m) => {
+                if let Some(note) = self.attached_note_mut(group, channel, m.note_number()) {
+                    note.pressure = m.key_pressure_data();
+                }
+            }
+            ChannelVoice2::RegisteredPerNoteController(m) => {
+                if let Some(note) = self.attached_note_mut(group, channel, m.note_number()) {
+                    note.set_registered_controller(m.controller())?;
+                }
+            }
+            ChannelVoice2::AssignablePerNoteController(m) => {
+                if let Some(note) = self.attached_note_mut(group, channel, m.note_number()) {
+                    note.set_assignable_controller(m.index(), m.controller_data())?;
+                }
+            }
+            ChannelVoice2::PerNoteManagement(m) => {
+                let note_number = m.note_number();
+                if m.detach() {
+                    for note in self.notes.iter_mut().flatten() {
+                        if note.is(group, channel, note_number) {
+                            note.detached = true;
+                        }
+                    }
+                }
+                if m.reset() {
+                    if let Some(note) = self.attached_note_mut(group, channel, note_number) {
+                        note.reset_controllers();
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The attached note with the given number, if it is sounding.
+    pub fn note(&self, group: u4, channel: u4, note_number: u7) -> Option<&NoteState> {
+        self.notes
+            .iter()
+            .flatten()
+            .find(|n| n.is(group, channel, note_number) && !n.detached)
+    }
+
+    /// Every sounding note, including detached notes.
+    pub fn notes(&self) -> impl Iterator<Item = &NoteState> {
+        self.notes.iter().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.iter().all(Option::is_none)
+    }
+
+    /// Note off messages releasing every sounding note.
+    ///
+    /// The tracker is left unchanged; apply the messages or
+    /// [clear](NoteTracker::clear) the tracker once they are sent.
+    pub fn note_offs(&self) -> impl Iterator<Item = NoteOff<[u32; 4]>> + '_ {
+        self.notes().map(|note| {
+            let mut message = NoteOff::<[u32; 4]>::new();
+            message.set_group(note.group);
+            message.set_channel(note.channel);
+            message.set_note_number(note.note_number);
+            message
+        })
+    }
+
+    /// Forget every tracked note.
+    pub fn clear(&mut self) {
+        self.notes = [None; N];
+    }
+
+    fn position<F: Fn(&NoteState) -> bool>(&self, predicate: F) -> Option<usize> {
+        self.notes
+            .iter()
+            .position(|n| n.as_ref().is_some_and(&predicate))
+    }
+
+    fn attached_note_mut(
+        &mut self,
+        group: u4,
+        channel: u4,
+        note_number: u7,
+    ) -> Option<&mut NoteState> {
+        self.notes
+            .iter_mut()
+            .flatten()
+            .find(|n| n.is(group, channel, note_number) && !n.detached)
+    }
+}
+
+impl<const N: usize> core::default::Default for NoteTracker<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        channel_voice2::{
+            AssignablePerNoteController, KeyPressure, NoteOn, PerNoteManagement, PerNotePitchBend,
+            RegisteredPerNoteController,
+        },
+        num::Fixed7_9,
+        Data,
+    };
+    use pretty_assertions::assert_eq;
+    use std::vec::Vec;
+
+    fn note_on(channel: u8, note_number: u8, velocity: u16) -> ChannelVoice2<[u32; 4]> {
+        let mut message = NoteOn::<[u32; 4]>::new();
+        message.set_channel(u4::new(channel));
+        message.set_note_number(u7::new(note_number));
+        message.set_velocity(velocity);
+        message.into()
+    }
+
+    fn note_off(channel: u8, note_number: u8) -> ChannelVoice2<[u32; 4]> {
+        let mut message = NoteOff::<[u32; 4]>::new();
+        message.set_channel(u4::new(channel));
+        message.set_note_number(u7::new(note_number));
+        message.into()
+    }
+
+    fn pitch_bend(note_number: u8, data: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = PerNotePitchBend::<[u32; 4]>::new();
+        message.set_note_number(u7::new(note_number));
+        message.set_pitch_bend_data(data);
+        message.into()
+    }
+
+    fn assignable_controller(note_number: u8, index: u8, data: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = AssignablePerNoteController::<[u32; 4]>::new();
+        message.set_note_number(u7::new(note_number));
+        message.set_index(index);
+        message.set_controller_data(data);
+        message.into()
+    }
+
+    fn management(note_number: u8, detach: bool, reset: bool) -> ChannelVoice2<[u32; 4]> {
+        let mut message = PerNoteManagement::<[u32; 4]>::new();
+        message.set_note_number(u7::new(note_number));
+        message.set_detach(detach);
+        message.set_reset(reset);
+        message.into()
+    }
+
+    fn note(tracker: &NoteTracker<4>, note_number: u8) -> Option<&NoteState> {
+        tracker.note(u4::new(0x0), u4::new(0x0), u7::new(note_number))
+    }
+
+    #[test]
+    fn note_on_with_attribute() {
+        let mut tracker = NoteTracker::<4>::new();
+        let mut message = NoteOn::<[u32; 4]>::new();
+        message.set_group(u4::new(0x3));
+        message.set_channel(u4::new(0x5));
+        message.set_note_number(u7::new(0x40));
+        message.set_velocity(0x1234);
+        message.set_attribute(Some(Attribute::Pitch7_9(Fixed7_9::from_num(64.5))));
+        tracker.apply(&message.into()).unwrap();
+
+        let note = tracker
+            .note(u4::new(0x3), u4::new(0x5), u7::new(0x40))
+            .unwrap();
+        assert_eq!(note.velocity(), 0x1234);
+        assert_eq!(
+            note.attribute(),
+            Some(Attribute::Pitch7_9(Fixed7_9::from_num(64.5)))
+        );
+        assert_eq!(note.pitch_bend(), 0x8000_0000);
+        assert_eq!(note.pressure(), 0x0);
+        assert_eq!(
+            tracker.note(u4::new(0x3), u4::new(0x4), u7::new(0x40)),
+            None
+        );
+    }
+
+    #[test]
+    fn release_note() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker.apply(&note_off(0x1, 0x40)).unwrap();
+        assert_eq!(tracker.len(), 1);
+        tracker.apply(&note_off(0x0, 0x40)).unwrap();
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn retrigger_note() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker.apply(&pitch_bend(0x40, 0x1)).unwrap();
+        tracker.apply(&note_on(0x0, 0x40, 0x5678)).unwrap();
+        assert_eq!(tracker.len(), 1);
+        assert_eq!(note(&tracker, 0x40).unwrap().velocity(), 0x5678);
+        assert_eq!(note(&tracker, 0x40).unwrap().pitch_bend(), 0x1);
+    }
+
+    #[test]
+    fn too_many_notes() {
+        let mut tracker = NoteTracker::<4>::new();
+        for n in 0..4 {
+            tracker.apply(&note_on(0x0, n, 0x1234)).unwrap();
+        }
+        assert_eq!(
+            tracker.apply(&note_on(0x0, 0x4, 0x1234)),
+            Err(BufferOverflow)
+        );
+        assert_eq!(tracker.len(), 4);
+    }
+
+    #[test]
+    fn key_pressure() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        let mut message = KeyPressure::<[u32; 4]>::new();
+        message.set_note_number(u7::new(0x40));
+        message.set_key_pressure_data(0xABCD_0123);
+        tracker.apply(&message.into()).unwrap();
+        assert_eq!(note(&tracker, 0x40).unwrap().pressure(), 0xABCD_0123);
+    }
+
+    #[test]
+    fn per_note_messages_without_note_are_ignored() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&pitch_bend(0x40, 0x1)).unwrap();
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn registered_controllers() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        for controller in [
+            Controller::Modulation(0x1),
+            Controller::Volume(0x2),
+            Controller::Modulation(0x3),
+        ] {
+            let mut message = RegisteredPerNoteController::<[u32; 4]>::new();
+            message.set_note_number(u7::new(0x40));
+            message.set_controller(controller);
+            tracker.apply(&message.into()).unwrap();
+        }
+        let note = note(&tracker, 0x40).unwrap();
+        assert_eq!(
+            note.registered_controller(1),
+            Some(Controller::Modulation(0x3))
+        );
+        assert_eq!(note.registered_controller(7), Some(Controller::Volume(0x2)));
+        assert_eq!(note.registered_controller(10), None);
+        assert_eq!(note.registered_controllers().count(), 2);
+    }
+
+    #[test]
+    fn assignable_controllers() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker
+            .apply(&assignable_controller(0x40, 0x10, 0x1))
+            .unwrap();
+        tracker
+            .apply(&assignable_controller(0x40, 0x10, 0x2))
+            .unwrap();
+        tracker
+            .apply(&assignable_controller(0x40, 0x20, 0x3))
+            .unwrap();
+        let note = note(&tracker, 0x40).unwrap();
+        assert_eq!(note.assignable_controller(0x10), Some(0x2));
+        assert_eq!(note.assignable_controller(0x20), Some(0x3));
+        assert_eq!(note.assignable_controller(0x30), None);
+        assert_eq!(
+            note.assignable_controllers().collect::<Vec<_>>(),
+            std::vec![(0x10, 0x2), (0x20, 0x3)]
+        );
+    }
+
+    #[test]
+    fn too_many_controllers() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        for index in 0..MAX_PER_NOTE_CONTROLLERS as u8 {
+            tracker
+                .apply(&assignable_controller(0x40, index, 0x1))
+                .unwrap();
+        }
+        assert_eq!(
+            tracker.apply(&assignable_controller(0x40, 0xFF, 0x1)),
+            Err(BufferOverflow)
+        );
+        assert_eq!(
+            tracker.apply(&assignable_controller(0x40, 0x0, 0x2)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker.apply(&pitch_bend(0x40, 0x1)).unwrap();
+        tracker
+            .apply(&assignable_controller(0x40, 0x10, 0x1))
+            .unwrap();
+        tracker.apply(&management(0x40, false, true)).unwrap();
+        let note = note(&tracker, 0x40).unwrap();
+        assert_eq!(note.pitch_bend(), 0x8000_0000);
+        assert_eq!(note.assignable_controller(0x10), None);
+        assert_eq!(note.velocity(), 0x1234);
+    }
+
+    #[test]
+    fn detach() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker.apply(&pitch_bend(0x40, 0x1)).unwrap();
+        tracker.apply(&management(0x40, true, false)).unwrap();
+
+        // the detached note keeps its state and ignores new per note messages
+        tracker.apply(&pitch_bend(0x40, 0x2)).unwrap();
+        assert_eq!(note(&tracker, 0x40), None);
+        let detached = *tracker.notes().next().unwrap();
+        assert!(detached.is_detached());
+        assert_eq!(detached.pitch_bend(), 0x1);
+
+        // a new note on the same number starts fresh
+        tracker.apply(&note_on(0x0, 0x40, 0x5678)).unwrap();
+        tracker.apply(&pitch_bend(0x40, 0x3)).unwrap();
+        assert_eq!(tracker.len(), 2);
+        assert_eq!(note(&tracker, 0x40).unwrap().pitch_bend(), 0x3);
+        assert_eq!(note(&tracker, 0x40).unwrap().velocity(), 0x5678);
+
+        // note off releases the attached note first
+        tracker.apply(&note_off(0x0, 0x40)).unwrap();
+        assert_eq!(tracker.notes().collect::<Vec<_>>(), std::vec![&detached]);
+        tracker.apply(&note_off(0x0, 0x40)).unwrap();
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn note_offs() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker.apply(&management(0x40, true, false)).unwrap();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker.apply(&note_on(0x9, 0x3C, 0x1234)).unwrap();
+        assert_eq!(
+            tracker
+                .note_offs()
+                .map(|m| m.data().to_vec())
+                .collect::<Vec<_>>(),
+            std::vec![
+                std::vec![0x4080_4000, 0x0],
+                std::vec![0x4080_4000, 0x0],
+                std::vec![0x4089_3C00, 0x0],
+            ]
+        );
+
+        for message in tracker.clone().note_offs() {
+            tracker.apply(&message.into()).unwrap();
+        }
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn clear() {
+        let mut tracker = NoteTracker::<4>::new();
+        tracker.apply(&note_on(0x0, 0x40, 0x1234)).unwrap();
+        tracker.clear();
+        assert_eq!(tracker, NoteTracker::new());
+    }
+}
@@ -1,12 +1,32 @@
 #![doc = include_str!("ci/README.md")]
 
+mod ack;
 mod common_properties;
 mod device_id;
 mod discovery;
+mod discovery_agent;
+mod endpoint;
+mod invalidate_muid;
+mod message;
+mod nak;
+mod process_inquiry;
+mod profile;
+mod property_exchange;
+mod transport;
 mod version;
 
+pub use ack::*;
 pub use device_id::*;
 pub use discovery::*;
+pub use discovery_agent::*;
+pub use endpoint::*;
+pub use invalidate_muid::*;
+pub use message::*;
+pub use nak::*;
+pub use process_inquiry::*;
+pub use profile::*;
+pub use property_exchange::*;
+pub use transport::*;
 pub use version::*;
 
 pub trait Ci<B: crate::buffer::Bytes> {
@@ -0,0 +1,137 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x7D;
+
+/// Acknowledge a MIDI-CI message.
+///
+/// Introduced with version 2 of MIDI-CI.
+/// The original transaction is identified by its Universal Sysex Sub ID #2,
+/// and the outcome is described by a status code, status data and
+/// five bytes of details, optionally followed by a human readable message.
+#[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 25))]
+struct Ack {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x2)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x2)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x2)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x2)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x2)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x2)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x2)]
+    original_transaction: ux::u7,
+    #[property(ci::common_properties::U7Property<15>)]
+    #[version(0x2)]
+    status_code: ux::u7,
+    #[property(ci::common_properties::U7Property<16>)]
+    #[version(0x2)]
+    status_data: ux::u7,
+    #[property(ci::common_properties::U7ArrayProperty<17, 5>)]
+    #[version(0x2)]
+    details: [ux::u7; 5],
+    #[property(ci::common_properties::TrailingDataWriteProperty<22>)]
+    #[version(0x2)]
+    #[writeonly]
+    #[resize]
+    message_text: &[ux::u7],
+    #[property(ci::common_properties::TrailingDataReadProperty<22>)]
+    #[version(0x2)]
+    #[readonly]
+    message_text: &[u8],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        let message = Ack::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x7D, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn setters() {
+        let mut message = Ack::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::FunctionBlock);
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        message.set_original_transaction(ux::u7::new(0x34));
+        message.set_status_code(ux::u7::new(0x00));
+        message.set_status_data(ux::u7::new(0x05));
+        message.set_details([
+            ux::u7::new(0x1),
+            ux::u7::new(0x2),
+            ux::u7::new(0x3),
+            ux::u7::new(0x4),
+            ux::u7::new(0x5),
+        ]);
+        message.set_message_text(&[ux::u7::new(0x4F), ux::u7::new(0x4B)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7D, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0x34, 0x00, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05, 0x02, 0x00, 0x4F, 0x4B, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn getters() {
+        use crate::ci::Ci;
+        let message = Ack::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7D, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0x34, 0x00, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05, 0x02, 0x00, 0x4F, 0x4B, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.device_id(), ci::DeviceId::FunctionBlock);
+        assert_eq!(message.source(), ux::u28::new(0x1234567));
+        assert_eq!(message.destination(), ux::u28::new(0xB48D9D9));
+        assert_eq!(message.original_transaction(), ux::u7::new(0x34));
+        assert_eq!(message.status_code(), ux::u7::new(0x00));
+        assert_eq!(message.status_data(), ux::u7::new(0x05));
+        assert_eq!(
+            message.details(),
+            [
+                ux::u7::new(0x1),
+                ux::u7::new(0x2),
+                ux::u7::new(0x3),
+                ux::u7::new(0x4),
+                ux::u7::new(0x5),
+            ]
+        );
+        assert_eq!(message.message_text(), b"OK");
+    }
+
+    #[test]
+    fn try_from_slice_v1() {
+        assert!(Ack::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7D, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
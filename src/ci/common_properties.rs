@@ -244,3 +244,235 @@ impl<B: Bytes + BufferMut> property::WriteProperty<B> for BroadcastDestinationPr
         Default::default()
     }
 }
+
+pub struct U7Property<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for U7Property<INDEX> {
+    type Type = ux::u7;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for U7Property<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        ux::u7::new(buffer.buffer()[INDEX] & 0x7F)
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B> for U7Property<INDEX> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        buffer.buffer_mut()[INDEX] = v.into();
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+pub struct U14Property<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for U14Property<INDEX> {
+    type Type = ux::u14;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for U14Property<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        use crate::detail::Encode7Bit;
+        ux::u14::from_u7s(&buffer.buffer()[INDEX..INDEX + 2])
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B> for U14Property<INDEX> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        use crate::detail::Encode7Bit;
+        v.to_u7s(&mut buffer.buffer_mut()[INDEX..INDEX + 2]);
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+pub struct U28Property<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for U28Property<INDEX> {
+    type Type = ux::u28;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for U28Property<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        use crate::detail::Encode7Bit;
+        ux::u28::from_u7s(&buffer.buffer()[INDEX..INDEX + 4])
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B> for U28Property<INDEX> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        use crate::detail::Encode7Bit;
+        v.to_u7s(&mut buffer.buffer_mut()[INDEX..INDEX + 4]);
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+pub struct U7ArrayProperty<const INDEX: usize, const SIZE: usize>;
+
+impl<const INDEX: usize, const SIZE: usize, B: Bytes> property::Property<B>
+    for U7ArrayProperty<INDEX, SIZE>
+{
+    type Type = [ux::u7; SIZE];
+}
+
+impl<'a, const INDEX: usize, const SIZE: usize, B: Bytes> property::ReadProperty<'a, B>
+    for U7ArrayProperty<INDEX, SIZE>
+{
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        core::array::from_fn(|i| ux::u7::new(buffer.buffer()[INDEX + i] & 0x7F))
+    }
+}
+
+impl<const INDEX: usize, const SIZE: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for U7ArrayProperty<INDEX, SIZE>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        for (byte, value) in buffer.buffer_mut()[INDEX..INDEX + SIZE].iter_mut().zip(v) {
+            *byte = value.into();
+        }
+    }
+    fn default() -> Self::Type {
+        [ux::u7::default(); SIZE]
+    }
+}
+
+// Variable length data which ends the message, preceded by its length
+// encoded as a 7 bit little endian number of `LENGTH_SIZE` bytes.
+// The length is found at `INDEX` and the data follows immediately.
+
+const SYSEX_END: u8 = 0xF7;
+const ERR_INVALID_DATA_LENGTH: &str = "Data length field is inconsistent with the message size";
+
+fn trailing_data_max_size(length_size: usize) -> usize {
+    (1_usize << (7 * length_size)) - 1
+}
+
+fn read_length(buffer: &[u8]) -> usize {
+    buffer
+        .iter()
+        .rev()
+        .fold(0, |acc, b| (acc << 7) | usize::from(b & 0x7F))
+}
+
+fn write_length(buffer: &mut [u8], mut length: usize) {
+    for b in buffer.iter_mut() {
+        *b = (length & 0x7F) as u8;
+        length >>= 7;
+    }
+}
+
+pub struct TrailingDataWriteProperty<'a, const INDEX: usize, const LENGTH_SIZE: usize = 2>(
+    core::marker::PhantomData<&'a u8>,
+);
+
+impl<'a, const INDEX: usize, const LENGTH_SIZE: usize>
+    TrailingDataWriteProperty<'a, INDEX, LENGTH_SIZE>
+{
+    fn size(data: &[ux::u7]) -> usize {
+        data.len().min(trailing_data_max_size(LENGTH_SIZE))
+    }
+}
+
+impl<'a, const INDEX: usize, const LENGTH_SIZE: usize, B: Bytes> property::Property<B>
+    for TrailingDataWriteProperty<'a, INDEX, LENGTH_SIZE>
+{
+    type Type = &'a [ux::u7];
+}
+
+impl<'a, const INDEX: usize, const LENGTH_SIZE: usize, B: Bytes + BufferMut>
+    property::WriteProperty<B> for TrailingDataWriteProperty<'a, INDEX, LENGTH_SIZE>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        let size = Self::size(v);
+        let data_index = INDEX + LENGTH_SIZE;
+        let buffer = buffer.buffer_mut();
+        write_length(&mut buffer[INDEX..data_index], size);
+        for (byte, value) in buffer[data_index..data_index + size].iter_mut().zip(v) {
+            *byte = (*value).into();
+        }
+        buffer[data_index + size] = SYSEX_END;
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+}
+
+impl<'a, const INDEX: usize, const LENGTH_SIZE: usize, B: Bytes + BufferMut>
+    property::ResizeProperty<B> for TrailingDataWriteProperty<'a, INDEX, LENGTH_SIZE>
+{
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        buffer.resize(INDEX + LENGTH_SIZE + Self::size(value) + 1);
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        buffer.try_resize(INDEX + LENGTH_SIZE + Self::size(value) + 1)
+    }
+}
+
+pub struct TrailingDataReadProperty<'a, const INDEX: usize, const LENGTH_SIZE: usize = 2>(
+    core::marker::PhantomData<&'a u8>,
+);
+
+impl<'a, const INDEX: usize, const LENGTH_SIZE: usize, B: Bytes> property::Property<B>
+    for TrailingDataReadProperty<'a, INDEX, LENGTH_SIZE>
+{
+    type Type = &'a [u8];
+}
+
+impl<'a, const INDEX: usize, const LENGTH_SIZE: usize, B: 'a + Bytes> property::ReadProperty<'a, B>
+    for TrailingDataReadProperty<'a, INDEX, LENGTH_SIZE>
+{
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        let buffer = buffer.buffer();
+        let data_index = INDEX + LENGTH_SIZE;
+        let size = read_length(&buffer[INDEX..data_index]);
+        if buffer.get(data_index + size) == Some(&SYSEX_END) {
+            Ok(())
+        } else {
+            Err(crate::error::InvalidData(ERR_INVALID_DATA_LENGTH))
+        }
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        let buffer = buffer.buffer();
+        let data_index = INDEX + LENGTH_SIZE;
+        let size = read_length(&buffer[INDEX..data_index]);
+        &buffer[data_index..data_index + size]
+    }
+}
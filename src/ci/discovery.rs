@@ -1,6 +1,7 @@
 use crate::{ci, detail::property};
 
 pub(crate) const STATUS: u8 = 0x70;
+pub(crate) const REPLY_STATUS: u8 = 0x71;
 
 #[midi2_proc::generate_ci(
     SupportedVersion(version = 0x1, min_size = 31),
@@ -57,6 +58,70 @@ struct DiscoveryQuery {
     output_path_id: ux::u7,
 }
 
+pub use reply::DiscoveryReply;
+
+mod reply {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 31),
+        SupportedVersion(version = 0x2, min_size = 33)
+    )]
+    struct DiscoveryReply {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x1)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<REPLY_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+        #[property(DeviceManufacturerProperty)]
+        #[version(0x1)]
+        device_manufacturer: [ux::u7; 3],
+        #[property(DeviceFamilyProperty)]
+        #[version(0x1)]
+        device_family: ux::u14,
+        #[property(DeviceModelNumberProperty)]
+        #[version(0x1)]
+        model_number: ux::u14,
+        #[property(SoftwareVersionProperty)]
+        #[version(0x1)]
+        software_version: [ux::u7; 4],
+        #[property(CiSupportProperty<3>)]
+        #[version(0x1)]
+        process_inquiry_supported: bool,
+        #[property(CiSupportProperty<4>)]
+        #[version(0x1)]
+        property_exchange_supported: bool,
+        #[property(CiSupportProperty<5>)]
+        #[version(0x1)]
+        profile_configuration_supported: bool,
+        #[property(CiSupportProperty<6>)]
+        #[version(0x1)]
+        protocol_negotiation_supported: bool,
+        #[property(MaxSysexSizeProperty)]
+        #[version(0x1)]
+        max_sysex_size: ux::u28,
+        #[property(OutputPathIdProperty)]
+        #[version(0x2)]
+        output_path_id: ux::u7,
+        #[property(ci::common_properties::U7Property<31>)]
+        #[version(0x2)]
+        function_block: ux::u7,
+    }
+}
+
 struct DeviceManufacturerProperty;
 
 impl<B: crate::buffer::Bytes> property::Property<B> for DeviceManufacturerProperty {
@@ -665,4 +730,155 @@ mod tests {
         assert_eq!(message.output_path_id(), value);
         assert_eq!(message.data()[30], u8::from(value));
     }
+
+    #[test]
+    fn reply_new() {
+        use crate::Data;
+
+        let message = DiscoveryReply::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x71, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_new_v1() {
+        use crate::Data;
+
+        let message = DiscoveryReply::<0x1, [u8; 31]>::try_new().expect("Buffer is large enough");
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x71, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_setters() {
+        use crate::Data;
+
+        let mut message = DiscoveryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        message.set_device_manufacturer([ux::u7::new(0x21), ux::u7::new(0x66), ux::u7::new(0x61)]);
+        message.set_device_family(ux::u14::new(0x278A));
+        message.set_model_number(ux::u14::new(0x2269));
+        message.set_software_version([
+            ux::u7::new(0x30),
+            ux::u7::new(0x49),
+            ux::u7::new(0xB),
+            ux::u7::new(0x63),
+        ]);
+        message.set_property_exchange_supported(true);
+        message.set_max_sysex_size(ux::u28::new(0xEF6EFE2));
+        message.set_output_path_id(ux::u7::new(0x25));
+        message.set_function_block(ux::u7::new(0x7F));
+
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0,
+                0x7E,
+                0x7F,
+                0x0D,
+                0x71,
+                0x02,
+                0x67,
+                0x0A,
+                0x0D,
+                0x09,
+                0x59,
+                0x33,
+                0x23,
+                0x5A,
+                0x21,
+                0x66,
+                0x61,
+                0x0A,
+                0x4F,
+                0x69,
+                0x44,
+                0x30,
+                0x49,
+                0x0B,
+                0x63,
+                0b0000_1000,
+                0x62,
+                0x5F,
+                0x5B,
+                0x77,
+                0x25,
+                0x7F,
+                0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_try_from_slice() {
+        use crate::ci::Ci;
+
+        let message = DiscoveryReply::<0x2, _>::try_from(
+            &[
+                0xF0,
+                0x7E,
+                0x7F,
+                0x0D,
+                0x71,
+                0x02,
+                0x67,
+                0x0A,
+                0x0D,
+                0x09,
+                0x59,
+                0x33,
+                0x23,
+                0x5A,
+                0x21,
+                0x66,
+                0x61,
+                0x0A,
+                0x4F,
+                0x69,
+                0x44,
+                0x30,
+                0x49,
+                0x0B,
+                0x63,
+                0b0000_1000,
+                0x62,
+                0x5F,
+                0x5B,
+                0x77,
+                0x25,
+                0x7F,
+                0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.source(), ux::u28::new(0x1234567));
+        assert_eq!(message.destination(), ux::u28::new(0xB48D9D9));
+        assert!(message.property_exchange_supported());
+        assert_eq!(message.output_path_id(), ux::u7::new(0x25));
+        assert_eq!(message.function_block(), ux::u7::new(0x7F));
+    }
+
+    #[test]
+    fn reply_try_from_query() {
+        assert!(DiscoveryReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x70, 0x02, 0x0, 0x0, 0x0, 0x0, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
 }
@@ -0,0 +1,611 @@
+use crate::{
+    ci::{self, Ci, CiMessage},
+    ux::{u14, u28, u7},
+};
+
+/// MUIDs from this value upwards are reserved, the last of them being the broadcast MUID.
+const RESERVED_MUID_START: u32 = 0x0FFF_FF00;
+const MUID_MASK: u32 = 0x0FFF_FFFF;
+/// Function block value of devices which aren't tied to a function block.
+const NO_FUNCTION_BLOCK: u8 = 0x7F;
+
+// the sizes of the messages produced by the agent
+const DISCOVERY_QUERY_SIZE: usize = 32;
+const DISCOVERY_REPLY_SIZE: usize = 33;
+const INVALIDATE_MUID_SIZE: usize = 19;
+
+pub type AgentDiscoveryQuery = ci::DiscoveryQuery<0x2, [u8; DISCOVERY_QUERY_SIZE]>;
+pub type AgentDiscoveryReply = ci::DiscoveryReply<0x2, [u8; DISCOVERY_REPLY_SIZE]>;
+pub type AgentInvalidateMuid = ci::InvalidateMuid<0x2, [u8; INVALIDATE_MUID_SIZE]>;
+
+/// Generate a random MUID outside of the reserved range.
+///
+/// The random number generator is supplied by the caller
+/// and is asked for as many values as it takes to produce a valid MUID.
+///
+/// ```rust
+/// use midi2::ci::generate_muid;
+///
+/// let mut values = [0xFFFF_FFFF, 0x1234_5678].into_iter();
+/// let muid = generate_muid(&mut || values.next().unwrap());
+///
+/// // the first value lands in the reserved range and is discarded
+/// assert_eq!(u32::from(muid), 0x0234_5678);
+/// ```
+pub fn generate_muid<R: FnMut() -> u32>(rng: &mut R) -> u28 {
+    loop {
+        let value = rng() & MUID_MASK;
+        if value < RESERVED_MUID_START {
+            return u28::new(value);
+        }
+    }
+}
+
+/// Identity and capabilities of a device, as exchanged during discovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub manufacturer: [u7; 3],
+    pub family: u14,
+    pub model_number: u14,
+    pub software_version: [u7; 4],
+    pub max_sysex_size: u28,
+    pub process_inquiry_supported: bool,
+    pub property_exchange_supported: bool,
+    pub profile_configuration_supported: bool,
+    /// The function block the device belongs to,
+    /// or `0x7F` when it isn't tied to a function block.
+    /// Only carried by version 2 discovery replies.
+    pub function_block: u7,
+}
+
+impl core::default::Default for DeviceInfo {
+    fn default() -> Self {
+        DeviceInfo {
+            manufacturer: Default::default(),
+            family: Default::default(),
+            model_number: Default::default(),
+            software_version: Default::default(),
+            max_sysex_size: Default::default(),
+            process_inquiry_supported: false,
+            property_exchange_supported: false,
+            profile_configuration_supported: false,
+            function_block: u7::new(NO_FUNCTION_BLOCK),
+        }
+    }
+}
+
+/// A device found by the [CiDiscoveryAgent].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteDevice {
+    pub muid: u28,
+    pub info: DeviceInfo,
+}
+
+/// Changes to the devices known to the [CiDiscoveryAgent].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveryEvent {
+    /// A device was discovered, or the information of a known device was updated.
+    DeviceDiscovered(u28),
+    /// A device was removed following an invalidate MUID message.
+    DeviceRemoved(u28),
+    /// The agent picked a new MUID after a collision was detected.
+    MuidChanged { old: u28, new: u28 },
+}
+
+/// The outcome of feeding a message to the [CiDiscoveryAgent]:
+/// the messages to send in response and what changed as a consequence.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DiscoveryOutcome {
+    event: Option<DiscoveryEvent>,
+    reply: Option<AgentDiscoveryReply>,
+    invalidate_muid: Option<AgentInvalidateMuid>,
+    discovery_query: Option<AgentDiscoveryQuery>,
+}
+
+impl DiscoveryOutcome {
+    pub fn event(&self) -> Option<DiscoveryEvent> {
+        self.event
+    }
+
+    /// The reply to an incoming discovery query.
+    pub fn reply(&self) -> Option<&AgentDiscoveryReply> {
+        self.reply.as_ref()
+    }
+
+    /// Invalidates the previous MUID of the agent after a collision.
+    /// Should be sent before the new discovery query.
+    pub fn invalidate_muid(&self) -> Option<&AgentInvalidateMuid> {
+        self.invalidate_muid.as_ref()
+    }
+
+    /// Restarts discovery with the new MUID of the agent after a collision.
+    pub fn discovery_query(&self) -> Option<&AgentDiscoveryQuery> {
+        self.discovery_query.as_ref()
+    }
+}
+
+/// Carries out the MIDI-CI discovery handshake on behalf of a local device.
+///
+/// The agent owns the MUID of the local device, produces discovery queries,
+/// answers discovery queries from other devices and keeps a table of up to `N`
+/// discovered devices. When the table is full, further devices are ignored.
+///
+/// Receiving a message from our own MUID means another device picked
+/// the same MUID. The agent then invalidates its MUID, picks a new one
+/// and restarts discovery.
+///
+/// Randomness is supplied by the caller as a closure returning random `u32` values.
+///
+/// ```rust
+/// use midi2::{prelude::*, ci::{self, CiDiscoveryAgent, DeviceInfo, DiscoveryEvent}};
+///
+/// let mut seed = 0x1234_5678_u32;
+/// let mut rng = move || {
+///     seed ^= seed << 13;
+///     seed ^= seed >> 17;
+///     seed ^= seed << 5;
+///     seed
+/// };
+///
+/// let mut local = CiDiscoveryAgent::<4>::new(DeviceInfo::default(), &mut rng);
+/// let mut remote = CiDiscoveryAgent::<4>::new(DeviceInfo::default(), &mut rng);
+///
+/// // the local device starts discovery
+/// let query = local.discovery_query();
+///
+/// // the remote device answers
+/// let outcome = remote.handle(&ci::CiMessage::try_from(query.data()).unwrap(), &mut rng);
+/// let reply = outcome.reply().expect("Reply to the query");
+///
+/// // the local device records the remote device
+/// let outcome = local.handle(&ci::CiMessage::try_from(reply.data()).unwrap(), &mut rng);
+/// assert_eq!(outcome.event(), Some(DiscoveryEvent::DeviceDiscovered(remote.muid())));
+/// assert!(local.remote_device(remote.muid()).is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiDiscoveryAgent<const N: usize> {
+    muid: u28,
+    info: DeviceInfo,
+    remote_devices: [Option<RemoteDevice>; N],
+}
+
+impl<const N: usize> CiDiscoveryAgent<N> {
+    pub fn new<R: FnMut() -> u32>(info: DeviceInfo, rng: &mut R) -> Self {
+        CiDiscoveryAgent {
+            muid: generate_muid(rng),
+            info,
+            remote_devices: [None; N],
+        }
+    }
+
+    pub fn muid(&self) -> u28 {
+        self.muid
+    }
+
+    pub fn info(&self) -> &DeviceInfo {
+        &self.info
+    }
+
+    pub fn remote_device(&self, muid: u28) -> Option<&RemoteDevice> {
+        self.remote_devices
+            .iter()
+            .flatten()
+            .find(|device| device.muid == muid)
+    }
+
+    pub fn remote_devices(&self) -> impl Iterator<Item = &RemoteDevice> {
+        self.remote_devices.iter().flatten()
+    }
+
+    /// A broadcast discovery query announcing the local device.
+    pub fn discovery_query(&self) -> AgentDiscoveryQuery {
+        let mut message = AgentDiscoveryQuery::try_new().expect("Buffer fits the message");
+        message.set_source(self.muid);
+        message.set_device_manufacturer(self.info.manufacturer);
+        message.set_device_family(self.info.family);
+        message.set_model_number(self.info.model_number);
+        message.set_software_version(self.info.software_version);
+        message.set_max_sysex_size(self.info.max_sysex_size);
+        message.set_process_inquiry_supported(self.info.process_inquiry_supported);
+        message.set_property_exchange_supported(self.info.property_exchange_supported);
+        message.set_profile_configuration_supported(self.info.profile_configuration_supported);
+        message
+    }
+
+    /// Process an incoming MIDI-CI message.
+    ///
+    /// Messages unrelated to discovery, or addressed to other devices, are ignored.
+    pub fn handle<A: crate::buffer::Bytes, R: FnMut() -> u32>(
+        &mut self,
+        message: &CiMessage<A>,
+        rng: &mut R,
+    ) -> DiscoveryOutcome {
+        let source = message.source();
+        let mut outcome = DiscoveryOutcome::default();
+
+        let is_discovery_message = matches!(
+            message,
+            CiMessage::DiscoveryQueryV1(_)
+                | CiMessage::DiscoveryQueryV2(_)
+                | CiMessage::DiscoveryReplyV1(_)
+                | CiMessage::DiscoveryReplyV2(_)
+                | CiMessage::InvalidateMuidV1(_)
+                | CiMessage::InvalidateMuidV2(_)
+        );
+        if is_discovery_message && source == self.muid {
+            self.change_muid(rng, &mut outcome);
+            return outcome;
+        }
+
+        match message {
+            CiMessage::DiscoveryQueryV1(m) => {
+                outcome.event = self.record(source, device_info!(m));
+                outcome.reply = Some(self.reply(source, u7::new(0)));
+            }
+            CiMessage::DiscoveryQueryV2(m) => {
+                outcome.event = self.record(source, device_info!(m));
+                // the reply goes back on the output path of the query
+                outcome.reply = Some(self.reply(source, m.output_path_id()));
+            }
+            CiMessage::DiscoveryReplyV1(m) if m.destination() == self.muid => {
+                outcome.event = self.record(source, device_info!(m));
+            }
+            CiMessage::DiscoveryReplyV2(m) if m.destination() == self.muid => {
+                outcome.event = self.record(source, device_info!(m, m.function_block()));
+            }
+            CiMessage::InvalidateMuidV1(m) => {
+                outcome.event = self.invalidate(m.target_muid(), rng, &mut outcome);
+            }
+            CiMessage::InvalidateMuidV2(m) => {
+                outcome.event = self.invalidate(m.target_muid(), rng, &mut outcome);
+            }
+            _ => {}
+        }
+
+        outcome
+    }
+
+    fn reply(&self, destination: u28, output_path_id: u7) -> AgentDiscoveryReply {
+        let mut message = AgentDiscoveryReply::try_new().expect("Buffer fits the message");
+        message.set_source(self.muid);
+        message.set_destination(destination);
+        message.set_output_path_id(output_path_id);
+        message.set_function_block(self.info.function_block);
+        message.set_device_manufacturer(self.info.manufacturer);
+        message.set_device_family(self.info.family);
+        message.set_model_number(self.info.model_number);
+        message.set_software_version(self.info.software_version);
+        message.set_max_sysex_size(self.info.max_sysex_size);
+        message.set_process_inquiry_supported(self.info.process_inquiry_supported);
+        message.set_property_exchange_supported(self.info.property_exchange_supported);
+        message.set_profile_configuration_supported(self.info.profile_configuration_supported);
+        message
+    }
+
+    fn record(&mut self, muid: u28, info: DeviceInfo) -> Option<DiscoveryEvent> {
+        let slot = match self
+            .remote_devices
+            .iter()
+            .position(|d| matches!(d, Some(d) if d.muid == muid))
+        {
+            Some(index) => Some(index),
+            None => self.remote_devices.iter().position(Option::is_none),
+        }?;
+        self.remote_devices[slot] = Some(RemoteDevice { muid, info });
+        Some(DiscoveryEvent::DeviceDiscovered(muid))
+    }
+
+    fn invalidate<R: FnMut() -> u32>(
+        &mut self,
+        target: u28,
+        rng: &mut R,
+        outcome: &mut DiscoveryOutcome,
+    ) -> Option<DiscoveryEvent> {
+        if target == self.muid {
+            self.change_muid(rng, outcome);
+            return outcome.event;
+        }
+        let slot = self
+            .remote_devices
+            .iter_mut()
+            .find(|d| matches!(d, Some(d) if d.muid == target))?;
+        *slot = None;
+        Some(DiscoveryEvent::DeviceRemoved(target))
+    }
+
+    fn change_muid<R: FnMut() -> u32>(&mut self, rng: &mut R, outcome: &mut DiscoveryOutcome) {
+        let old = self.muid;
+        let mut new = generate_muid(rng);
+        while new == old {
+            new = generate_muid(rng);
+        }
+        self.muid = new;
+
+        let mut invalidate_muid = AgentInvalidateMuid::try_new().expect("Buffer fits the message");
+        invalidate_muid.set_source(new);
+        invalidate_muid.set_target_muid(old);
+
+        outcome.event = Some(DiscoveryEvent::MuidChanged { old, new });
+        outcome.invalidate_muid = Some(invalidate_muid);
+        outcome.discovery_query = Some(self.discovery_query());
+    }
+}
+
+// reads the device info out of a discovery query or reply of any version
+macro_rules! device_info {
+    ($message:expr) => {
+        device_info!($message, u7::new(NO_FUNCTION_BLOCK))
+    };
+    ($message:expr, $function_block:expr) => {
+        DeviceInfo {
+            manufacturer: $message.device_manufacturer(),
+            family: $message.device_family(),
+            model_number: $message.model_number(),
+            software_version: $message.software_version(),
+            max_sysex_size: $message.max_sysex_size(),
+            process_inquiry_supported: $message.process_inquiry_supported(),
+            property_exchange_supported: $message.property_exchange_supported(),
+            profile_configuration_supported: $message.profile_configuration_supported(),
+            function_block: $function_block,
+        }
+    };
+}
+
+use device_info;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    // yields the given values, then counts upwards
+    fn rng(values: &[u32]) -> impl FnMut() -> u32 + '_ {
+        let mut index = 0;
+        move || {
+            index += 1;
+            values.get(index - 1).copied().unwrap_or(index as u32)
+        }
+    }
+
+    fn info() -> DeviceInfo {
+        DeviceInfo {
+            manufacturer: [u7::new(0x00), u7::new(0x21), u7::new(0x09)],
+            family: u14::new(0x1234),
+            model_number: u14::new(0x0567),
+            software_version: [u7::new(1), u7::new(2), u7::new(3), u7::new(4)],
+            max_sysex_size: u28::new(0x200),
+            process_inquiry_supported: false,
+            property_exchange_supported: true,
+            profile_configuration_supported: true,
+            function_block: u7::new(0x3),
+        }
+    }
+
+    fn parse(data: &[u8]) -> CiMessage<&[u8]> {
+        CiMessage::try_from(data).unwrap()
+    }
+
+    #[test]
+    fn generate_muid_avoids_reserved_range() {
+        let mut rng = rng(&[0x0FFF_FF00, 0x1FFF_FFFF, 0xF0FF_FEFF]);
+        assert_eq!(generate_muid(&mut rng), u28::new(0x00FF_FEFF));
+    }
+
+    #[test]
+    fn discovery_query() {
+        let agent = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x0123_4567]));
+        let query = agent.discovery_query();
+        assert_eq!(query.source(), u28::new(0x0123_4567));
+        assert_eq!(query.destination(), u28::MAX);
+        assert_eq!(query.device_family(), u14::new(0x1234));
+        assert_eq!(query.max_sysex_size(), u28::new(0x200));
+        assert!(query.property_exchange_supported());
+        assert!(!query.process_inquiry_supported());
+    }
+
+    #[test]
+    fn reply_to_query() {
+        let mut agent = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x0123_4567]));
+        let remote = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x0000_0042]));
+
+        let mut query = remote.discovery_query();
+        query.set_output_path_id(u7::new(0x5));
+        let outcome = agent.handle(&parse(query.data()), &mut rng(&[]));
+        let reply = outcome.reply().unwrap();
+        assert_eq!(reply.source(), u28::new(0x0123_4567));
+        assert_eq!(reply.destination(), u28::new(0x42));
+        assert_eq!(reply.software_version(), info().software_version);
+        assert_eq!(reply.output_path_id(), u7::new(0x5));
+        assert_eq!(reply.function_block(), u7::new(0x3));
+        assert_eq!(
+            outcome.event(),
+            Some(DiscoveryEvent::DeviceDiscovered(u28::new(0x42)))
+        );
+        assert!(outcome.invalidate_muid().is_none());
+    }
+
+    #[test]
+    fn reply_to_newer_version_query() {
+        let mut agent = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x0123_4567]));
+        let remote = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x0000_0042]));
+
+        let mut query = remote.discovery_query().data().to_vec();
+        query[5] = 0x3;
+        let outcome = agent.handle(&parse(&query), &mut rng(&[]));
+        assert_eq!(outcome.reply().unwrap().destination(), u28::new(0x42));
+        assert!(agent.remote_device(u28::new(0x42)).is_some());
+    }
+
+    #[test]
+    fn reply_without_function_block() {
+        let mut agent = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+        let remote = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x2]));
+
+        let outcome = agent.handle(&parse(remote.discovery_query().data()), &mut rng(&[]));
+        let reply = outcome.reply().unwrap();
+        assert_eq!(reply.output_path_id(), u7::new(0x0));
+        assert_eq!(reply.function_block(), u7::new(0x7F));
+    }
+
+    #[test]
+    fn record_reply() {
+        let mut agent = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+        let mut remote = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x2]));
+
+        let outcome = remote.handle(&parse(agent.discovery_query().data()), &mut rng(&[]));
+        let outcome = agent.handle(&parse(outcome.reply().unwrap().data()), &mut rng(&[]));
+
+        assert_eq!(
+            outcome.event(),
+            Some(DiscoveryEvent::DeviceDiscovered(u28::new(0x2)))
+        );
+        assert!(outcome.reply().is_none());
+        assert_eq!(
+            agent.remote_device(u28::new(0x2)),
+            Some(&RemoteDevice {
+                muid: u28::new(0x2),
+                info: info(),
+            })
+        );
+    }
+
+    #[test]
+    fn ignore_reply_to_other_device() {
+        let mut agent = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+        let mut remote = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x2]));
+        let other = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x3]));
+
+        let outcome = remote.handle(&parse(other.discovery_query().data()), &mut rng(&[]));
+        let outcome = agent.handle(&parse(outcome.reply().unwrap().data()), &mut rng(&[]));
+
+        assert_eq!(outcome, DiscoveryOutcome::default());
+        assert_eq!(agent.remote_devices().count(), 0);
+    }
+
+    #[test]
+    fn table_full() {
+        let mut agent = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+        let first = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x2]));
+        let second = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x3]));
+
+        agent.handle(&parse(first.discovery_query().data()), &mut rng(&[]));
+        let outcome = agent.handle(&parse(second.discovery_query().data()), &mut rng(&[]));
+
+        assert_eq!(outcome.event(), None);
+        assert!(outcome.reply().is_some());
+        assert_eq!(
+            agent
+                .remote_devices()
+                .map(|d| d.muid)
+                .collect::<std::vec::Vec<_>>(),
+            std::vec![u28::new(0x2)]
+        );
+    }
+
+    #[test]
+    fn rediscovery_updates_device() {
+        let mut agent = CiDiscoveryAgent::<2>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+        let remote = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x2]));
+        let updated = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x2]));
+
+        agent.handle(&parse(remote.discovery_query().data()), &mut rng(&[]));
+        agent.handle(&parse(updated.discovery_query().data()), &mut rng(&[]));
+
+        assert_eq!(agent.remote_devices().count(), 1);
+        // queries don't carry the function block
+        assert_eq!(
+            agent.remote_device(u28::new(0x2)).unwrap().info,
+            DeviceInfo {
+                function_block: u7::new(0x7F),
+                ..info()
+            }
+        );
+    }
+
+    #[test]
+    fn invalidate_remote_device() {
+        let mut agent = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+        let remote = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x2]));
+        agent.handle(&parse(remote.discovery_query().data()), &mut rng(&[]));
+
+        let mut invalidate = ci::InvalidateMuid::<0x2, std::vec::Vec<u8>>::new();
+        invalidate.set_source(u28::new(0x3));
+        invalidate.set_target_muid(u28::new(0x2));
+        let outcome = agent.handle(&parse(invalidate.data()), &mut rng(&[]));
+
+        assert_eq!(
+            outcome.event(),
+            Some(DiscoveryEvent::DeviceRemoved(u28::new(0x2)))
+        );
+        assert_eq!(agent.remote_devices().count(), 0);
+    }
+
+    #[test]
+    fn collision() {
+        let mut agent = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x1]));
+        let impostor = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+
+        let outcome = agent.handle(&parse(impostor.discovery_query().data()), &mut rng(&[0x5]));
+
+        assert_eq!(
+            outcome.event(),
+            Some(DiscoveryEvent::MuidChanged {
+                old: u28::new(0x1),
+                new: u28::new(0x5),
+            })
+        );
+        assert_eq!(agent.muid(), u28::new(0x5));
+        assert!(outcome.reply().is_none());
+
+        let invalidate = outcome.invalidate_muid().unwrap();
+        assert_eq!(invalidate.source(), u28::new(0x5));
+        assert_eq!(invalidate.target_muid(), u28::new(0x1));
+
+        let query = outcome.discovery_query().unwrap();
+        assert_eq!(query.source(), u28::new(0x5));
+        assert_eq!(agent.remote_devices().count(), 0);
+    }
+
+    #[test]
+    fn collision_new_muid_differs() {
+        let mut agent = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x1]));
+        let impostor = CiDiscoveryAgent::<1>::new(DeviceInfo::default(), &mut rng(&[0x1]));
+
+        agent.handle(
+            &parse(impostor.discovery_query().data()),
+            &mut rng(&[0x1, 0x6]),
+        );
+
+        assert_eq!(agent.muid(), u28::new(0x6));
+    }
+
+    #[test]
+    fn invalidated_own_muid() {
+        let mut agent = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x1]));
+
+        let mut invalidate = ci::InvalidateMuid::<0x1, std::vec::Vec<u8>>::new();
+        invalidate.set_source(u28::new(0x3));
+        invalidate.set_target_muid(u28::new(0x1));
+        let outcome = agent.handle(&parse(invalidate.data()), &mut rng(&[0x7]));
+
+        assert_eq!(
+            outcome.event(),
+            Some(DiscoveryEvent::MuidChanged {
+                old: u28::new(0x1),
+                new: u28::new(0x7),
+            })
+        );
+        assert!(outcome.discovery_query().is_some());
+    }
+
+    #[test]
+    fn ignore_other_messages() {
+        let mut agent = CiDiscoveryAgent::<1>::new(info(), &mut rng(&[0x1]));
+        let mut message = ci::ProfileInquiry::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(u28::new(0x1));
+        let outcome = agent.handle(&parse(message.data()), &mut rng(&[]));
+        assert_eq!(outcome, DiscoveryOutcome::default());
+        assert_eq!(agent.muid(), u28::new(0x1));
+    }
+}
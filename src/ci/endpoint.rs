@@ -0,0 +1,171 @@
+use crate::ci;
+
+pub(crate) const INQUIRY_STATUS: u8 = 0x72;
+pub(crate) const REPLY_STATUS: u8 = 0x73;
+
+/// The status of the endpoint information requested by
+/// an [EndpointInquiry] and provided by an [EndpointReply].
+pub const PRODUCT_INSTANCE_ID: u8 = 0x00;
+
+pub use inquiry::EndpointInquiry;
+pub use reply::EndpointReply;
+
+mod inquiry {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 16))]
+    struct EndpointInquiry {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x2)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<INQUIRY_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+        #[property(ci::common_properties::U7Property<14>)]
+        #[version(0x2)]
+        status: ux::u7,
+    }
+}
+
+mod reply {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 18))]
+    struct EndpointReply {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x2)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<REPLY_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+        #[property(ci::common_properties::U7Property<14>)]
+        #[version(0x2)]
+        status: ux::u7,
+        #[property(ci::common_properties::TrailingDataWriteProperty<15>)]
+        #[version(0x2)]
+        #[writeonly]
+        #[resize]
+        information_data: &[ux::u7],
+        #[property(ci::common_properties::TrailingDataReadProperty<15>)]
+        #[version(0x2)]
+        #[readonly]
+        information_data: &[u8],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn inquiry() {
+        let mut message = EndpointInquiry::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        message.set_status(ux::u7::new(PRODUCT_INSTANCE_ID));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x72, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0x00, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_new() {
+        let message = EndpointReply::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0xF7,
+            ]
+        );
+        assert!(message.information_data().is_empty());
+    }
+
+    #[test]
+    fn reply_information_data() {
+        let mut message = EndpointReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_information_data(&[ux::u7::new(0x41), ux::u7::new(0x42), ux::u7::new(0x43)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x03, 0x0, 0x41, 0x42, 0x43, 0xF7,
+            ]
+        );
+        assert_eq!(message.information_data(), b"ABC");
+    }
+
+    #[test]
+    fn reply_shrink_information_data() {
+        let mut message = EndpointReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_information_data(&[ux::u7::new(0x41), ux::u7::new(0x42), ux::u7::new(0x43)]);
+        message.set_information_data(&[ux::u7::new(0x44)]);
+        assert_eq!(message.information_data(), b"D");
+        assert_eq!(message.data().len(), 19);
+    }
+
+    #[test]
+    fn reply_try_information_data() {
+        let mut message = EndpointReply::<0x2, [u8; 20]>::try_new().unwrap();
+        assert!(message
+            .try_set_information_data(&[ux::u7::new(0x41), ux::u7::new(0x42)])
+            .is_ok());
+        assert_eq!(message.information_data(), b"AB");
+        assert!(message
+            .try_set_information_data(&[ux::u7::new(0x41); 3])
+            .is_err());
+    }
+
+    #[test]
+    fn reply_try_from_slice() {
+        let message = EndpointReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x03, 0x0, 0x41, 0x42, 0x43, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.information_data(), b"ABC");
+    }
+
+    #[test]
+    fn reply_try_from_slice_inconsistent_length() {
+        assert!(EndpointReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x73, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x04, 0x0, 0x41, 0x42, 0x43, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
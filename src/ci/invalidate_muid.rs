@@ -0,0 +1,109 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x7E;
+
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 19),
+    SupportedVersion(version = 0x2, min_size = 19)
+)]
+struct InvalidateMuid {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+    #[version(0x1)]
+    device_id: (),
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::BroadcastDestinationProperty)]
+    #[version(0x1)]
+    broadcast_destination: (),
+    #[property(ci::common_properties::U28Property<14>)]
+    #[version(0x1)]
+    target_muid: ux::u28,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new() {
+        let message = InvalidateMuid::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x02, 0x0, 0x0, 0x0, 0x0, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_new_v1() {
+        let message = InvalidateMuid::<0x1, [u8; 19]>::try_new().expect("Buffer is large enough");
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x01, 0x0, 0x0, 0x0, 0x0, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn target_muid() {
+        let mut message = InvalidateMuid::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_target_muid(ux::u28::new(0xB48D9D9));
+        assert_eq!(message.target_muid(), ux::u28::new(0xB48D9D9));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x59, 0x33, 0x23, 0x5A, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = InvalidateMuid::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x59, 0x33, 0x23, 0x5A, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.target_muid(), ux::u28::new(0xB48D9D9));
+    }
+
+    #[test]
+    fn try_from_slice_not_broadcast() {
+        assert!(InvalidateMuid::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7E, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x01, 0x02, 0x03, 0x04,
+                0x59, 0x33, 0x23, 0x5A, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn source() {
+        use crate::ci::Ci;
+        let mut message = InvalidateMuid::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0xEAEB42D));
+        assert_eq!(message.source(), ux::u28::new(0xEAEB42D));
+        assert_eq!(message.destination(), ux::u28::MAX);
+    }
+}
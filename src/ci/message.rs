@@ -0,0 +1,721 @@
+use crate::{
+    buffer::Bytes,
+    ci::{
+        self, ack, common_properties, discovery, endpoint, invalidate_muid, nak, process_inquiry,
+        profile, property_exchange, version::CiVersion, Ci,
+    },
+    detail::property::ReadProperty,
+    sysex7::Sysex7,
+    traits::{BufferAccess, Data},
+};
+
+const SYSEX_START: u8 = 0xF0;
+const UNIVERSAL_SYSEX_NON_REALTIME: u8 = 0x7E;
+const UNIVERSAL_SYSEX_SUB_ID_1_MIDI_CI: u8 = 0x0D;
+const STATUS_INDEX: usize = 4;
+const VERSION_INDEX: usize = 5;
+// the universal sysex header, the ci header and the terminating byte
+const MIN_SIZE: usize = 15;
+
+const ERR_NOT_CI: &str =
+    "Expected universal sysex header for MIDI-CI (0xF0, 0x7E, <device id>, 0x0D)";
+
+/// A MIDI-CI message which isn't represented by any of the typed messages,
+/// either because its sub-ID#2 is unsupported or because its version is.
+///
+/// Only the common MIDI-CI header is interpreted, so that
+/// the message can still be routed on.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UnknownMessage<B: Bytes>(Sysex7<B>);
+
+impl<B: Bytes> UnknownMessage<B> {
+    /// The universal sysex sub-ID#2, identifying the kind of message.
+    pub fn status(&self) -> u8 {
+        self.0.buffer_access().buffer()[STATUS_INDEX]
+    }
+
+    pub fn version(&self) -> u8 {
+        self.0.buffer_access().buffer()[VERSION_INDEX]
+    }
+}
+
+impl<B: Bytes> core::ops::Deref for UnknownMessage<B> {
+    type Target = Sysex7<B>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<B: Bytes> Data<B> for UnknownMessage<B> {
+    fn data(&self) -> &[u8] {
+        self.0.data()
+    }
+}
+
+impl<B: Bytes> CiVersion<0x1> for UnknownMessage<B> {}
+
+impl<B: Bytes> Ci<B> for UnknownMessage<B> {
+    fn device_id(&self) -> ci::DeviceId {
+        <common_properties::DeviceIdProperty as ReadProperty<B>>::read(self.0.buffer_access())
+    }
+    fn source(&self) -> ux::u28 {
+        <common_properties::SourceProperty as ReadProperty<B>>::read(self.0.buffer_access())
+    }
+    fn destination(&self) -> ux::u28 {
+        <common_properties::DestinationProperty as ReadProperty<B>>::read(self.0.buffer_access())
+    }
+}
+
+impl<'a> core::convert::TryFrom<&'a [u8]> for UnknownMessage<&'a [u8]> {
+    type Error = crate::error::InvalidData;
+    fn try_from(buffer: &'a [u8]) -> Result<Self, Self::Error> {
+        validate_header(buffer)?;
+        let sysex7 = Sysex7::try_from(buffer)?;
+        if sysex7.data().len() < MIN_SIZE {
+            return Err(crate::error::InvalidData(
+                crate::detail::common_err_strings::ERR_SLICE_TOO_SHORT,
+            ));
+        }
+        <common_properties::DeviceIdProperty as ReadProperty<&[u8]>>::validate(
+            sysex7.buffer_access(),
+        )?;
+        Ok(UnknownMessage(sysex7))
+    }
+}
+
+fn validate_header(buffer: &[u8]) -> Result<(), crate::error::InvalidData> {
+    if buffer.len() < MIN_SIZE {
+        return Err(crate::error::InvalidData(
+            crate::detail::common_err_strings::ERR_SLICE_TOO_SHORT,
+        ));
+    }
+    if buffer[0] != SYSEX_START
+        || buffer[1] != UNIVERSAL_SYSEX_NON_REALTIME
+        || buffer[3] != UNIVERSAL_SYSEX_SUB_ID_1_MIDI_CI
+    {
+        return Err(crate::error::InvalidData(ERR_NOT_CI));
+    }
+    Ok(())
+}
+
+/// Any MIDI-CI message.
+///
+/// Messages are told apart by their sub-ID#2 and their version.
+/// Messages of a version newer than 2 are backwards compatible and are
+/// represented by the version 2 variant, which reads the fields it knows.
+/// Messages of a kind or version which isn't supported are
+/// represented by [UnknownMessage].
+///
+/// ```rust
+/// use midi2::{prelude::*, ci::{self, Ci}};
+///
+/// let mut query = ci::DiscoveryQuery::<0x2, Vec<u8>>::new();
+/// query.set_source(ux::u28::new(0x1234567));
+///
+/// let message = ci::CiMessage::try_from(query.data()).expect("Valid data");
+/// assert!(matches!(message, ci::CiMessage::DiscoveryQueryV2(_)));
+/// assert_eq!(message.source(), ux::u28::new(0x1234567));
+///
+/// let unknown = ci::CiMessage::try_from(&[
+///     0xF0, 0x7E, 0x7F, 0x0D, 0x10, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x7F, 0x7F, 0x7F, 0x7F,
+///     0xF7,
+/// ][..]).expect("Valid data");
+/// assert!(matches!(unknown, ci::CiMessage::Unknown(_)));
+/// assert_eq!(unknown.source(), ux::u28::new(0x1234567));
+/// assert_eq!(unknown.destination(), ux::u28::MAX);
+/// ```
+#[derive(derive_more::From, midi2_proc::Data, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CiMessage<B: Bytes> {
+    DiscoveryQueryV1(ci::DiscoveryQuery<0x1, B>),
+    DiscoveryQueryV2(ci::DiscoveryQuery<0x2, B>),
+    DiscoveryReplyV1(ci::DiscoveryReply<0x1, B>),
+    DiscoveryReplyV2(ci::DiscoveryReply<0x2, B>),
+    EndpointInquiryV2(ci::EndpointInquiry<0x2, B>),
+    EndpointReplyV2(ci::EndpointReply<0x2, B>),
+    InvalidateMuidV1(ci::InvalidateMuid<0x1, B>),
+    InvalidateMuidV2(ci::InvalidateMuid<0x2, B>),
+    AckV2(ci::Ack<0x2, B>),
+    NakV1(ci::Nak<0x1, B>),
+    NakV2(ci::Nak<0x2, B>),
+    ProfileInquiryV1(ci::ProfileInquiry<0x1, B>),
+    ProfileInquiryV2(ci::ProfileInquiry<0x2, B>),
+    ProfileInquiryReplyV1(ci::ProfileInquiryReply<0x1, B>),
+    ProfileInquiryReplyV2(ci::ProfileInquiryReply<0x2, B>),
+    SetProfileOnV1(ci::SetProfileOn<0x1, B>),
+    SetProfileOnV2(ci::SetProfileOn<0x2, B>),
+    SetProfileOffV1(ci::SetProfileOff<0x1, B>),
+    SetProfileOffV2(ci::SetProfileOff<0x2, B>),
+    ProfileEnabledReportV1(ci::ProfileEnabledReport<0x1, B>),
+    ProfileEnabledReportV2(ci::ProfileEnabledReport<0x2, B>),
+    ProfileDisabledReportV1(ci::ProfileDisabledReport<0x1, B>),
+    ProfileDisabledReportV2(ci::ProfileDisabledReport<0x2, B>),
+    ProfileAddedReportV2(ci::ProfileAddedReport<0x2, B>),
+    ProfileRemovedReportV2(ci::ProfileRemovedReport<0x2, B>),
+    ProfileDetailsInquiryV2(ci::ProfileDetailsInquiry<0x2, B>),
+    ProfileDetailsReplyV2(ci::ProfileDetailsReply<0x2, B>),
+    ProfileSpecificDataV1(ci::ProfileSpecificData<0x1, B>),
+    ProfileSpecificDataV2(ci::ProfileSpecificData<0x2, B>),
+    PropertyExchangeCapabilitiesV1(ci::PropertyExchangeCapabilities<0x1, B>),
+    PropertyExchangeCapabilitiesV2(ci::PropertyExchangeCapabilities<0x2, B>),
+    PropertyExchangeCapabilitiesReplyV1(ci::PropertyExchangeCapabilitiesReply<0x1, B>),
+    PropertyExchangeCapabilitiesReplyV2(ci::PropertyExchangeCapabilitiesReply<0x2, B>),
+    GetPropertyDataV1(ci::GetPropertyData<0x1, B>),
+    GetPropertyDataV2(ci::GetPropertyData<0x2, B>),
+    GetPropertyDataReplyV1(ci::GetPropertyDataReply<0x1, B>),
+    GetPropertyDataReplyV2(ci::GetPropertyDataReply<0x2, B>),
+    SetPropertyDataV1(ci::SetPropertyData<0x1, B>),
+    SetPropertyDataV2(ci::SetPropertyData<0x2, B>),
+    SetPropertyDataReplyV1(ci::SetPropertyDataReply<0x1, B>),
+    SetPropertyDataReplyV2(ci::SetPropertyDataReply<0x2, B>),
+    SubscriptionV1(ci::Subscription<0x1, B>),
+    SubscriptionV2(ci::Subscription<0x2, B>),
+    SubscriptionReplyV1(ci::SubscriptionReply<0x1, B>),
+    SubscriptionReplyV2(ci::SubscriptionReply<0x2, B>),
+    NotifyV1(ci::Notify<0x1, B>),
+    NotifyV2(ci::Notify<0x2, B>),
+    ProcessInquiryCapabilitiesV2(ci::ProcessInquiryCapabilities<0x2, B>),
+    ProcessInquiryCapabilitiesReplyV2(ci::ProcessInquiryCapabilitiesReply<0x2, B>),
+    MidiMessageReportV2(ci::MidiMessageReport<0x2, B>),
+    MidiMessageReportReplyV2(ci::MidiMessageReportReply<0x2, B>),
+    EndOfMidiMessageReportV2(ci::EndOfMidiMessageReport<0x2, B>),
+    Unknown(UnknownMessage<B>),
+}
+
+impl<'a> core::convert::TryFrom<&'a [u8]> for CiMessage<&'a [u8]> {
+    type Error = crate::error::InvalidData;
+    fn try_from(buffer: &'a [u8]) -> Result<Self, Self::Error> {
+        use CiMessage::*;
+
+        validate_header(buffer)?;
+
+        // later versions are parsed as the latest supported one
+        let version = buffer[VERSION_INDEX].min(0x2);
+
+        Ok(match (buffer[STATUS_INDEX], version) {
+            (discovery::STATUS, 0x1) => DiscoveryQueryV1(ci::DiscoveryQuery::try_from(buffer)?),
+            (discovery::STATUS, 0x2) => DiscoveryQueryV2(ci::DiscoveryQuery::try_from(buffer)?),
+            (discovery::REPLY_STATUS, 0x1) => {
+                DiscoveryReplyV1(ci::DiscoveryReply::try_from(buffer)?)
+            }
+            (discovery::REPLY_STATUS, 0x2) => {
+                DiscoveryReplyV2(ci::DiscoveryReply::try_from(buffer)?)
+            }
+            (endpoint::INQUIRY_STATUS, 0x2) => {
+                EndpointInquiryV2(ci::EndpointInquiry::try_from(buffer)?)
+            }
+            (endpoint::REPLY_STATUS, 0x2) => EndpointReplyV2(ci::EndpointReply::try_from(buffer)?),
+            (invalidate_muid::STATUS, 0x1) => {
+                InvalidateMuidV1(ci::InvalidateMuid::try_from(buffer)?)
+            }
+            (invalidate_muid::STATUS, 0x2) => {
+                InvalidateMuidV2(ci::InvalidateMuid::try_from(buffer)?)
+            }
+            (ack::STATUS, 0x2) => AckV2(ci::Ack::try_from(buffer)?),
+            (nak::STATUS, 0x1) => NakV1(ci::Nak::try_from(buffer)?),
+            (nak::STATUS, 0x2) => NakV2(ci::Nak::try_from(buffer)?),
+            (profile::INQUIRY_STATUS, 0x1) => {
+                ProfileInquiryV1(ci::ProfileInquiry::try_from(buffer)?)
+            }
+            (profile::INQUIRY_STATUS, 0x2) => {
+                ProfileInquiryV2(ci::ProfileInquiry::try_from(buffer)?)
+            }
+            (profile::REPLY_STATUS, 0x1) => {
+                ProfileInquiryReplyV1(ci::ProfileInquiryReply::try_from(buffer)?)
+            }
+            (profile::REPLY_STATUS, 0x2) => {
+                ProfileInquiryReplyV2(ci::ProfileInquiryReply::try_from(buffer)?)
+            }
+            (profile::SET_ON_STATUS, 0x1) => SetProfileOnV1(ci::SetProfileOn::try_from(buffer)?),
+            (profile::SET_ON_STATUS, 0x2) => SetProfileOnV2(ci::SetProfileOn::try_from(buffer)?),
+            (profile::SET_OFF_STATUS, 0x1) => SetProfileOffV1(ci::SetProfileOff::try_from(buffer)?),
+            (profile::SET_OFF_STATUS, 0x2) => SetProfileOffV2(ci::SetProfileOff::try_from(buffer)?),
+            (profile::ENABLED_REPORT_STATUS, 0x1) => {
+                ProfileEnabledReportV1(ci::ProfileEnabledReport::try_from(buffer)?)
+            }
+            (profile::ENABLED_REPORT_STATUS, 0x2) => {
+                ProfileEnabledReportV2(ci::ProfileEnabledReport::try_from(buffer)?)
+            }
+            (profile::DISABLED_REPORT_STATUS, 0x1) => {
+                ProfileDisabledReportV1(ci::ProfileDisabledReport::try_from(buffer)?)
+            }
+            (profile::DISABLED_REPORT_STATUS, 0x2) => {
+                ProfileDisabledReportV2(ci::ProfileDisabledReport::try_from(buffer)?)
+            }
+            (profile::ADDED_REPORT_STATUS, 0x2) => {
+                ProfileAddedReportV2(ci::ProfileAddedReport::try_from(buffer)?)
+            }
+            (profile::REMOVED_REPORT_STATUS, 0x2) => {
+                ProfileRemovedReportV2(ci::ProfileRemovedReport::try_from(buffer)?)
+            }
+            (profile::DETAILS_INQUIRY_STATUS, 0x2) => {
+                ProfileDetailsInquiryV2(ci::ProfileDetailsInquiry::try_from(buffer)?)
+            }
+            (profile::DETAILS_REPLY_STATUS, 0x2) => {
+                ProfileDetailsReplyV2(ci::ProfileDetailsReply::try_from(buffer)?)
+            }
+            (profile::SPECIFIC_DATA_STATUS, 0x1) => {
+                ProfileSpecificDataV1(ci::ProfileSpecificData::try_from(buffer)?)
+            }
+            (profile::SPECIFIC_DATA_STATUS, 0x2) => {
+                ProfileSpecificDataV2(ci::ProfileSpecificData::try_from(buffer)?)
+            }
+            (property_exchange::CAPABILITIES_STATUS, 0x1) => {
+                PropertyExchangeCapabilitiesV1(ci::PropertyExchangeCapabilities::try_from(buffer)?)
+            }
+            (property_exchange::CAPABILITIES_STATUS, 0x2) => {
+                PropertyExchangeCapabilitiesV2(ci::PropertyExchangeCapabilities::try_from(buffer)?)
+            }
+            (property_exchange::CAPABILITIES_REPLY_STATUS, 0x1) => {
+                PropertyExchangeCapabilitiesReplyV1(
+                    ci::PropertyExchangeCapabilitiesReply::try_from(buffer)?,
+                )
+            }
+            (property_exchange::CAPABILITIES_REPLY_STATUS, 0x2) => {
+                PropertyExchangeCapabilitiesReplyV2(
+                    ci::PropertyExchangeCapabilitiesReply::try_from(buffer)?,
+                )
+            }
+            (property_exchange::GET_STATUS, 0x1) => {
+                GetPropertyDataV1(ci::GetPropertyData::try_from(buffer)?)
+            }
+            (property_exchange::GET_STATUS, 0x2) => {
+                GetPropertyDataV2(ci::GetPropertyData::try_from(buffer)?)
+            }
+            (property_exchange::GET_REPLY_STATUS, 0x1) => {
+                GetPropertyDataReplyV1(ci::GetPropertyDataReply::try_from(buffer)?)
+            }
+            (property_exchange::GET_REPLY_STATUS, 0x2) => {
+                GetPropertyDataReplyV2(ci::GetPropertyDataReply::try_from(buffer)?)
+            }
+            (property_exchange::SET_STATUS, 0x1) => {
+                SetPropertyDataV1(ci::SetPropertyData::try_from(buffer)?)
+            }
+            (property_exchange::SET_STATUS, 0x2) => {
+                SetPropertyDataV2(ci::SetPropertyData::try_from(buffer)?)
+            }
+            (property_exchange::SET_REPLY_STATUS, 0x1) => {
+                SetPropertyDataReplyV1(ci::SetPropertyDataReply::try_from(buffer)?)
+            }
+            (property_exchange::SET_REPLY_STATUS, 0x2) => {
+                SetPropertyDataReplyV2(ci::SetPropertyDataReply::try_from(buffer)?)
+            }
+            (property_exchange::SUBSCRIPTION_STATUS, 0x1) => {
+                SubscriptionV1(ci::Subscription::try_from(buffer)?)
+            }
+            (property_exchange::SUBSCRIPTION_STATUS, 0x2) => {
+                SubscriptionV2(ci::Subscription::try_from(buffer)?)
+            }
+            (property_exchange::SUBSCRIPTION_REPLY_STATUS, 0x1) => {
+                SubscriptionReplyV1(ci::SubscriptionReply::try_from(buffer)?)
+            }
+            (property_exchange::SUBSCRIPTION_REPLY_STATUS, 0x2) => {
+                SubscriptionReplyV2(ci::SubscriptionReply::try_from(buffer)?)
+            }
+            (property_exchange::NOTIFY_STATUS, 0x1) => NotifyV1(ci::Notify::try_from(buffer)?),
+            (property_exchange::NOTIFY_STATUS, 0x2) => NotifyV2(ci::Notify::try_from(buffer)?),
+            (process_inquiry::CAPABILITIES_STATUS, 0x2) => {
+                ProcessInquiryCapabilitiesV2(ci::ProcessInquiryCapabilities::try_from(buffer)?)
+            }
+            (process_inquiry::CAPABILITIES_REPLY_STATUS, 0x2) => ProcessInquiryCapabilitiesReplyV2(
+                ci::ProcessInquiryCapabilitiesReply::try_from(buffer)?,
+            ),
+            (process_inquiry::MESSAGE_REPORT_STATUS, 0x2) => {
+                MidiMessageReportV2(ci::MidiMessageReport::try_from(buffer)?)
+            }
+            (process_inquiry::MESSAGE_REPORT_REPLY_STATUS, 0x2) => {
+                MidiMessageReportReplyV2(ci::MidiMessageReportReply::try_from(buffer)?)
+            }
+            (process_inquiry::END_OF_MESSAGE_REPORT_STATUS, 0x2) => {
+                EndOfMidiMessageReportV2(ci::EndOfMidiMessageReport::try_from(buffer)?)
+            }
+            _ => Unknown(UnknownMessage::try_from(buffer)?),
+        })
+    }
+}
+
+impl<B: Bytes> core::ops::Deref for CiMessage<B> {
+    type Target = Sysex7<B>;
+    fn deref(&self) -> &Self::Target {
+        use CiMessage::*;
+        match self {
+            DiscoveryQueryV1(m) => m,
+            DiscoveryQueryV2(m) => m,
+            DiscoveryReplyV1(m) => m,
+            DiscoveryReplyV2(m) => m,
+            EndpointInquiryV2(m) => m,
+            EndpointReplyV2(m) => m,
+            InvalidateMuidV1(m) => m,
+            InvalidateMuidV2(m) => m,
+            AckV2(m) => m,
+            NakV1(m) => m,
+            NakV2(m) => m,
+            ProfileInquiryV1(m) => m,
+            ProfileInquiryV2(m) => m,
+            ProfileInquiryReplyV1(m) => m,
+            ProfileInquiryReplyV2(m) => m,
+            SetProfileOnV1(m) => m,
+            SetProfileOnV2(m) => m,
+            SetProfileOffV1(m) => m,
+            SetProfileOffV2(m) => m,
+            ProfileEnabledReportV1(m) => m,
+            ProfileEnabledReportV2(m) => m,
+            ProfileDisabledReportV1(m) => m,
+            ProfileDisabledReportV2(m) => m,
+            ProfileAddedReportV2(m) => m,
+            ProfileRemovedReportV2(m) => m,
+            ProfileDetailsInquiryV2(m) => m,
+            ProfileDetailsReplyV2(m) => m,
+            ProfileSpecificDataV1(m) => m,
+            ProfileSpecificDataV2(m) => m,
+            PropertyExchangeCapabilitiesV1(m) => m,
+            PropertyExchangeCapabilitiesV2(m) => m,
+            PropertyExchangeCapabilitiesReplyV1(m) => m,
+            PropertyExchangeCapabilitiesReplyV2(m) => m,
+            GetPropertyDataV1(m) => m,
+            GetPropertyDataV2(m) => m,
+            GetPropertyDataReplyV1(m) => m,
+            GetPropertyDataReplyV2(m) => m,
+            SetPropertyDataV1(m) => m,
+            SetPropertyDataV2(m) => m,
+            SetPropertyDataReplyV1(m) => m,
+            SetPropertyDataReplyV2(m) => m,
+            SubscriptionV1(m) => m,
+            SubscriptionV2(m) => m,
+            SubscriptionReplyV1(m) => m,
+            SubscriptionReplyV2(m) => m,
+            NotifyV1(m) => m,
+            NotifyV2(m) => m,
+            ProcessInquiryCapabilitiesV2(m) => m,
+            ProcessInquiryCapabilitiesReplyV2(m) => m,
+            MidiMessageReportV2(m) => m,
+            MidiMessageReportReplyV2(m) => m,
+            EndOfMidiMessageReportV2(m) => m,
+            Unknown(m) => m,
+        }
+    }
+}
+
+impl<B: Bytes> CiVersion<0x1> for CiMessage<B> {}
+
+impl<B: Bytes> Ci<B> for CiMessage<B> {
+    fn device_id(&self) -> ci::DeviceId {
+        use CiMessage::*;
+        match self {
+            DiscoveryQueryV1(m) => m.device_id(),
+            DiscoveryQueryV2(m) => m.device_id(),
+            DiscoveryReplyV1(m) => m.device_id(),
+            DiscoveryReplyV2(m) => m.device_id(),
+            EndpointInquiryV2(m) => m.device_id(),
+            EndpointReplyV2(m) => m.device_id(),
+            InvalidateMuidV1(m) => m.device_id(),
+            InvalidateMuidV2(m) => m.device_id(),
+            AckV2(m) => m.device_id(),
+            NakV1(m) => m.device_id(),
+            NakV2(m) => m.device_id(),
+            ProfileInquiryV1(m) => m.device_id(),
+            ProfileInquiryV2(m) => m.device_id(),
+            ProfileInquiryReplyV1(m) => m.device_id(),
+            ProfileInquiryReplyV2(m) => m.device_id(),
+            SetProfileOnV1(m) => m.device_id(),
+            SetProfileOnV2(m) => m.device_id(),
+            SetProfileOffV1(m) => m.device_id(),
+            SetProfileOffV2(m) => m.device_id(),
+            ProfileEnabledReportV1(m) => m.device_id(),
+            ProfileEnabledReportV2(m) => m.device_id(),
+            ProfileDisabledReportV1(m) => m.device_id(),
+            ProfileDisabledReportV2(m) => m.device_id(),
+            ProfileAddedReportV2(m) => m.device_id(),
+            ProfileRemovedReportV2(m) => m.device_id(),
+            ProfileDetailsInquiryV2(m) => m.device_id(),
+            ProfileDetailsReplyV2(m) => m.device_id(),
+            ProfileSpecificDataV1(m) => m.device_id(),
+            ProfileSpecificDataV2(m) => m.device_id(),
+            PropertyExchangeCapabilitiesV1(m) => m.device_id(),
+            PropertyExchangeCapabilitiesV2(m) => m.device_id(),
+            PropertyExchangeCapabilitiesReplyV1(m) => m.device_id(),
+            PropertyExchangeCapabilitiesReplyV2(m) => m.device_id(),
+            GetPropertyDataV1(m) => m.device_id(),
+            GetPropertyDataV2(m) => m.device_id(),
+            GetPropertyDataReplyV1(m) => m.device_id(),
+            GetPropertyDataReplyV2(m) => m.device_id(),
+            SetPropertyDataV1(m) => m.device_id(),
+            SetPropertyDataV2(m) => m.device_id(),
+            SetPropertyDataReplyV1(m) => m.device_id(),
+            SetPropertyDataReplyV2(m) => m.device_id(),
+            SubscriptionV1(m) => m.device_id(),
+            SubscriptionV2(m) => m.device_id(),
+            SubscriptionReplyV1(m) => m.device_id(),
+            SubscriptionReplyV2(m) => m.device_id(),
+            NotifyV1(m) => m.device_id(),
+            NotifyV2(m) => m.device_id(),
+            ProcessInquiryCapabilitiesV2(m) => m.device_id(),
+            ProcessInquiryCapabilitiesReplyV2(m) => m.device_id(),
+            MidiMessageReportV2(m) => m.device_id(),
+            MidiMessageReportReplyV2(m) => m.device_id(),
+            EndOfMidiMessageReportV2(m) => m.device_id(),
+            Unknown(m) => m.device_id(),
+        }
+    }
+    fn source(&self) -> ux::u28 {
+        use CiMessage::*;
+        match self {
+            DiscoveryQueryV1(m) => m.source(),
+            DiscoveryQueryV2(m) => m.source(),
+            DiscoveryReplyV1(m) => m.source(),
+            DiscoveryReplyV2(m) => m.source(),
+            EndpointInquiryV2(m) => m.source(),
+            EndpointReplyV2(m) => m.source(),
+            InvalidateMuidV1(m) => m.source(),
+            InvalidateMuidV2(m) => m.source(),
+            AckV2(m) => m.source(),
+            NakV1(m) => m.source(),
+            NakV2(m) => m.source(),
+            ProfileInquiryV1(m) => m.source(),
+            ProfileInquiryV2(m) => m.source(),
+            ProfileInquiryReplyV1(m) => m.source(),
+            ProfileInquiryReplyV2(m) => m.source(),
+            SetProfileOnV1(m) => m.source(),
+            SetProfileOnV2(m) => m.source(),
+            SetProfileOffV1(m) => m.source(),
+            SetProfileOffV2(m) => m.source(),
+            ProfileEnabledReportV1(m) => m.source(),
+            ProfileEnabledReportV2(m) => m.source(),
+            ProfileDisabledReportV1(m) => m.source(),
+            ProfileDisabledReportV2(m) => m.source(),
+            ProfileAddedReportV2(m) => m.source(),
+            ProfileRemovedReportV2(m) => m.source(),
+            ProfileDetailsInquiryV2(m) => m.source(),
+            ProfileDetailsReplyV2(m) => m.source(),
+            ProfileSpecificDataV1(m) => m.source(),
+            ProfileSpecificDataV2(m) => m.source(),
+            PropertyExchangeCapabilitiesV1(m) => m.source(),
+            PropertyExchangeCapabilitiesV2(m) => m.source(),
+            PropertyExchangeCapabilitiesReplyV1(m) => m.source(),
+            PropertyExchangeCapabilitiesReplyV2(m) => m.source(),
+            GetPropertyDataV1(m) => m.source(),
+            GetPropertyDataV2(m) => m.source(),
+            GetPropertyDataReplyV1(m) => m.source(),
+            GetPropertyDataReplyV2(m) => m.source(),
+            SetPropertyDataV1(m) => m.source(),
+            SetPropertyDataV2(m) => m.source(),
+            SetPropertyDataReplyV1(m) => m.source(),
+            SetPropertyDataReplyV2(m) => m.source(),
+            SubscriptionV1(m) => m.source(),
+            SubscriptionV2(m) => m.source(),
+            SubscriptionReplyV1(m) => m.source(),
+            SubscriptionReplyV2(m) => m.source(),
+            NotifyV1(m) => m.source(),
+            NotifyV2(m) => m.source(),
+            ProcessInquiryCapabilitiesV2(m) => m.source(),
+            ProcessInquiryCapabilitiesReplyV2(m) => m.source(),
+            MidiMessageReportV2(m) => m.source(),
+            MidiMessageReportReplyV2(m) => m.source(),
+            EndOfMidiMessageReportV2(m) => m.source(),
+            Unknown(m) => m.source(),
+        }
+    }
+    fn destination(&self) -> ux::u28 {
+        use CiMessage::*;
+        match self {
+            DiscoveryQueryV1(m) => m.destination(),
+            DiscoveryQueryV2(m) => m.destination(),
+            DiscoveryReplyV1(m) => m.destination(),
+            DiscoveryReplyV2(m) => m.destination(),
+            EndpointInquiryV2(m) => m.destination(),
+            EndpointReplyV2(m) => m.destination(),
+            InvalidateMuidV1(m) => m.destination(),
+            InvalidateMuidV2(m) => m.destination(),
+            AckV2(m) => m.destination(),
+            NakV1(m) => m.destination(),
+            NakV2(m) => m.destination(),
+            ProfileInquiryV1(m) => m.destination(),
+            ProfileInquiryV2(m) => m.destination(),
+            ProfileInquiryReplyV1(m) => m.destination(),
+            ProfileInquiryReplyV2(m) => m.destination(),
+            SetProfileOnV1(m) => m.destination(),
+            SetProfileOnV2(m) => m.destination(),
+            SetProfileOffV1(m) => m.destination(),
+            SetProfileOffV2(m) => m.destination(),
+            ProfileEnabledReportV1(m) => m.destination(),
+            ProfileEnabledReportV2(m) => m.destination(),
+            ProfileDisabledReportV1(m) => m.destination(),
+            ProfileDisabledReportV2(m) => m.destination(),
+            ProfileAddedReportV2(m) => m.destination(),
+            ProfileRemovedReportV2(m) => m.destination(),
+            ProfileDetailsInquiryV2(m) => m.destination(),
+            ProfileDetailsReplyV2(m) => m.destination(),
+            ProfileSpecificDataV1(m) => m.destination(),
+            ProfileSpecificDataV2(m) => m.destination(),
+            PropertyExchangeCapabilitiesV1(m) => m.destination(),
+            PropertyExchangeCapabilitiesV2(m) => m.destination(),
+            PropertyExchangeCapabilitiesReplyV1(m) => m.destination(),
+            PropertyExchangeCapabilitiesReplyV2(m) => m.destination(),
+            GetPropertyDataV1(m) => m.destination(),
+            GetPropertyDataV2(m) => m.destination(),
+            GetPropertyDataReplyV1(m) => m.destination(),
+            GetPropertyDataReplyV2(m) => m.destination(),
+            SetPropertyDataV1(m) => m.destination(),
+            SetPropertyDataV2(m) => m.destination(),
+            SetPropertyDataReplyV1(m) => m.destination(),
+            SetPropertyDataReplyV2(m) => m.destination(),
+            SubscriptionV1(m) => m.destination(),
+            SubscriptionV2(m) => m.destination(),
+            SubscriptionReplyV1(m) => m.destination(),
+            SubscriptionReplyV2(m) => m.destination(),
+            NotifyV1(m) => m.destination(),
+            NotifyV2(m) => m.destination(),
+            ProcessInquiryCapabilitiesV2(m) => m.destination(),
+            ProcessInquiryCapabilitiesReplyV2(m) => m.destination(),
+            MidiMessageReportV2(m) => m.destination(),
+            MidiMessageReportReplyV2(m) => m.destination(),
+            EndOfMidiMessageReportV2(m) => m.destination(),
+            Unknown(m) => m.destination(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn discovery_query_v1() {
+        let query = ci::DiscoveryQuery::<0x1, std::vec::Vec<u8>>::new();
+        assert!(matches!(
+            CiMessage::try_from(query.data()),
+            Ok(CiMessage::DiscoveryQueryV1(_))
+        ));
+    }
+
+    #[test]
+    fn discovery_query_v2() {
+        let query = ci::DiscoveryQuery::<0x2, std::vec::Vec<u8>>::new();
+        assert!(matches!(
+            CiMessage::try_from(query.data()),
+            Ok(CiMessage::DiscoveryQueryV2(_))
+        ));
+    }
+
+    #[test]
+    fn set_profile_on() {
+        let mut message = ci::SetProfileOn::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Channel(ux::u4::new(0x4)));
+        let parsed = CiMessage::try_from(message.data()).unwrap();
+        assert!(matches!(parsed, CiMessage::SetProfileOnV2(_)));
+        assert_eq!(parsed.device_id(), ci::DeviceId::Channel(ux::u4::new(0x4)));
+    }
+
+    #[test]
+    fn get_property_data() {
+        let mut message = ci::GetPropertyData::<0x1, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        let parsed = CiMessage::try_from(message.data()).unwrap();
+        assert!(matches!(parsed, CiMessage::GetPropertyDataV1(_)));
+        assert_eq!(parsed.source(), ux::u28::new(0x1234567));
+        assert_eq!(parsed.destination(), ux::u28::new(0xB48D9D9));
+        assert_eq!(parsed.data(), message.data());
+    }
+
+    #[test]
+    fn unknown_status() {
+        let buffer = [
+            0xF0, 0x7E, 0x7E, 0x0D, 0x10, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+            0x01, 0x02, 0xF7,
+        ];
+        let parsed = CiMessage::try_from(&buffer[..]).unwrap();
+        let CiMessage::Unknown(unknown) = &parsed else {
+            panic!("Expected an unknown message");
+        };
+        assert_eq!(unknown.status(), 0x10);
+        assert_eq!(unknown.version(), 0x02);
+        assert_eq!(parsed.device_id(), ci::DeviceId::Group);
+        assert_eq!(parsed.source(), ux::u28::new(0x1234567));
+        assert_eq!(parsed.destination(), ux::u28::new(0xB48D9D9));
+        assert_eq!(parsed.data(), &buffer[..]);
+    }
+
+    #[test]
+    fn newer_version() {
+        let mut query = ci::DiscoveryQuery::<0x2, std::vec::Vec<u8>>::new();
+        query.set_source(ux::u28::new(0x1234567));
+        let mut buffer = query.data().to_vec();
+        buffer[VERSION_INDEX] = 0x3;
+        let Ok(CiMessage::DiscoveryQueryV2(parsed)) = CiMessage::try_from(&buffer[..]) else {
+            panic!("Expected a version 2 discovery query");
+        };
+        assert_eq!(parsed.data()[VERSION_INDEX], 0x3);
+        assert_eq!(parsed.source(), ux::u28::new(0x1234567));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut buffer = ci::DiscoveryQuery::<0x2, std::vec::Vec<u8>>::new()
+            .data()
+            .to_vec();
+        buffer[VERSION_INDEX] = 0x0;
+        assert!(matches!(
+            CiMessage::try_from(&buffer[..]),
+            Ok(CiMessage::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn message_not_defined_for_version() {
+        let mut buffer = ci::EndpointInquiry::<0x2, std::vec::Vec<u8>>::new()
+            .data()
+            .to_vec();
+        buffer[VERSION_INDEX] = 0x1;
+        assert!(matches!(
+            CiMessage::try_from(&buffer[..]),
+            Ok(CiMessage::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn malformed_message() {
+        let mut buffer = ci::DiscoveryQuery::<0x2, std::vec::Vec<u8>>::new()
+            .data()
+            .to_vec();
+        // device id must address the function block
+        buffer[2] = 0x01;
+        assert!(CiMessage::try_from(&buffer[..]).is_err());
+    }
+
+    #[test]
+    fn not_ci() {
+        assert_eq!(
+            CiMessage::try_from(
+                &[
+                    0xF0, 0x7E, 0x7F, 0x06, 0x01, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                    0xF7,
+                ][..]
+            ),
+            Err(crate::error::InvalidData(ERR_NOT_CI))
+        );
+    }
+
+    #[test]
+    fn too_short() {
+        assert!(CiMessage::try_from(&[0xF0, 0x7E, 0x7F, 0x0D, 0x70, 0x02, 0xF7][..]).is_err());
+    }
+
+    #[test]
+    fn unknown_too_short() {
+        assert!(CiMessage::try_from(
+            &[0xF0, 0x7E, 0x7F, 0x0D, 0x10, 0x02, 0x0, 0x0, 0xF7, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0][..]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn deref_to_sysex7() {
+        let query = ci::DiscoveryQuery::<0x2, std::vec::Vec<u8>>::new();
+        let parsed = CiMessage::try_from(query.data()).unwrap();
+        let ump: Sysex7<std::vec::Vec<u32>> = ci::to_ump(&parsed, ux::u4::new(0x1));
+        let expected: Sysex7<std::vec::Vec<u32>> = ci::to_ump(&query, ux::u4::new(0x1));
+        assert_eq!(ump, expected);
+    }
+}
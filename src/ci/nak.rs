@@ -0,0 +1,167 @@
+use crate::ci;
+
+pub(crate) const STATUS: u8 = 0x7F;
+
+/// Negatively acknowledge a MIDI-CI message.
+///
+/// Version 1 of MIDI-CI defines the NAK without any data.
+/// Version 2 adds the Universal Sysex Sub ID #2 of the original transaction,
+/// a status code, status data and five bytes of details,
+/// optionally followed by a human readable message.
+#[midi2_proc::generate_ci(
+    SupportedVersion(version = 0x1, min_size = 15),
+    SupportedVersion(version = 0x2, min_size = 25)
+)]
+struct Nak {
+    #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+    #[version(0x1)]
+    universal_sysex_byte: (),
+    #[property(ci::common_properties::DeviceIdProperty)]
+    #[version(0x1)]
+    device_id: ci::DeviceId,
+    #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+    #[version(0x1)]
+    sysex_sub_id1_ci: (),
+    #[property(ci::common_properties::UniversalSysexSubId2<STATUS>)]
+    #[version(0x1)]
+    sysex_sub_id2_ci: (),
+    #[property(ci::common_properties::SourceProperty)]
+    #[version(0x1)]
+    source: ux::u28,
+    #[property(ci::common_properties::DestinationProperty)]
+    #[version(0x1)]
+    destination: ux::u28,
+    #[property(ci::common_properties::U7Property<14>)]
+    #[version(0x2)]
+    original_transaction: ux::u7,
+    #[property(ci::common_properties::U7Property<15>)]
+    #[version(0x2)]
+    status_code: ux::u7,
+    #[property(ci::common_properties::U7Property<16>)]
+    #[version(0x2)]
+    status_data: ux::u7,
+    #[property(ci::common_properties::U7ArrayProperty<17, 5>)]
+    #[version(0x2)]
+    details: [ux::u7; 5],
+    #[property(ci::common_properties::TrailingDataWriteProperty<22>)]
+    #[version(0x2)]
+    #[writeonly]
+    #[resize]
+    message_text: &[ux::u7],
+    #[property(ci::common_properties::TrailingDataReadProperty<22>)]
+    #[version(0x2)]
+    #[readonly]
+    message_text: &[u8],
+}
+
+/// Status codes of a version 2 [Nak].
+pub mod nak_status {
+    pub const NAK: u8 = 0x00;
+    pub const MESSAGE_NOT_SUPPORTED: u8 = 0x01;
+    pub const CI_VERSION_NOT_SUPPORTED: u8 = 0x02;
+    pub const CHANNEL_GROUP_OR_BLOCK_NOT_IN_USE: u8 = 0x03;
+    pub const PROFILE_NOT_SUPPORTED_ON_TARGET: u8 = 0x04;
+    pub const TERMINATE_INQUIRY: u8 = 0x20;
+    pub const PROPERTY_EXCHANGE_CHUNKS_OUT_OF_SEQUENCE: u8 = 0x21;
+    pub const ERROR_RETRY_SUGGESTED: u8 = 0x40;
+    pub const MALFORMED_MESSAGE: u8 = 0x41;
+    pub const TIMEOUT: u8 = 0x42;
+    pub const TIMEOUT_RETRY_SUGGESTED: u8 = 0x43;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn new_v1() {
+        let message = Nak::<0x1, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[0xF0, 0x7E, 0x0, 0x0D, 0x7F, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,]
+        );
+    }
+
+    #[test]
+    fn try_new_v1() {
+        let message = Nak::<0x1, [u8; 15]>::try_new().expect("Buffer is large enough");
+        assert_eq!(
+            message.data(),
+            &[0xF0, 0x7E, 0x0, 0x0D, 0x7F, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,]
+        );
+    }
+
+    #[test]
+    fn new_v2() {
+        let message = Nak::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x7F, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn setters_v2() {
+        let mut message = Nak::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Channel(ux::u4::new(0x3)));
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        message.set_original_transaction(ux::u7::new(0x70));
+        message.set_status_code(ux::u7::new(nak_status::CI_VERSION_NOT_SUPPORTED));
+        message.set_message_text(&[ux::u7::new(0x4E), ux::u7::new(0x6F)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x03, 0x0D, 0x7F, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0x70, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x4E, 0x6F, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn getters_v2() {
+        let message = Nak::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x03, 0x0D, 0x7F, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0x70, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x4E, 0x6F, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.original_transaction(), ux::u7::new(0x70));
+        assert_eq!(
+            message.status_code(),
+            ux::u7::new(nak_status::CI_VERSION_NOT_SUPPORTED)
+        );
+        assert_eq!(message.message_text(), b"No");
+    }
+
+    #[test]
+    fn try_from_slice_v1() {
+        use crate::ci::Ci;
+        let message = Nak::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7F, 0x01, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.device_id(), ci::DeviceId::FunctionBlock);
+        assert_eq!(message.destination(), ux::u28::new(0xB48D9D9));
+    }
+
+    #[test]
+    fn try_from_slice_too_short() {
+        assert!(Nak::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x7F, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
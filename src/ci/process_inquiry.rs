@@ -0,0 +1,559 @@
+use crate::{
+    buffer::{BufferMut, Bytes},
+    ci,
+    detail::property,
+};
+
+#[cfg(feature = "channel-voice2")]
+mod report;
+
+#[cfg(feature = "channel-voice2")]
+pub use report::*;
+
+pub(crate) const CAPABILITIES_STATUS: u8 = 0x40;
+pub(crate) const CAPABILITIES_REPLY_STATUS: u8 = 0x41;
+pub(crate) const MESSAGE_REPORT_STATUS: u8 = 0x42;
+pub(crate) const MESSAGE_REPORT_REPLY_STATUS: u8 = 0x43;
+pub(crate) const END_OF_MESSAGE_REPORT_STATUS: u8 = 0x44;
+
+const ERR_INVALID_MESSAGE_DATA_CONTROL: &str = "Expected message data control: 0x00, 0x01 or 0x7F";
+
+// conversion between the flag types and their bitmap byte in the message
+trait Bitmap: Copy + Default {
+    fn from_bits(bits: u8) -> Self;
+    fn to_bits(&self) -> u8;
+}
+
+fn bit(bits: u8, index: u8) -> bool {
+    bits & (1 << index) != 0
+}
+
+fn bits(flags: &[bool]) -> u8 {
+    flags
+        .iter()
+        .enumerate()
+        .fold(0, |acc, (i, flag)| acc | (u8::from(*flag) << i))
+}
+
+/// The process inquiry features supported by a device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ProcessInquiryFeatures {
+    pub midi_message_report: bool,
+}
+
+impl Bitmap for ProcessInquiryFeatures {
+    fn from_bits(bits: u8) -> Self {
+        ProcessInquiryFeatures {
+            midi_message_report: bit(bits, 0),
+        }
+    }
+    fn to_bits(&self) -> u8 {
+        bits(&[self.midi_message_report])
+    }
+}
+
+/// System messages requested in, or reported by, a MIDI message report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SystemMessages {
+    pub mtc_quarter_frame: bool,
+    pub song_position: bool,
+    pub song_select: bool,
+}
+
+impl Bitmap for SystemMessages {
+    fn from_bits(bits: u8) -> Self {
+        SystemMessages {
+            mtc_quarter_frame: bit(bits, 0),
+            song_position: bit(bits, 1),
+            song_select: bit(bits, 2),
+        }
+    }
+    fn to_bits(&self) -> u8 {
+        bits(&[self.mtc_quarter_frame, self.song_position, self.song_select])
+    }
+}
+
+/// Channel controller messages requested in, or reported by, a MIDI message report.
+///
+/// Registered and assignable controllers correspond to the
+/// registered and non-registered parameter numbers of MIDI 1.0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ChannelControllerMessages {
+    pub pitch_bend: bool,
+    pub control_change: bool,
+    pub registered_controller: bool,
+    pub assignable_controller: bool,
+    pub program_change: bool,
+    pub channel_pressure: bool,
+}
+
+impl Bitmap for ChannelControllerMessages {
+    fn from_bits(bits: u8) -> Self {
+        ChannelControllerMessages {
+            pitch_bend: bit(bits, 0),
+            control_change: bit(bits, 1),
+            registered_controller: bit(bits, 2),
+            assignable_controller: bit(bits, 3),
+            program_change: bit(bits, 4),
+            channel_pressure: bit(bits, 5),
+        }
+    }
+    fn to_bits(&self) -> u8 {
+        bits(&[
+            self.pitch_bend,
+            self.control_change,
+            self.registered_controller,
+            self.assignable_controller,
+            self.program_change,
+            self.channel_pressure,
+        ])
+    }
+}
+
+/// Note data messages requested in, or reported by, a MIDI message report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NoteDataMessages {
+    pub notes: bool,
+    pub key_pressure: bool,
+    pub per_note_pitch_bend: bool,
+    pub registered_per_note_controller: bool,
+    pub assignable_per_note_controller: bool,
+}
+
+impl Bitmap for NoteDataMessages {
+    fn from_bits(bits: u8) -> Self {
+        NoteDataMessages {
+            notes: bit(bits, 0),
+            key_pressure: bit(bits, 1),
+            per_note_pitch_bend: bit(bits, 2),
+            registered_per_note_controller: bit(bits, 3),
+            assignable_per_note_controller: bit(bits, 4),
+        }
+    }
+    fn to_bits(&self) -> u8 {
+        bits(&[
+            self.notes,
+            self.key_pressure,
+            self.per_note_pitch_bend,
+            self.registered_per_note_controller,
+            self.assignable_per_note_controller,
+        ])
+    }
+}
+
+/// Which values a MIDI message report should contain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MessageDataControl {
+    /// No values are reported.
+    NoData,
+    /// Only values which differ from their default are reported.
+    #[default]
+    OnlyNonDefault,
+    /// All values are reported.
+    Full,
+}
+
+impl MessageDataControl {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x00 => Some(MessageDataControl::NoData),
+            0x01 => Some(MessageDataControl::OnlyNonDefault),
+            0x7F => Some(MessageDataControl::Full),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            MessageDataControl::NoData => 0x00,
+            MessageDataControl::OnlyNonDefault => 0x01,
+            MessageDataControl::Full => 0x7F,
+        }
+    }
+}
+
+struct BitmapProperty<const INDEX: usize, T: Bitmap>(core::marker::PhantomData<T>);
+
+impl<const INDEX: usize, T: Bitmap, B: Bytes> property::Property<B> for BitmapProperty<INDEX, T> {
+    type Type = T;
+}
+
+impl<'a, const INDEX: usize, T: Bitmap, B: Bytes> property::ReadProperty<'a, B>
+    for BitmapProperty<INDEX, T>
+{
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        T::from_bits(buffer.buffer()[INDEX])
+    }
+}
+
+impl<const INDEX: usize, T: Bitmap, B: Bytes + BufferMut> property::WriteProperty<B>
+    for BitmapProperty<INDEX, T>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        buffer.buffer_mut()[INDEX] = v.to_bits();
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+struct MessageDataControlProperty<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for MessageDataControlProperty<INDEX> {
+    type Type = MessageDataControl;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B>
+    for MessageDataControlProperty<INDEX>
+{
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        MessageDataControl::from_byte(buffer.buffer()[INDEX])
+            .map(|_| ())
+            .ok_or(crate::error::InvalidData(ERR_INVALID_MESSAGE_DATA_CONTROL))
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        MessageDataControl::from_byte(buffer.buffer()[INDEX])
+            .expect("Message data control should be valid")
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for MessageDataControlProperty<INDEX>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        buffer.buffer_mut()[INDEX] = v.to_byte();
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+pub use capabilities::ProcessInquiryCapabilities;
+pub use capabilities_reply::ProcessInquiryCapabilitiesReply;
+pub use end_of_message_report::EndOfMidiMessageReport;
+pub use message_report::MidiMessageReport;
+pub use message_report_reply::MidiMessageReportReply;
+
+mod capabilities {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 15))]
+    struct ProcessInquiryCapabilities {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x2)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<CAPABILITIES_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+    }
+}
+
+mod capabilities_reply {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 16))]
+    struct ProcessInquiryCapabilitiesReply {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x2)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<CAPABILITIES_REPLY_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+        #[property(BitmapProperty<14, ProcessInquiryFeatures>)]
+        #[version(0x2)]
+        supported_features: ProcessInquiryFeatures,
+    }
+}
+
+mod message_report {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 20))]
+    struct MidiMessageReport {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x2)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<MESSAGE_REPORT_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+        #[property(MessageDataControlProperty<14>)]
+        #[version(0x2)]
+        message_data_control: MessageDataControl,
+        #[property(BitmapProperty<15, SystemMessages>)]
+        #[version(0x2)]
+        system_messages: SystemMessages,
+        #[property(BitmapProperty<17, ChannelControllerMessages>)]
+        #[version(0x2)]
+        channel_controller_messages: ChannelControllerMessages,
+        #[property(BitmapProperty<18, NoteDataMessages>)]
+        #[version(0x2)]
+        note_data_messages: NoteDataMessages,
+    }
+}
+
+mod message_report_reply {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 19))]
+    struct MidiMessageReportReply {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x2)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<MESSAGE_REPORT_REPLY_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+        #[property(BitmapProperty<14, SystemMessages>)]
+        #[version(0x2)]
+        system_messages: SystemMessages,
+        #[property(BitmapProperty<16, ChannelControllerMessages>)]
+        #[version(0x2)]
+        channel_controller_messages: ChannelControllerMessages,
+        #[property(BitmapProperty<17, NoteDataMessages>)]
+        #[version(0x2)]
+        note_data_messages: NoteDataMessages,
+    }
+}
+
+mod end_of_message_report {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 15))]
+    struct EndOfMidiMessageReport {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x2)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<END_OF_MESSAGE_REPORT_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn capabilities() {
+        let mut message = ProcessInquiryCapabilities::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x40, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn capabilities_reply() {
+        let mut message = ProcessInquiryCapabilitiesReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_supported_features(ProcessInquiryFeatures {
+            midi_message_report: true,
+        });
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x41, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01,
+                0xF7,
+            ]
+        );
+        assert!(message.supported_features().midi_message_report);
+    }
+
+    #[test]
+    fn message_report() {
+        let mut message = MidiMessageReport::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Channel(ux::u4::new(0x3)));
+        message.set_message_data_control(MessageDataControl::Full);
+        message.set_system_messages(SystemMessages {
+            song_position: true,
+            ..Default::default()
+        });
+        message.set_channel_controller_messages(ChannelControllerMessages {
+            pitch_bend: true,
+            control_change: true,
+            program_change: true,
+            ..Default::default()
+        });
+        message.set_note_data_messages(NoteDataMessages {
+            notes: true,
+            per_note_pitch_bend: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x03, 0x0D, 0x42, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7F,
+                0x02, 0x00, 0x13, 0x05, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn message_report_default_data_control() {
+        let message = MidiMessageReport::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.message_data_control(),
+            MessageDataControl::OnlyNonDefault
+        );
+    }
+
+    #[test]
+    fn message_report_try_from_slice() {
+        let message = MidiMessageReport::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x42, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x00,
+                0x07, 0x00, 0x3F, 0x1F, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.message_data_control(), MessageDataControl::NoData);
+        assert_eq!(
+            message.system_messages(),
+            SystemMessages {
+                mtc_quarter_frame: true,
+                song_position: true,
+                song_select: true,
+            }
+        );
+        assert_eq!(
+            message.channel_controller_messages(),
+            ChannelControllerMessages {
+                pitch_bend: true,
+                control_change: true,
+                registered_controller: true,
+                assignable_controller: true,
+                program_change: true,
+                channel_pressure: true,
+            }
+        );
+        assert_eq!(
+            message.note_data_messages(),
+            NoteDataMessages {
+                notes: true,
+                key_pressure: true,
+                per_note_pitch_bend: true,
+                registered_per_note_controller: true,
+                assignable_per_note_controller: true,
+            }
+        );
+    }
+
+    #[test]
+    fn message_report_invalid_data_control() {
+        assert!(MidiMessageReport::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x42, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x02,
+                0x00, 0x00, 0x00, 0x00, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn message_report_reply() {
+        let mut message = MidiMessageReportReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_system_messages(SystemMessages {
+            mtc_quarter_frame: true,
+            ..Default::default()
+        });
+        message.set_channel_controller_messages(ChannelControllerMessages {
+            channel_pressure: true,
+            ..Default::default()
+        });
+        message.set_note_data_messages(NoteDataMessages {
+            key_pressure: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x43, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01,
+                0x00, 0x20, 0x02, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn end_of_message_report() {
+        let mut message = EndOfMidiMessageReport::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Group);
+        assert_eq!(
+            message.data(),
+            &[0xF0, 0x7E, 0x7E, 0x0D, 0x44, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0xF7,]
+        );
+    }
+}
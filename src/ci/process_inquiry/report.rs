@@ -0,0 +1,554 @@
+use crate::{
+    channel_voice2::{self, ChannelVoice2},
+    ci::{ChannelControllerMessages, MessageDataControl, NoteDataMessages},
+    ux::{u14, u4, u7},
+    Channeled, Grouped,
+};
+
+const NUMBER_OF_CONTROLS: usize = 128;
+const DEFAULT_PRESSURE: u32 = 0x0;
+const DEFAULT_PITCH_BEND: u32 = 0x8000_0000;
+
+/// The value of a registered or assignable controller.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ControllerValue {
+    pub bank: u7,
+    pub index: u7,
+    pub value: u32,
+}
+
+/// The state of a sounding note.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NoteValue {
+    pub note_number: u7,
+    pub velocity: u16,
+    pub key_pressure: Option<u32>,
+    pub pitch_bend: Option<u32>,
+}
+
+/// The state of a single channel to be reported in reply to a
+/// [MidiMessageReport](crate::ci::MidiMessageReport) inquiry.
+///
+/// Values are held at MIDI 2.0 resolution.
+/// Values left as `None` are taken to be at their default. They are not reported,
+/// unless a [Full](MessageDataControl::Full) report asks for them and their default
+/// is known (see [MessageReport]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelReportState<'a> {
+    pub group: u4,
+    pub channel: u4,
+    pub program: Option<u7>,
+    pub bank: Option<u14>,
+    pub control_changes: [Option<u32>; NUMBER_OF_CONTROLS],
+    pub registered_controllers: &'a [ControllerValue],
+    pub assignable_controllers: &'a [ControllerValue],
+    pub channel_pressure: Option<u32>,
+    pub pitch_bend: Option<u32>,
+    pub notes: &'a [NoteValue],
+}
+
+impl<'a> core::default::Default for ChannelReportState<'a> {
+    fn default() -> Self {
+        ChannelReportState {
+            group: Default::default(),
+            channel: Default::default(),
+            program: None,
+            bank: None,
+            control_changes: [None; NUMBER_OF_CONTROLS],
+            registered_controllers: &[],
+            assignable_controllers: &[],
+            channel_pressure: None,
+            pitch_bend: None,
+            notes: &[],
+        }
+    }
+}
+
+/// Generates the channel voice messages which report the state of a channel
+/// in reply to a [MidiMessageReport](crate::ci::MidiMessageReport) inquiry.
+///
+/// Only the message types requested by the inquiry are generated, in the order:
+/// program change, control changes, registered controllers, assignable controllers,
+/// channel pressure, pitch bend and finally the notes with their
+/// key pressure and per note pitch bend.
+/// Per note controllers are not reported.
+///
+/// A [Full](MessageDataControl::Full) report also includes the values which the state
+/// leaves at their default: channel pressure (0), pitch bend (0x8000_0000) and the key
+/// pressure (0) and per note pitch bend (0x8000_0000) of each note.
+/// Other reports leave these values out whenever they are at their default,
+/// including when the state holds the default explicitly.
+/// Program, control changes and controllers have no default which applies to every
+/// device, so they are only reported when the state holds them, whatever the
+/// requested data control.
+///
+/// The messages are MIDI 2.0 channel voice messages. Receivers using the MIDI 1.0
+/// protocol can be served by translating them with the
+/// [translation](crate::translation) module.
+///
+/// ```rust
+/// use midi2::{prelude::*, ci};
+///
+/// let mut state = ci::ChannelReportState::default();
+/// state.channel = u4::new(0x2);
+/// state.program = Some(u7::new(0x05));
+/// state.control_changes[7] = Some(0x8000_0000);
+///
+/// let mut inquiry = ci::MidiMessageReport::<0x2, Vec<u8>>::new();
+/// inquiry.set_channel_controller_messages(ci::ChannelControllerMessages {
+///     control_change: true,
+///     program_change: true,
+///     ..Default::default()
+/// });
+///
+/// let report = ci::MessageReport::new(
+///     &state,
+///     inquiry.message_data_control(),
+///     inquiry.channel_controller_messages(),
+///     inquiry.note_data_messages(),
+/// );
+/// let messages = report.collect::<Vec<_>>();
+///
+/// assert_eq!(messages.len(), 2);
+/// assert_eq!(messages[0].data(), &[0x40C2_0000, 0x0500_0000]);
+/// assert_eq!(messages[1].data(), &[0x40B2_0700, 0x8000_0000]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageReport<'a> {
+    state: &'a ChannelReportState<'a>,
+    channel_controllers: ChannelControllerMessages,
+    note_data: NoteDataMessages,
+    full: bool,
+    position: usize,
+}
+
+impl<'a> MessageReport<'a> {
+    pub fn new(
+        state: &'a ChannelReportState<'a>,
+        data_control: MessageDataControl,
+        channel_controllers: ChannelControllerMessages,
+        note_data: NoteDataMessages,
+    ) -> Self {
+        MessageReport {
+            state,
+            channel_controllers,
+            note_data,
+            full: data_control == MessageDataControl::Full,
+            position: match data_control {
+                MessageDataControl::NoData => usize::MAX,
+                _ => 0,
+            },
+        }
+    }
+
+    // the report is laid out as a sequence of positions,
+    // each of which may or may not produce a message
+    fn message_at(&self, position: usize) -> Option<ChannelVoice2<[u32; 4]>> {
+        let state = self.state;
+        let controllers = &self.channel_controllers;
+
+        let mut position = position;
+        if position == 0 {
+            return state
+                .program
+                .filter(|_| controllers.program_change)
+                .map(|program| self.program_change(program));
+        }
+        position -= 1;
+
+        if position < NUMBER_OF_CONTROLS {
+            return state.control_changes[position]
+                .filter(|_| controllers.control_change)
+                .map(|value| self.control_change(u7::new(position as u8), value));
+        }
+        position -= NUMBER_OF_CONTROLS;
+
+        if position < state.registered_controllers.len() {
+            return Some(state.registered_controllers[position])
+                .filter(|_| controllers.registered_controller)
+                .map(|value| self.registered_controller(value));
+        }
+        position -= state.registered_controllers.len();
+
+        if position < state.assignable_controllers.len() {
+            return Some(state.assignable_controllers[position])
+                .filter(|_| controllers.assignable_controller)
+                .map(|value| self.assignable_controller(value));
+        }
+        position -= state.assignable_controllers.len();
+
+        if position == 0 {
+            return self
+                .or_default(state.channel_pressure, DEFAULT_PRESSURE)
+                .filter(|_| controllers.channel_pressure)
+                .map(|value| self.channel_pressure(value));
+        }
+        position -= 1;
+
+        if position == 0 {
+            return self
+                .or_default(state.pitch_bend, DEFAULT_PITCH_BEND)
+                .filter(|_| controllers.pitch_bend)
+                .map(|value| self.pitch_bend(value));
+        }
+        position -= 1;
+
+        let note = state.notes[position / 3];
+        match position % 3 {
+            0 => Some(note)
+                .filter(|_| self.note_data.notes)
+                .map(|note| self.note_on(note)),
+            1 => self
+                .or_default(note.key_pressure, DEFAULT_PRESSURE)
+                .filter(|_| self.note_data.key_pressure)
+                .map(|value| self.key_pressure(note.note_number, value)),
+            _ => self
+                .or_default(note.pitch_bend, DEFAULT_PITCH_BEND)
+                .filter(|_| self.note_data.per_note_pitch_bend)
+                .map(|value| self.per_note_pitch_bend(note.note_number, value)),
+        }
+    }
+
+    // full reports fill in the values left at their default,
+    // other reports leave out every value equal to its default
+    fn or_default(&self, value: Option<u32>, default: u32) -> Option<u32> {
+        if self.full {
+            Some(value.unwrap_or(default))
+        } else {
+            value.filter(|value| *value != default)
+        }
+    }
+
+    fn len(&self) -> usize {
+        let state = self.state;
+        1 + NUMBER_OF_CONTROLS
+            + state.registered_controllers.len()
+            + state.assignable_controllers.len()
+            + 2
+            + 3 * state.notes.len()
+    }
+
+    fn program_change(&self, program: u7) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::ProgramChange::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_program(program);
+        message.set_bank(self.state.bank);
+        message.into()
+    }
+
+    fn control_change(&self, control: u7, value: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::ControlChange::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_control(control);
+        message.set_control_change_data(value);
+        message.into()
+    }
+
+    fn registered_controller(&self, value: ControllerValue) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::RegisteredController::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_bank(value.bank);
+        message.set_index(value.index);
+        message.set_controller_data(value.value);
+        message.into()
+    }
+
+    fn assignable_controller(&self, value: ControllerValue) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::AssignableController::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_bank(value.bank);
+        message.set_index(value.index);
+        message.set_controller_data(value.value);
+        message.into()
+    }
+
+    fn channel_pressure(&self, value: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::ChannelPressure::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_channel_pressure_data(value);
+        message.into()
+    }
+
+    fn pitch_bend(&self, value: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::ChannelPitchBend::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_pitch_bend_data(value);
+        message.into()
+    }
+
+    fn note_on(&self, note: NoteValue) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::NoteOn::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_note_number(note.note_number);
+        message.set_velocity(note.velocity);
+        message.into()
+    }
+
+    fn key_pressure(&self, note_number: u7, value: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::KeyPressure::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_note_number(note_number);
+        message.set_key_pressure_data(value);
+        message.into()
+    }
+
+    fn per_note_pitch_bend(&self, note_number: u7, value: u32) -> ChannelVoice2<[u32; 4]> {
+        let mut message = channel_voice2::PerNotePitchBend::<[u32; 4]>::new();
+        message.set_group(self.state.group);
+        message.set_channel(self.state.channel);
+        message.set_note_number(note_number);
+        message.set_pitch_bend_data(value);
+        message.into()
+    }
+}
+
+impl<'a> core::iter::Iterator for MessageReport<'a> {
+    type Item = ChannelVoice2<[u32; 4]>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.len() {
+            let position = self.position;
+            self.position += 1;
+            if let Some(message) = self.message_at(position) {
+                return Some(message);
+            }
+        }
+        None
+    }
+}
+
+impl<'a> core::iter::FusedIterator for MessageReport<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    const ALL_CONTROLLERS: ChannelControllerMessages = ChannelControllerMessages {
+        pitch_bend: true,
+        control_change: true,
+        registered_controller: true,
+        assignable_controller: true,
+        program_change: true,
+        channel_pressure: true,
+    };
+
+    const ALL_NOTE_DATA: NoteDataMessages = NoteDataMessages {
+        notes: true,
+        key_pressure: true,
+        per_note_pitch_bend: true,
+        registered_per_note_controller: true,
+        assignable_per_note_controller: true,
+    };
+
+    fn report(
+        state: &ChannelReportState,
+        data_control: MessageDataControl,
+        channel_controllers: ChannelControllerMessages,
+        note_data: NoteDataMessages,
+    ) -> std::vec::Vec<std::vec::Vec<u32>> {
+        MessageReport::new(state, data_control, channel_controllers, note_data)
+            .map(|m| m.data().to_vec())
+            .collect()
+    }
+
+    fn full_state<'a>(
+        registered: &'a [ControllerValue],
+        notes: &'a [NoteValue],
+    ) -> ChannelReportState<'a> {
+        let mut state = ChannelReportState {
+            group: u4::new(0x1),
+            channel: u4::new(0x3),
+            program: Some(u7::new(0x10)),
+            bank: Some(u14::new(0x0081)),
+            registered_controllers: registered,
+            channel_pressure: Some(0x1234_5678),
+            pitch_bend: Some(0x9000_0000),
+            notes,
+            ..Default::default()
+        };
+        state.control_changes[1] = Some(0x4000_0000);
+        state.control_changes[74] = Some(0xFFFF_FFFF);
+        state
+    }
+
+    #[test]
+    fn empty_state() {
+        let state = ChannelReportState::default();
+        assert!(report(
+            &state,
+            MessageDataControl::OnlyNonDefault,
+            ALL_CONTROLLERS,
+            ALL_NOTE_DATA
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn empty_state_full_report() {
+        let state = ChannelReportState::default();
+        assert_eq!(
+            report(
+                &state,
+                MessageDataControl::Full,
+                ALL_CONTROLLERS,
+                ALL_NOTE_DATA
+            ),
+            std::vec![
+                std::vec![0x40D0_0000, 0x0000_0000],
+                std::vec![0x40E0_0000, 0x8000_0000],
+            ]
+        );
+    }
+
+    #[test]
+    fn full_report_includes_defaults() {
+        let notes = [NoteValue {
+            note_number: u7::new(0x3C),
+            velocity: 0xFFFF,
+            key_pressure: None,
+            pitch_bend: None,
+        }];
+        let state = ChannelReportState {
+            group: u4::new(0x1),
+            channel: u4::new(0x3),
+            notes: &notes,
+            ..Default::default()
+        };
+        assert_eq!(
+            report(
+                &state,
+                MessageDataControl::OnlyNonDefault,
+                ALL_CONTROLLERS,
+                ALL_NOTE_DATA
+            ),
+            std::vec![std::vec![0x4193_3C00, 0xFFFF_0000]]
+        );
+        assert_eq!(
+            report(
+                &state,
+                MessageDataControl::Full,
+                ALL_CONTROLLERS,
+                ALL_NOTE_DATA
+            ),
+            std::vec![
+                std::vec![0x41D3_0000, 0x0000_0000],
+                std::vec![0x41E3_0000, 0x8000_0000],
+                std::vec![0x4193_3C00, 0xFFFF_0000],
+                std::vec![0x41A3_3C00, 0x0000_0000],
+                std::vec![0x4163_3C00, 0x8000_0000],
+            ]
+        );
+    }
+
+    #[test]
+    fn only_non_default_report_omits_explicit_defaults() {
+        let notes = [NoteValue {
+            note_number: u7::new(0x3C),
+            velocity: 0xFFFF,
+            key_pressure: Some(DEFAULT_PRESSURE),
+            pitch_bend: Some(DEFAULT_PITCH_BEND),
+        }];
+        let state = ChannelReportState {
+            group: u4::new(0x1),
+            channel: u4::new(0x3),
+            channel_pressure: Some(DEFAULT_PRESSURE),
+            pitch_bend: Some(DEFAULT_PITCH_BEND),
+            notes: &notes,
+            ..Default::default()
+        };
+        assert_eq!(
+            report(
+                &state,
+                MessageDataControl::OnlyNonDefault,
+                ALL_CONTROLLERS,
+                ALL_NOTE_DATA
+            ),
+            std::vec![std::vec![0x4193_3C00, 0xFFFF_0000]]
+        );
+    }
+
+    #[test]
+    fn full_report() {
+        let registered = [ControllerValue {
+            bank: u7::new(0x0),
+            index: u7::new(0x0),
+            value: 0x0400_0000,
+        }];
+        let notes = [NoteValue {
+            note_number: u7::new(0x3C),
+            velocity: 0xFFFF,
+            key_pressure: Some(0x8000_0000),
+            pitch_bend: None,
+        }];
+        let state = full_state(&registered, &notes);
+        assert_eq!(
+            report(
+                &state,
+                MessageDataControl::Full,
+                ALL_CONTROLLERS,
+                ALL_NOTE_DATA
+            ),
+            std::vec![
+                std::vec![0x41C3_0001, 0x1000_0101],
+                std::vec![0x41B3_0100, 0x4000_0000],
+                std::vec![0x41B3_4A00, 0xFFFF_FFFF],
+                std::vec![0x4123_0000, 0x0400_0000],
+                std::vec![0x41D3_0000, 0x1234_5678],
+                std::vec![0x41E3_0000, 0x9000_0000],
+                std::vec![0x4193_3C00, 0xFFFF_0000],
+                std::vec![0x41A3_3C00, 0x8000_0000],
+                std::vec![0x4163_3C00, 0x8000_0000],
+            ]
+        );
+    }
+
+    #[test]
+    fn requested_messages_only() {
+        let notes = [NoteValue {
+            note_number: u7::new(0x3C),
+            velocity: 0xFFFF,
+            key_pressure: Some(0x8000_0000),
+            pitch_bend: Some(0x1234_5678),
+        }];
+        let state = full_state(&[], &notes);
+        assert_eq!(
+            report(
+                &state,
+                MessageDataControl::OnlyNonDefault,
+                ChannelControllerMessages {
+                    pitch_bend: true,
+                    ..Default::default()
+                },
+                NoteDataMessages {
+                    per_note_pitch_bend: true,
+                    ..Default::default()
+                },
+            ),
+            std::vec![
+                std::vec![0x41E3_0000, 0x9000_0000],
+                std::vec![0x4163_3C00, 0x1234_5678],
+            ]
+        );
+    }
+
+    #[test]
+    fn no_data() {
+        let state = full_state(&[], &[]);
+        assert!(report(
+            &state,
+            MessageDataControl::NoData,
+            ALL_CONTROLLERS,
+            ALL_NOTE_DATA
+        )
+        .is_empty());
+    }
+}
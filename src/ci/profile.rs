@@ -0,0 +1,981 @@
+use crate::{
+    buffer::{BufferMut, Bytes},
+    ci,
+    detail::property,
+};
+
+pub(crate) const INQUIRY_STATUS: u8 = 0x20;
+pub(crate) const REPLY_STATUS: u8 = 0x21;
+pub(crate) const SET_ON_STATUS: u8 = 0x22;
+pub(crate) const SET_OFF_STATUS: u8 = 0x23;
+pub(crate) const ENABLED_REPORT_STATUS: u8 = 0x24;
+pub(crate) const DISABLED_REPORT_STATUS: u8 = 0x25;
+pub(crate) const ADDED_REPORT_STATUS: u8 = 0x26;
+pub(crate) const REMOVED_REPORT_STATUS: u8 = 0x27;
+pub(crate) const DETAILS_INQUIRY_STATUS: u8 = 0x28;
+pub(crate) const DETAILS_REPLY_STATUS: u8 = 0x29;
+pub(crate) const SPECIFIC_DATA_STATUS: u8 = 0x2F;
+
+const STANDARD_PROFILE: u8 = 0x7E;
+const PROFILE_ID_SIZE: usize = 5;
+
+/// Identifies a MIDI-CI profile.
+///
+/// Standard profiles are defined by the MMA and AMEI,
+/// manufacturer specific profiles are identified by the sysex id of the manufacturer.
+///
+/// ```rust
+/// use midi2::{ci::ProfileId, ux::u7};
+///
+/// let id = ProfileId::Standard {
+///     bank: u7::new(0x21),
+///     number: u7::new(0x01),
+///     version: u7::new(0x01),
+///     level: u7::new(0x01),
+/// };
+/// assert_eq!(id.to_u7s().map(u8::from), [0x7E, 0x21, 0x01, 0x01, 0x01]);
+/// assert_eq!(ProfileId::from_u7s(id.to_u7s()), id);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProfileId {
+    Standard {
+        bank: ux::u7,
+        number: ux::u7,
+        version: ux::u7,
+        level: ux::u7,
+    },
+    ManufacturerSpecific {
+        manufacturer: [ux::u7; 3],
+        data: [ux::u7; 2],
+    },
+}
+
+impl core::default::Default for ProfileId {
+    fn default() -> Self {
+        ProfileId::Standard {
+            bank: Default::default(),
+            number: Default::default(),
+            version: Default::default(),
+            level: Default::default(),
+        }
+    }
+}
+
+impl ProfileId {
+    pub fn from_u7s(data: [ux::u7; 5]) -> Self {
+        if u8::from(data[0]) == STANDARD_PROFILE {
+            ProfileId::Standard {
+                bank: data[1],
+                number: data[2],
+                version: data[3],
+                level: data[4],
+            }
+        } else {
+            ProfileId::ManufacturerSpecific {
+                manufacturer: [data[0], data[1], data[2]],
+                data: [data[3], data[4]],
+            }
+        }
+    }
+
+    pub fn to_u7s(&self) -> [ux::u7; 5] {
+        match *self {
+            ProfileId::Standard {
+                bank,
+                number,
+                version,
+                level,
+            } => [ux::u7::new(STANDARD_PROFILE), bank, number, version, level],
+            ProfileId::ManufacturerSpecific { manufacturer, data } => [
+                manufacturer[0],
+                manufacturer[1],
+                manufacturer[2],
+                data[0],
+                data[1],
+            ],
+        }
+    }
+
+    fn from_bytes(data: &[u8]) -> Self {
+        Self::from_u7s(core::array::from_fn(|i| ux::u7::new(data[i] & 0x7F)))
+    }
+
+    fn to_bytes(self, data: &mut [u8]) {
+        for (byte, value) in data.iter_mut().zip(self.to_u7s()) {
+            *byte = value.into();
+        }
+    }
+}
+
+/// Iterates over the [ProfileId]s of a list in a [ProfileInquiryReply].
+#[derive(Debug, Clone)]
+pub struct ProfileIdIterator<'a>(core::slice::ChunksExact<'a, u8>);
+
+impl<'a> core::iter::Iterator for ProfileIdIterator<'a> {
+    type Item = ProfileId;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(ProfileId::from_bytes)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> core::iter::ExactSizeIterator for ProfileIdIterator<'a> {}
+
+impl<'a> core::iter::FusedIterator for ProfileIdIterator<'a> {}
+
+struct ProfileIdProperty<const INDEX: usize>;
+
+impl<const INDEX: usize, B: Bytes> property::Property<B> for ProfileIdProperty<INDEX> {
+    type Type = ProfileId;
+}
+
+impl<'a, const INDEX: usize, B: Bytes> property::ReadProperty<'a, B> for ProfileIdProperty<INDEX> {
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        ProfileId::from_bytes(&buffer.buffer()[INDEX..INDEX + PROFILE_ID_SIZE])
+    }
+}
+
+impl<const INDEX: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for ProfileIdProperty<INDEX>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        v.to_bytes(&mut buffer.buffer_mut()[INDEX..INDEX + PROFILE_ID_SIZE]);
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+// The reply to a profile inquiry carries two lists of profile ids,
+// the enabled profiles followed by the disabled profiles.
+// Each list is preceded by the number of ids it contains.
+
+const PROFILE_LISTS_INDEX: usize = 14;
+const SYSEX_END: u8 = 0xF7;
+const ERR_INVALID_PROFILE_LISTS: &str = "Profile lists are inconsistent with the message size";
+
+fn profile_count(buffer: &[u8], index: usize) -> Option<usize> {
+    use crate::detail::Encode7Bit;
+    let count = buffer.get(index..index + 2)?;
+    Some(u16::from(ux::u14::from_u7s(count)) as usize)
+}
+
+// the index of the count of the given list
+fn profile_list_index(buffer: &[u8], list: usize) -> Option<usize> {
+    let mut index = PROFILE_LISTS_INDEX;
+    for _ in 0..list {
+        index += 2 + PROFILE_ID_SIZE * profile_count(buffer, index)?;
+    }
+    Some(index)
+}
+
+fn max_profile_count(profiles: &[ProfileId]) -> usize {
+    profiles.len().min(u16::from(ux::u14::MAX) as usize)
+}
+
+struct ProfileListWriteProperty<'a, const LIST: usize>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, const LIST: usize, B: Bytes> property::Property<B> for ProfileListWriteProperty<'a, LIST> {
+    type Type = &'a [ProfileId];
+}
+
+impl<'a, const LIST: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for ProfileListWriteProperty<'a, LIST>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        use crate::detail::Encode7Bit;
+        let buffer = buffer.buffer_mut();
+        let index = profile_list_index(buffer, LIST).expect("Profile lists should be consistent");
+        let count = max_profile_count(v);
+        ux::u14::new(count as u16).to_u7s(&mut buffer[index..index + 2]);
+        for (data, id) in buffer[index + 2..]
+            .chunks_exact_mut(PROFILE_ID_SIZE)
+            .zip(&v[..count])
+        {
+            id.to_bytes(data);
+        }
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+}
+
+impl<'a, const LIST: usize> ProfileListWriteProperty<'a, LIST> {
+    // make space for the new list, moving any following data
+    fn resize_impl<
+        B: Bytes + BufferMut,
+        R: Fn(&mut B, usize) -> Result<(), crate::error::BufferOverflow>,
+    >(
+        buffer: &mut B,
+        value: &[ProfileId],
+        resize: R,
+    ) -> Result<(), crate::error::BufferOverflow> {
+        let data = buffer.buffer();
+        let index = profile_list_index(data, LIST).expect("Profile lists should be consistent");
+        let old_end = index
+            + 2
+            + PROFILE_ID_SIZE
+                * profile_count(data, index).expect("Profile lists should be consistent");
+        let new_end = index + 2 + PROFILE_ID_SIZE * max_profile_count(value);
+        let size = profile_list_index(data, 2).expect("Profile lists should be consistent") + 1;
+        let new_size = size + new_end - old_end;
+
+        if new_end > old_end {
+            resize(buffer, new_size)?;
+            buffer.buffer_mut().copy_within(old_end..size, new_end);
+        } else {
+            buffer.buffer_mut().copy_within(old_end..size, new_end);
+            resize(buffer, new_size)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, const LIST: usize, B: Bytes + BufferMut> property::ResizeProperty<B>
+    for ProfileListWriteProperty<'a, LIST>
+{
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        Self::resize_impl(buffer, value, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .expect("Resizable buffers should not fail here")
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        Self::resize_impl(buffer, value, |buffer, size| buffer.try_resize(size))
+    }
+}
+
+struct ProfileListReadProperty<'a, const LIST: usize>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, const LIST: usize, B: Bytes> property::Property<B> for ProfileListReadProperty<'a, LIST> {
+    type Type = ProfileIdIterator<'a>;
+}
+
+impl<'a, const LIST: usize, B: 'a + Bytes> property::ReadProperty<'a, B>
+    for ProfileListReadProperty<'a, LIST>
+{
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        let buffer = buffer.buffer();
+        match profile_list_index(buffer, 2).and_then(|end| buffer.get(end)) {
+            Some(&SYSEX_END) => Ok(()),
+            _ => Err(crate::error::InvalidData(ERR_INVALID_PROFILE_LISTS)),
+        }
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        let buffer = buffer.buffer();
+        let index = profile_list_index(buffer, LIST).expect("Profile lists should be consistent");
+        let count = profile_count(buffer, index).expect("Profile lists should be consistent");
+        ProfileIdIterator(
+            buffer[index + 2..index + 2 + PROFILE_ID_SIZE * count].chunks_exact(PROFILE_ID_SIZE),
+        )
+    }
+}
+
+pub use added_report::ProfileAddedReport;
+pub use details_inquiry::ProfileDetailsInquiry;
+pub use details_reply::ProfileDetailsReply;
+pub use disabled_report::ProfileDisabledReport;
+pub use enabled_report::ProfileEnabledReport;
+pub use inquiry::ProfileInquiry;
+pub use removed_report::ProfileRemovedReport;
+pub use reply::ProfileInquiryReply;
+pub use set_off::SetProfileOff;
+pub use set_on::SetProfileOn;
+pub use specific_data::ProfileSpecificData;
+
+mod inquiry {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 15),
+        SupportedVersion(version = 0x2, min_size = 15)
+    )]
+    struct ProfileInquiry {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x1)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<INQUIRY_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+    }
+}
+
+mod reply {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 19),
+        SupportedVersion(version = 0x2, min_size = 19)
+    )]
+    struct ProfileInquiryReply {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x1)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<REPLY_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+        #[property(ProfileListWriteProperty<0>)]
+        #[version(0x1)]
+        #[writeonly]
+        #[resize]
+        enabled_profiles: &[ProfileId],
+        #[property(ProfileListReadProperty<0>)]
+        #[version(0x1)]
+        #[readonly]
+        enabled_profiles: ProfileIdIterator,
+        #[property(ProfileListWriteProperty<1>)]
+        #[version(0x1)]
+        #[writeonly]
+        #[resize]
+        disabled_profiles: &[ProfileId],
+        #[property(ProfileListReadProperty<1>)]
+        #[version(0x1)]
+        #[readonly]
+        disabled_profiles: ProfileIdIterator,
+    }
+}
+
+mod set_on {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 20),
+        SupportedVersion(version = 0x2, min_size = 22)
+    )]
+    struct SetProfileOn {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x1)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<SET_ON_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x1)]
+        profile_id: ProfileId,
+        #[property(ci::common_properties::U14Property<19>)]
+        #[version(0x2)]
+        number_of_channels: ux::u14,
+    }
+}
+
+mod set_off {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 20),
+        SupportedVersion(version = 0x2, min_size = 22)
+    )]
+    struct SetProfileOff {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x1)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<SET_OFF_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x1)]
+        profile_id: ProfileId,
+    }
+}
+
+mod enabled_report {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 20),
+        SupportedVersion(version = 0x2, min_size = 22)
+    )]
+    struct ProfileEnabledReport {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x1)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<ENABLED_REPORT_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::BroadcastDestinationProperty)]
+        #[version(0x1)]
+        broadcast_destination: (),
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x1)]
+        profile_id: ProfileId,
+        #[property(ci::common_properties::U14Property<19>)]
+        #[version(0x2)]
+        number_of_channels: ux::u14,
+    }
+}
+
+mod disabled_report {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 20),
+        SupportedVersion(version = 0x2, min_size = 22)
+    )]
+    struct ProfileDisabledReport {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x1)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<DISABLED_REPORT_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::BroadcastDestinationProperty)]
+        #[version(0x1)]
+        broadcast_destination: (),
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x1)]
+        profile_id: ProfileId,
+        #[property(ci::common_properties::U14Property<19>)]
+        #[version(0x2)]
+        number_of_channels: ux::u14,
+    }
+}
+
+mod added_report {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 20))]
+    struct ProfileAddedReport {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x2)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<ADDED_REPORT_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::BroadcastDestinationProperty)]
+        #[version(0x2)]
+        broadcast_destination: (),
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x2)]
+        profile_id: ProfileId,
+    }
+}
+
+mod removed_report {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 20))]
+    struct ProfileRemovedReport {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x2)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<REMOVED_REPORT_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::BroadcastDestinationProperty)]
+        #[version(0x2)]
+        broadcast_destination: (),
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x2)]
+        profile_id: ProfileId,
+    }
+}
+
+mod details_inquiry {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 21))]
+    struct ProfileDetailsInquiry {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x2)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<DETAILS_INQUIRY_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x2)]
+        profile_id: ProfileId,
+        #[property(ci::common_properties::U7Property<19>)]
+        #[version(0x2)]
+        inquiry_target: ux::u7,
+    }
+}
+
+mod details_reply {
+    use super::*;
+
+    #[midi2_proc::generate_ci(SupportedVersion(version = 0x2, min_size = 23))]
+    struct ProfileDetailsReply {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x2)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x2)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x2)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<DETAILS_REPLY_STATUS>)]
+        #[version(0x2)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x2)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x2)]
+        destination: ux::u28,
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x2)]
+        profile_id: ProfileId,
+        #[property(ci::common_properties::U7Property<19>)]
+        #[version(0x2)]
+        inquiry_target: ux::u7,
+        #[property(ci::common_properties::TrailingDataWriteProperty<20>)]
+        #[version(0x2)]
+        #[writeonly]
+        #[resize]
+        inquiry_target_data: &[ux::u7],
+        #[property(ci::common_properties::TrailingDataReadProperty<20>)]
+        #[version(0x2)]
+        #[readonly]
+        inquiry_target_data: &[u8],
+    }
+}
+
+mod specific_data {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 24),
+        SupportedVersion(version = 0x2, min_size = 24)
+    )]
+    struct ProfileSpecificData {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdProperty)]
+        #[version(0x1)]
+        device_id: ci::DeviceId,
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<SPECIFIC_DATA_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+        #[property(ProfileIdProperty<14>)]
+        #[version(0x1)]
+        profile_id: ProfileId,
+        #[property(ci::common_properties::TrailingDataWriteProperty<19, 4>)]
+        #[version(0x1)]
+        #[writeonly]
+        #[resize]
+        profile_data: &[ux::u7],
+        #[property(ci::common_properties::TrailingDataReadProperty<19, 4>)]
+        #[version(0x1)]
+        #[readonly]
+        profile_data: &[u8],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn standard_profile(bank: u8, number: u8) -> ProfileId {
+        ProfileId::Standard {
+            bank: ux::u7::new(bank),
+            number: ux::u7::new(number),
+            version: ux::u7::new(0x1),
+            level: ux::u7::new(0x1),
+        }
+    }
+
+    fn manufacturer_profile() -> ProfileId {
+        ProfileId::ManufacturerSpecific {
+            manufacturer: [ux::u7::new(0x00), ux::u7::new(0x21), ux::u7::new(0x09)],
+            data: [ux::u7::new(0x12), ux::u7::new(0x34)],
+        }
+    }
+
+    #[test]
+    fn profile_id_standard() {
+        let id = standard_profile(0x21, 0x01);
+        assert_eq!(id.to_u7s().map(u8::from), [0x7E, 0x21, 0x01, 0x01, 0x01]);
+        assert_eq!(ProfileId::from_u7s(id.to_u7s()), id);
+    }
+
+    #[test]
+    fn profile_id_manufacturer_specific() {
+        let id = manufacturer_profile();
+        assert_eq!(id.to_u7s().map(u8::from), [0x00, 0x21, 0x09, 0x12, 0x34]);
+        assert_eq!(ProfileId::from_u7s(id.to_u7s()), id);
+    }
+
+    #[test]
+    fn inquiry() {
+        let mut message = ProfileInquiry::<0x2, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Group);
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7E, 0x0D, 0x20, 0x02, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_new() {
+        let message = ProfileInquiryReply::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x21, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+        assert_eq!(message.enabled_profiles().count(), 0);
+        assert_eq!(message.disabled_profiles().count(), 0);
+    }
+
+    #[test]
+    fn reply_set_profiles() {
+        let mut message = ProfileInquiryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_disabled_profiles(&[manufacturer_profile()]);
+        message.set_enabled_profiles(&[standard_profile(0x21, 0x01), standard_profile(0x31, 0x02)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x21, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x02,
+                0x00, 0x7E, 0x21, 0x01, 0x01, 0x01, 0x7E, 0x31, 0x02, 0x01, 0x01, 0x01, 0x00, 0x00,
+                0x21, 0x09, 0x12, 0x34, 0xF7,
+            ]
+        );
+        assert_eq!(
+            message.enabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![standard_profile(0x21, 0x01), standard_profile(0x31, 0x02)]
+        );
+        assert_eq!(
+            message.disabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![manufacturer_profile()]
+        );
+    }
+
+    #[test]
+    fn reply_shrink_enabled_profiles() {
+        let mut message = ProfileInquiryReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_enabled_profiles(&[standard_profile(0x21, 0x01), standard_profile(0x31, 0x02)]);
+        message.set_disabled_profiles(&[manufacturer_profile()]);
+        message.set_enabled_profiles(&[]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x21, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x00,
+                0x00, 0x01, 0x00, 0x00, 0x21, 0x09, 0x12, 0x34, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_try_set_profiles() {
+        let mut message = ProfileInquiryReply::<0x2, [u8; 24]>::try_new().unwrap();
+        assert_eq!(
+            message.try_set_enabled_profiles(&[standard_profile(0x21, 0x01)]),
+            Ok(())
+        );
+        assert_eq!(
+            message.try_set_disabled_profiles(&[manufacturer_profile()]),
+            Err(crate::error::BufferOverflow)
+        );
+        assert_eq!(message.enabled_profiles().count(), 1);
+        assert_eq!(message.disabled_profiles().count(), 0);
+    }
+
+    #[test]
+    fn reply_try_from_slice() {
+        let message = ProfileInquiryReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x21, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01,
+                0x00, 0x7E, 0x21, 0x01, 0x01, 0x01, 0x00, 0x00, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(
+            message.enabled_profiles().collect::<std::vec::Vec<_>>(),
+            std::vec![standard_profile(0x21, 0x01)]
+        );
+    }
+
+    #[test]
+    fn reply_try_from_slice_inconsistent_lists() {
+        assert!(ProfileInquiryReply::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x21, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x02,
+                0x00, 0x7E, 0x21, 0x01, 0x01, 0x01, 0x00, 0x00, 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn set_on_v1() {
+        let mut message = SetProfileOn::<0x1, std::vec::Vec<u8>>::new();
+        message.set_device_id(ci::DeviceId::Channel(ux::u4::new(0x2)));
+        message.set_profile_id(standard_profile(0x21, 0x01));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x02, 0x0D, 0x22, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x21, 0x01, 0x01, 0x01, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn set_on_v2() {
+        let mut message = SetProfileOn::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(standard_profile(0x21, 0x01));
+        message.set_number_of_channels(ux::u14::new(0x90));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x22, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x21, 0x01, 0x01, 0x01, 0x10, 0x01, 0xF7,
+            ]
+        );
+        assert_eq!(message.number_of_channels(), ux::u14::new(0x90));
+    }
+
+    #[test]
+    fn set_off_v2() {
+        let mut message = SetProfileOff::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(manufacturer_profile());
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x23, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x00,
+                0x21, 0x09, 0x12, 0x34, 0x0, 0x0, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn enabled_report() {
+        use crate::ci::Ci;
+        let mut message = ProfileEnabledReport::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(standard_profile(0x21, 0x01));
+        message.set_number_of_channels(ux::u14::new(0x1));
+        assert_eq!(message.destination(), ux::u28::MAX);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x24, 0x02, 0x0, 0x0, 0x0, 0x0, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x7E, 0x21, 0x01, 0x01, 0x01, 0x01, 0x00, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_report_try_from_slice() {
+        let message = ProfileDisabledReport::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x25, 0x01, 0x0, 0x0, 0x0, 0x0, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x7E, 0x21, 0x01, 0x01, 0x01, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.profile_id(), standard_profile(0x21, 0x01));
+    }
+
+    #[test]
+    fn added_report() {
+        use crate::ci::Ci;
+        let mut message = ProfileAddedReport::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(standard_profile(0x21, 0x01));
+        assert_eq!(message.device_id(), ci::DeviceId::FunctionBlock);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x26, 0x02, 0x0, 0x0, 0x0, 0x0, 0x7F, 0x7F, 0x7F, 0x7F,
+                0x7E, 0x21, 0x01, 0x01, 0x01, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_report() {
+        let message = ProfileRemovedReport::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(message.data()[4], 0x27);
+    }
+
+    #[test]
+    fn details_inquiry() {
+        let mut message = ProfileDetailsInquiry::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(standard_profile(0x21, 0x01));
+        message.set_inquiry_target(ux::u7::new(0x05));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x28, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x21, 0x01, 0x01, 0x01, 0x05, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn details_reply() {
+        let mut message = ProfileDetailsReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_profile_id(standard_profile(0x21, 0x01));
+        message.set_inquiry_target(ux::u7::new(0x05));
+        message.set_inquiry_target_data(&[ux::u7::new(0x10), ux::u7::new(0x20)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x29, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x7E,
+                0x21, 0x01, 0x01, 0x01, 0x05, 0x02, 0x00, 0x10, 0x20, 0xF7,
+            ]
+        );
+        assert_eq!(message.inquiry_target_data(), &[0x10, 0x20]);
+    }
+
+    #[test]
+    fn specific_data() {
+        let mut message = ProfileSpecificData::<0x1, std::vec::Vec<u8>>::new();
+        message.set_profile_id(manufacturer_profile());
+        message.set_profile_data(&[ux::u7::new(0x1), ux::u7::new(0x2), ux::u7::new(0x3)]);
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x2F, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x00,
+                0x21, 0x09, 0x12, 0x34, 0x03, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0xF7,
+            ]
+        );
+        assert_eq!(message.profile_data(), &[0x1, 0x2, 0x3]);
+    }
+
+    #[test]
+    fn specific_data_try_from_slice() {
+        let message = ProfileSpecificData::<0x2, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x0, 0x0D, 0x2F, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x00,
+                0x21, 0x09, 0x12, 0x34, 0x01, 0x00, 0x00, 0x00, 0x42, 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.profile_id(), manufacturer_profile());
+        assert_eq!(message.profile_data(), &[0x42]);
+    }
+}
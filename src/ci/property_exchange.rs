@@ -0,0 +1,603 @@
+use crate::{
+    buffer::{BufferMut, Bytes},
+    ci,
+    detail::{property, Encode7Bit},
+};
+
+mod assembler;
+
+pub use assembler::*;
+
+pub(crate) const CAPABILITIES_STATUS: u8 = 0x30;
+pub(crate) const CAPABILITIES_REPLY_STATUS: u8 = 0x31;
+pub(crate) const GET_STATUS: u8 = 0x34;
+pub(crate) const GET_REPLY_STATUS: u8 = 0x35;
+pub(crate) const SET_STATUS: u8 = 0x36;
+pub(crate) const SET_REPLY_STATUS: u8 = 0x37;
+pub(crate) const SUBSCRIPTION_STATUS: u8 = 0x38;
+pub(crate) const SUBSCRIPTION_REPLY_STATUS: u8 = 0x39;
+pub(crate) const NOTIFY_STATUS: u8 = 0x3F;
+
+// Property data messages share a common layout following the ci header:
+//
+// | request id | header size (u14) | header | number of chunks (u14) |
+// | this chunk (u14) | body size (u14) | body | 0xF7 |
+//
+// The header and the body are variable in size so the position of
+// the fields following the header depend on the header size.
+
+const STATUS_INDEX: usize = 4;
+const SOURCE_INDEX: usize = 6;
+const REQUEST_ID_INDEX: usize = 14;
+const HEADER_SIZE_INDEX: usize = 15;
+const HEADER_INDEX: usize = 17;
+const NUMBER_OF_CHUNKS_OFFSET: usize = 0;
+const THIS_CHUNK_OFFSET: usize = 2;
+const BODY_SIZE_OFFSET: usize = 4;
+const BODY_OFFSET: usize = 6;
+const SYSEX_END: u8 = 0xF7;
+
+const ERR_INVALID_HEADER_SIZE: &str = "Property exchange header exceeds the message size";
+const ERR_INVALID_BODY_SIZE: &str = "Property exchange body is inconsistent with the message size";
+
+fn read_u14(buffer: &[u8], index: usize) -> Option<usize> {
+    let data = buffer.get(index..index + 2)?;
+    Some(u16::from(ux::u14::from_u7s(data)) as usize)
+}
+
+fn write_u14(buffer: &mut [u8], index: usize, value: usize) {
+    ux::u14::new(value as u16).to_u7s(&mut buffer[index..index + 2]);
+}
+
+fn max_data_size(data: &[ux::u7]) -> usize {
+    data.len().min(u16::from(ux::u14::MAX) as usize)
+}
+
+fn header_range(buffer: &[u8]) -> Option<core::ops::Range<usize>> {
+    let end = HEADER_INDEX + read_u14(buffer, HEADER_SIZE_INDEX)?;
+    if end > buffer.len() {
+        return None;
+    }
+    Some(HEADER_INDEX..end)
+}
+
+// the index of the number of chunks field
+fn chunk_index(buffer: &[u8]) -> Option<usize> {
+    header_range(buffer).map(|range| range.end)
+}
+
+fn body_range(buffer: &[u8]) -> Option<core::ops::Range<usize>> {
+    let chunk_index = chunk_index(buffer)?;
+    let start = chunk_index + BODY_OFFSET;
+    let end = start + read_u14(buffer, chunk_index + BODY_SIZE_OFFSET)?;
+    if end > buffer.len() {
+        return None;
+    }
+    Some(start..end)
+}
+
+/// Whether the sysex status identifies a property data message,
+/// i.e. one carrying a header, chunk information and a body.
+fn is_property_data_status(status: u8) -> bool {
+    matches!(
+        status,
+        GET_STATUS
+            | GET_REPLY_STATUS
+            | SET_STATUS
+            | SET_REPLY_STATUS
+            | SUBSCRIPTION_STATUS
+            | SUBSCRIPTION_REPLY_STATUS
+            | NOTIFY_STATUS
+    )
+}
+
+struct HeaderWriteProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, B: Bytes> property::Property<B> for HeaderWriteProperty<'a> {
+    type Type = &'a [ux::u7];
+}
+
+impl<'a, B: Bytes + BufferMut> property::WriteProperty<B> for HeaderWriteProperty<'a> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        let size = max_data_size(v);
+        let buffer = buffer.buffer_mut();
+        write_u14(buffer, HEADER_SIZE_INDEX, size);
+        for (byte, value) in buffer[HEADER_INDEX..HEADER_INDEX + size].iter_mut().zip(v) {
+            *byte = (*value).into();
+        }
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+}
+
+impl<'a> HeaderWriteProperty<'a> {
+    // make space for the new header, moving the chunk fields and the body
+    fn resize_impl<
+        B: Bytes + BufferMut,
+        R: Fn(&mut B, usize) -> Result<(), crate::error::BufferOverflow>,
+    >(
+        buffer: &mut B,
+        value: &[ux::u7],
+        resize: R,
+    ) -> Result<(), crate::error::BufferOverflow> {
+        let data = buffer.buffer();
+        let old_end = chunk_index(data).expect("Header should be consistent");
+        let new_end = HEADER_INDEX + max_data_size(value);
+        let size = body_range(data).expect("Body should be consistent").end + 1;
+        let new_size = size + new_end - old_end;
+
+        if new_end > old_end {
+            resize(buffer, new_size)?;
+            buffer.buffer_mut().copy_within(old_end..size, new_end);
+        } else {
+            buffer.buffer_mut().copy_within(old_end..size, new_end);
+            resize(buffer, new_size)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, B: Bytes + BufferMut> property::ResizeProperty<B> for HeaderWriteProperty<'a> {
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        Self::resize_impl(buffer, value, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .expect("Resizable buffers should not fail here")
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        Self::resize_impl(buffer, value, |buffer, size| buffer.try_resize(size))
+    }
+}
+
+struct HeaderReadProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, B: Bytes> property::Property<B> for HeaderReadProperty<'a> {
+    type Type = &'a [u8];
+}
+
+impl<'a, B: 'a + Bytes> property::ReadProperty<'a, B> for HeaderReadProperty<'a> {
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        header_range(buffer.buffer())
+            .map(|_| ())
+            .ok_or(crate::error::InvalidData(ERR_INVALID_HEADER_SIZE))
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        let buffer = buffer.buffer();
+        &buffer[header_range(buffer).expect("Header should be consistent")]
+    }
+}
+
+struct ChunkProperty<const OFFSET: usize>;
+
+impl<const OFFSET: usize, B: Bytes> property::Property<B> for ChunkProperty<OFFSET> {
+    type Type = ux::u14;
+}
+
+impl<'a, const OFFSET: usize, B: Bytes> property::ReadProperty<'a, B> for ChunkProperty<OFFSET> {
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        let buffer = buffer.buffer();
+        match chunk_index(buffer).and_then(|index| read_u14(buffer, index + OFFSET)) {
+            Some(_) => Ok(()),
+            None => Err(crate::error::InvalidData(ERR_INVALID_HEADER_SIZE)),
+        }
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        let buffer = buffer.buffer();
+        let index = chunk_index(buffer).expect("Header should be consistent") + OFFSET;
+        ux::u14::from_u7s(&buffer[index..index + 2])
+    }
+}
+
+impl<const OFFSET: usize, B: Bytes + BufferMut> property::WriteProperty<B>
+    for ChunkProperty<OFFSET>
+{
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        let buffer = buffer.buffer_mut();
+        let index = chunk_index(buffer).expect("Header should be consistent") + OFFSET;
+        v.to_u7s(&mut buffer[index..index + 2]);
+    }
+    fn default() -> Self::Type {
+        // messages are sent in a single chunk unless stated otherwise
+        ux::u14::new(1)
+    }
+}
+
+struct BodyWriteProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl<'a> BodyWriteProperty<'a> {
+    fn size(buffer: &[u8], value: &[ux::u7]) -> usize {
+        chunk_index(buffer).expect("Header should be consistent")
+            + BODY_OFFSET
+            + max_data_size(value)
+            + 1
+    }
+}
+
+impl<'a, B: Bytes> property::Property<B> for BodyWriteProperty<'a> {
+    type Type = &'a [ux::u7];
+}
+
+impl<'a, B: Bytes + BufferMut> property::WriteProperty<B> for BodyWriteProperty<'a> {
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn write(buffer: &mut B, v: Self::Type) {
+        let size = max_data_size(v);
+        let buffer = buffer.buffer_mut();
+        let index = chunk_index(buffer).expect("Header should be consistent");
+        write_u14(buffer, index + BODY_SIZE_OFFSET, size);
+        let body_index = index + BODY_OFFSET;
+        for (byte, value) in buffer[body_index..body_index + size].iter_mut().zip(v) {
+            *byte = (*value).into();
+        }
+        buffer[body_index + size] = SYSEX_END;
+    }
+    fn default() -> Self::Type {
+        &[]
+    }
+}
+
+impl<'a, B: Bytes + BufferMut> property::ResizeProperty<B> for BodyWriteProperty<'a> {
+    fn resize(buffer: &mut B, value: &Self::Type)
+    where
+        B: crate::buffer::BufferResize,
+    {
+        let size = Self::size(buffer.buffer(), value);
+        buffer.resize(size);
+    }
+    fn try_resize(buffer: &mut B, value: &Self::Type) -> Result<(), crate::error::BufferOverflow>
+    where
+        B: crate::buffer::BufferTryResize,
+    {
+        let size = Self::size(buffer.buffer(), value);
+        buffer.try_resize(size)
+    }
+}
+
+struct BodyReadProperty<'a>(core::marker::PhantomData<&'a u8>);
+
+impl<'a, B: Bytes> property::Property<B> for BodyReadProperty<'a> {
+    type Type = &'a [u8];
+}
+
+impl<'a, B: 'a + Bytes> property::ReadProperty<'a, B> for BodyReadProperty<'a> {
+    fn validate(buffer: &B) -> Result<(), crate::error::InvalidData> {
+        let buffer = buffer.buffer();
+        match body_range(buffer).and_then(|range| buffer.get(range.end)) {
+            Some(&SYSEX_END) => Ok(()),
+            _ => Err(crate::error::InvalidData(ERR_INVALID_BODY_SIZE)),
+        }
+    }
+    fn read(buffer: &'a B) -> Self::Type {
+        let buffer = buffer.buffer();
+        &buffer[body_range(buffer).expect("Body should be consistent")]
+    }
+}
+
+pub use capabilities::PropertyExchangeCapabilities;
+pub use capabilities_reply::PropertyExchangeCapabilitiesReply;
+pub use get::GetPropertyData;
+pub use get_reply::GetPropertyDataReply;
+pub use notify::Notify;
+pub use set::SetPropertyData;
+pub use set_reply::SetPropertyDataReply;
+pub use subscription::Subscription;
+pub use subscription_reply::SubscriptionReply;
+
+mod capabilities {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 16),
+        SupportedVersion(version = 0x2, min_size = 18)
+    )]
+    struct PropertyExchangeCapabilities {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x1)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<CAPABILITIES_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+        #[property(ci::common_properties::U7Property<14>)]
+        #[version(0x1)]
+        number_of_simultaneous_requests: ux::u7,
+        #[property(ci::common_properties::U7Property<15>)]
+        #[version(0x2)]
+        major_version: ux::u7,
+        #[property(ci::common_properties::U7Property<16>)]
+        #[version(0x2)]
+        minor_version: ux::u7,
+    }
+}
+
+mod capabilities_reply {
+    use super::*;
+
+    #[midi2_proc::generate_ci(
+        SupportedVersion(version = 0x1, min_size = 16),
+        SupportedVersion(version = 0x2, min_size = 18)
+    )]
+    struct PropertyExchangeCapabilitiesReply {
+        #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+        #[version(0x1)]
+        universal_sysex_byte: (),
+        #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+        #[version(0x1)]
+        device_id: (),
+        #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+        #[version(0x1)]
+        sysex_sub_id1_ci: (),
+        #[property(ci::common_properties::UniversalSysexSubId2<CAPABILITIES_REPLY_STATUS>)]
+        #[version(0x1)]
+        sysex_sub_id2_ci: (),
+        #[property(ci::common_properties::SourceProperty)]
+        #[version(0x1)]
+        source: ux::u28,
+        #[property(ci::common_properties::DestinationProperty)]
+        #[version(0x1)]
+        destination: ux::u28,
+        #[property(ci::common_properties::U7Property<14>)]
+        #[version(0x1)]
+        number_of_simultaneous_requests: ux::u7,
+        #[property(ci::common_properties::U7Property<15>)]
+        #[version(0x2)]
+        major_version: ux::u7,
+        #[property(ci::common_properties::U7Property<16>)]
+        #[version(0x2)]
+        minor_version: ux::u7,
+    }
+}
+
+// All property data messages share the same fields,
+// differing only in their sysex status.
+macro_rules! property_data_message {
+    ($module:ident, $message:ident, $status:ident) => {
+        mod $module {
+            use super::*;
+
+            #[midi2_proc::generate_ci(
+                SupportedVersion(version = 0x1, min_size = 24),
+                SupportedVersion(version = 0x2, min_size = 24)
+            )]
+            struct $message {
+                #[property(ci::common_properties::UniversalSystemExclusiveByteProperty)]
+                #[version(0x1)]
+                universal_sysex_byte: (),
+                #[property(ci::common_properties::DeviceIdToFunctionBlockProperty)]
+                #[version(0x1)]
+                device_id: (),
+                #[property(ci::common_properties::UniversalSysexSubId1MidiCi)]
+                #[version(0x1)]
+                sysex_sub_id1_ci: (),
+                #[property(ci::common_properties::UniversalSysexSubId2<$status>)]
+                #[version(0x1)]
+                sysex_sub_id2_ci: (),
+                #[property(ci::common_properties::SourceProperty)]
+                #[version(0x1)]
+                source: ux::u28,
+                #[property(ci::common_properties::DestinationProperty)]
+                #[version(0x1)]
+                destination: ux::u28,
+                #[property(ci::common_properties::U7Property<REQUEST_ID_INDEX>)]
+                #[version(0x1)]
+                request_id: ux::u7,
+                #[property(HeaderWriteProperty)]
+                #[version(0x1)]
+                #[writeonly]
+                #[resize]
+                header: &[ux::u7],
+                #[property(HeaderReadProperty)]
+                #[version(0x1)]
+                #[readonly]
+                header: &[u8],
+                #[property(ChunkProperty<NUMBER_OF_CHUNKS_OFFSET>)]
+                #[version(0x1)]
+                number_of_chunks: ux::u14,
+                #[property(ChunkProperty<THIS_CHUNK_OFFSET>)]
+                #[version(0x1)]
+                this_chunk: ux::u14,
+                #[property(BodyWriteProperty)]
+                #[version(0x1)]
+                #[writeonly]
+                #[resize]
+                body: &[ux::u7],
+                #[property(BodyReadProperty)]
+                #[version(0x1)]
+                #[readonly]
+                body: &[u8],
+            }
+        }
+    };
+}
+
+property_data_message!(get, GetPropertyData, GET_STATUS);
+property_data_message!(get_reply, GetPropertyDataReply, GET_REPLY_STATUS);
+property_data_message!(set, SetPropertyData, SET_STATUS);
+property_data_message!(set_reply, SetPropertyDataReply, SET_REPLY_STATUS);
+property_data_message!(subscription, Subscription, SUBSCRIPTION_STATUS);
+property_data_message!(
+    subscription_reply,
+    SubscriptionReply,
+    SUBSCRIPTION_REPLY_STATUS
+);
+property_data_message!(notify, Notify, NOTIFY_STATUS);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn u7s(data: &[u8]) -> std::vec::Vec<ux::u7> {
+        data.iter().map(|b| ux::u7::new(*b)).collect()
+    }
+
+    #[test]
+    fn capabilities_v1() {
+        let mut message = PropertyExchangeCapabilities::<0x1, std::vec::Vec<u8>>::new();
+        message.set_source(ux::u28::new(0x1234567));
+        message.set_destination(ux::u28::new(0xB48D9D9));
+        message.set_number_of_simultaneous_requests(ux::u7::new(0x4));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x30, 0x01, 0x67, 0x0A, 0x0D, 0x09, 0x59, 0x33, 0x23, 0x5A,
+                0x04, 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn capabilities_reply_v2() {
+        let mut message = PropertyExchangeCapabilitiesReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_number_of_simultaneous_requests(ux::u7::new(0x4));
+        message.set_major_version(ux::u7::new(0x0));
+        message.set_minor_version(ux::u7::new(0x2));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x31, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x04,
+                0x00, 0x02, 0xF7,
+            ]
+        );
+        assert_eq!(message.minor_version(), ux::u7::new(0x2));
+    }
+
+    #[test]
+    fn get_new() {
+        let message = GetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x34, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x01, 0x0, 0x01, 0x0, 0x0, 0x0, 0xF7,
+            ]
+        );
+        assert_eq!(message.number_of_chunks(), ux::u14::new(1));
+        assert_eq!(message.this_chunk(), ux::u14::new(1));
+        assert!(message.header().is_empty());
+        assert!(message.body().is_empty());
+    }
+
+    #[test]
+    fn get_set_header() {
+        let mut message = GetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        message.set_request_id(ux::u7::new(0x2));
+        message.set_header(&u7s(b"{\"resource\":\"DeviceInfo\"}"));
+        assert_eq!(message.request_id(), ux::u7::new(0x2));
+        assert_eq!(message.header(), b"{\"resource\":\"DeviceInfo\"}");
+        assert_eq!(message.number_of_chunks(), ux::u14::new(1));
+        assert_eq!(message.this_chunk(), ux::u14::new(1));
+        assert!(message.body().is_empty());
+        assert_eq!(message.data().len(), 24 + 25);
+    }
+
+    #[test]
+    fn set_reply_header_and_body() {
+        let mut message = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_body(&u7s(b"[1,2]"));
+        message.set_header(&u7s(b"{\"status\":200}"));
+        message.set_number_of_chunks(ux::u14::new(0x2));
+        message.set_this_chunk(ux::u14::new(0x1));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x35, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x0E, 0x00, b'{', b'"', b's', b't', b'a', b't', b'u', b's', b'"', b':', b'2', b'0',
+                b'0', b'}', 0x02, 0x00, 0x01, 0x00, 0x05, 0x00, b'[', b'1', b',', b'2', b']', 0xF7,
+            ]
+        );
+        assert_eq!(message.body(), b"[1,2]");
+    }
+
+    #[test]
+    fn shrink_header() {
+        let mut message = SetPropertyData::<0x2, std::vec::Vec<u8>>::new();
+        message.set_header(&u7s(b"{\"resource\":\"X\"}"));
+        message.set_body(&u7s(b"{}"));
+        message.set_header(&u7s(b"{}"));
+        assert_eq!(
+            message.data(),
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x36, 0x02, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+                0x02, 0x00, b'{', b'}', 0x01, 0x00, 0x01, 0x00, 0x02, 0x00, b'{', b'}', 0xF7,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_set_header_overflow() {
+        let mut message = Notify::<0x2, [u8; 26]>::try_new().unwrap();
+        assert_eq!(message.try_set_header(&u7s(b"{}")), Ok(()));
+        assert_eq!(
+            message.try_set_body(&u7s(b"{}")),
+            Err(crate::error::BufferOverflow)
+        );
+        assert_eq!(message.header(), b"{}");
+        assert!(message.body().is_empty());
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let message = SubscriptionReply::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x39, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x05,
+                0x02, 0x00, b'{', b'}', 0x03, 0x00, 0x02, 0x00, 0x01, 0x00, b'x', 0xF7,
+            ][..],
+        )
+        .expect("Valid data");
+        assert_eq!(message.request_id(), ux::u7::new(0x5));
+        assert_eq!(message.header(), b"{}");
+        assert_eq!(message.number_of_chunks(), ux::u14::new(3));
+        assert_eq!(message.this_chunk(), ux::u14::new(2));
+        assert_eq!(message.body(), b"x");
+    }
+
+    #[test]
+    fn try_from_slice_header_too_large() {
+        assert!(Subscription::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x38, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x05,
+                0x40, 0x00, b'{', b'}', 0x03, 0x00, 0x02, 0x00, 0x01, 0x00, b'x', 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn try_from_slice_body_size_mismatch() {
+        assert!(Subscription::<0x1, _>::try_from(
+            &[
+                0xF0, 0x7E, 0x7F, 0x0D, 0x38, 0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x05,
+                0x02, 0x00, b'{', b'}', 0x03, 0x00, 0x02, 0x00, 0x02, 0x00, b'x', 0xF7,
+            ][..],
+        )
+        .is_err());
+    }
+}
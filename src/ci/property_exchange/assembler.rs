@@ -0,0 +1,540 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Bytes},
+    ci::property_exchange::{
+        body_range, chunk_index, header_range, is_property_data_status, read_u14,
+        NUMBER_OF_CHUNKS_OFFSET, REQUEST_ID_INDEX, SOURCE_INDEX, STATUS_INDEX, THIS_CHUNK_OFFSET,
+    },
+    detail::Encode7Bit,
+    error::BufferOverflow,
+    sysex7::Sysex7,
+    Data,
+};
+
+/// Errors reported by the [ChunkAssembler].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkError {
+    /// The message isn't a property data message, i.e. it carries
+    /// no header, chunk information or body.
+    NotPropertyData,
+    /// The chunk doesn't follow on from the previous chunk of the request.
+    /// The chunk and the unfinished request are discarded.
+    UnexpectedChunk,
+    /// The first chunk of a new request arrived while all the slots
+    /// of the assembler were taken. The chunk is discarded.
+    TooManyRequests,
+    /// The property data doesn't fit into the storage of the assembler.
+    /// The unfinished request is discarded.
+    BufferOverflow,
+}
+
+impl core::convert::From<BufferOverflow> for ChunkError {
+    fn from(_value: BufferOverflow) -> Self {
+        ChunkError::BufferOverflow
+    }
+}
+
+/// The header and complete body of a property exchange request,
+/// rebuilt from its chunks by the [ChunkAssembler].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyData<B: Bytes> {
+    source: ux::u28,
+    request_id: ux::u7,
+    header: B,
+    header_size: usize,
+    body: B,
+    body_size: usize,
+}
+
+impl<B: Bytes> PropertyData<B> {
+    /// The MUID of the device which sent the chunks.
+    pub fn source(&self) -> ux::u28 {
+        self.source
+    }
+
+    pub fn request_id(&self) -> ux::u7 {
+        self.request_id
+    }
+
+    /// The JSON header, sent with the first chunk.
+    pub fn header(&self) -> &[u8] {
+        &self.header.buffer()[..self.header_size]
+    }
+
+    /// The bodies of all the chunks, in order.
+    pub fn body(&self) -> &[u8] {
+        &self.body.buffer()[..self.body_size]
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<B: Bytes + BufferMut> {
+    active: bool,
+    source: ux::u28,
+    request_id: ux::u7,
+    next_chunk: u16,
+    header: B,
+    header_size: usize,
+    body: B,
+    body_size: usize,
+}
+
+/// Rebuilds the property data of property exchange requests
+/// from a sequence of chunked messages.
+///
+/// Large property data is split over several messages, each carrying
+/// the number of chunks and the index of the chunk.
+/// The header is carried by the first chunk only.
+/// Chunks of up to `N` requests can be interleaved. Request ids are only
+/// unique per device, so each request is identified by the MUID of its sender
+/// together with its request id.
+///
+/// Accepts any property data message, i.e. [GetPropertyData](crate::ci::GetPropertyData),
+/// [GetPropertyDataReply](crate::ci::GetPropertyDataReply), [SetPropertyData](crate::ci::SetPropertyData),
+/// [SetPropertyDataReply](crate::ci::SetPropertyDataReply), [Subscription](crate::ci::Subscription),
+/// [SubscriptionReply](crate::ci::SubscriptionReply) and [Notify](crate::ci::Notify).
+///
+/// ```rust
+/// use midi2::{prelude::*, ci};
+///
+/// let u7s = |data: &[u8]| data.iter().map(|b| u7::new(*b)).collect::<Vec<_>>();
+///
+/// let mut first = ci::GetPropertyDataReply::<0x2, Vec<u8>>::new();
+/// first.set_request_id(u7::new(0x1));
+/// first.set_header(&u7s(b"{\"status\":200}"));
+/// first.set_number_of_chunks(ux::u14::new(2));
+/// first.set_this_chunk(ux::u14::new(1));
+/// first.set_body(&u7s(b"[{\"title\":"));
+///
+/// let mut second = ci::GetPropertyDataReply::<0x2, Vec<u8>>::new();
+/// second.set_request_id(u7::new(0x1));
+/// second.set_number_of_chunks(ux::u14::new(2));
+/// second.set_this_chunk(ux::u14::new(2));
+/// second.set_body(&u7s(b"\"Piano\"}]"));
+///
+/// let mut assembler = ci::ChunkAssembler::<Vec<u8>, 4>::new();
+/// assert_eq!(assembler.push(&first), Ok(None));
+///
+/// let data = assembler.push(&second).unwrap().expect("Request complete");
+/// assert_eq!(data.header(), b"{\"status\":200}");
+/// assert_eq!(data.body(), b"[{\"title\":\"Piano\"}]");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChunkAssembler<B: Bytes + BufferMut, const N: usize> {
+    slots: [Slot<B>; N],
+}
+
+impl<B: Bytes + BufferMut + BufferDefault, const N: usize> ChunkAssembler<B, N> {
+    pub fn new() -> Self {
+        ChunkAssembler {
+            slots: core::array::from_fn(|_| Slot {
+                active: false,
+                source: Default::default(),
+                request_id: Default::default(),
+                next_chunk: 0,
+                header: B::default(),
+                header_size: 0,
+                body: B::default(),
+                body_size: 0,
+            }),
+        }
+    }
+
+    /// Discard every request in progress.
+    pub fn reset(&mut self) {
+        for slot in self.slots.iter_mut() {
+            slot.active = false;
+        }
+    }
+
+    /// Whether chunks of the given request of the given device are awaited.
+    pub fn in_progress(&self, source: ux::u28, request_id: ux::u7) -> bool {
+        self.slots.iter().any(|s| s.matches(source, request_id))
+    }
+
+    /// Feed the next chunk to the assembler.
+    ///
+    /// Returns the property data once the last chunk of a request arrives.
+    pub fn push<M, A>(&mut self, message: &M) -> Result<Option<PropertyData<B>>, ChunkError>
+    where
+        M: core::ops::Deref<Target = Sysex7<A>>,
+        A: Bytes,
+        B: BufferResize,
+    {
+        self.push_impl(message.data(), |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+    }
+
+    /// Feed the next chunk to the assembler.
+    ///
+    /// Returns the property data once the last chunk of a request arrives.
+    /// Fails with [ChunkError::BufferOverflow] when the property data
+    /// doesn't fit into the fixed size buffers.
+    pub fn try_push<M, A>(&mut self, message: &M) -> Result<Option<PropertyData<B>>, ChunkError>
+    where
+        M: core::ops::Deref<Target = Sysex7<A>>,
+        A: Bytes,
+        B: BufferTryResize,
+    {
+        self.push_impl(message.data(), |buffer, size| buffer.try_resize(size))
+    }
+
+    fn push_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        data: &[u8],
+        resize: R,
+    ) -> Result<Option<PropertyData<B>>, ChunkError> {
+        let chunk = Chunk::read(data).ok_or(ChunkError::NotPropertyData)?;
+        let index = self
+            .slots
+            .iter()
+            .position(|s| s.matches(chunk.source, chunk.request_id));
+
+        if chunk.this_chunk == 1 {
+            // a first chunk restarts any request in progress with the same id
+            // from the same device
+            if let Some(index) = index {
+                self.slots[index].active = false;
+            }
+            if chunk.is_last() {
+                let mut header = B::default();
+                let mut body = B::default();
+                copy(&mut header, 0, chunk.header, &resize)?;
+                copy(&mut body, 0, chunk.body, &resize)?;
+                return Ok(Some(PropertyData {
+                    source: chunk.source,
+                    request_id: chunk.request_id,
+                    header,
+                    header_size: chunk.header.len(),
+                    body,
+                    body_size: chunk.body.len(),
+                }));
+            }
+            let index = self
+                .slots
+                .iter()
+                .position(|s| !s.active)
+                .ok_or(ChunkError::TooManyRequests)?;
+            let slot = &mut self.slots[index];
+            slot.source = chunk.source;
+            slot.request_id = chunk.request_id;
+            slot.header_size = 0;
+            slot.body_size = 0;
+            copy(&mut slot.header, 0, chunk.header, &resize)?;
+            slot.header_size = chunk.header.len();
+            slot.active = true;
+            return self.append(index, &chunk, &resize);
+        }
+
+        let Some(index) = index else {
+            return Err(ChunkError::UnexpectedChunk);
+        };
+        if self.slots[index].next_chunk != chunk.this_chunk {
+            self.slots[index].active = false;
+            return Err(ChunkError::UnexpectedChunk);
+        }
+        self.append(index, &chunk, &resize)
+    }
+
+    fn append<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        index: usize,
+        chunk: &Chunk,
+        resize: &R,
+    ) -> Result<Option<PropertyData<B>>, ChunkError> {
+        let slot = &mut self.slots[index];
+        if let Err(e) = copy(&mut slot.body, slot.body_size, chunk.body, resize) {
+            slot.active = false;
+            return Err(e.into());
+        }
+        slot.body_size += chunk.body.len();
+        slot.next_chunk = chunk.this_chunk + 1;
+
+        if !chunk.is_last() {
+            return Ok(None);
+        }
+
+        slot.active = false;
+        let mut header = core::mem::replace(&mut slot.header, B::default());
+        let mut body = core::mem::replace(&mut slot.body, B::default());
+        // shrink resizable buffers down to the data
+        let _ = resize(&mut header, slot.header_size);
+        let _ = resize(&mut body, slot.body_size);
+        Ok(Some(PropertyData {
+            source: slot.source,
+            request_id: slot.request_id,
+            header,
+            header_size: slot.header_size,
+            body,
+            body_size: slot.body_size,
+        }))
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferDefault, const N: usize> core::default::Default
+    for ChunkAssembler<B, N>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Bytes + BufferMut> Slot<B> {
+    fn matches(&self, source: ux::u28, request_id: ux::u7) -> bool {
+        self.active && self.source == source && self.request_id == request_id
+    }
+}
+
+struct Chunk<'a> {
+    source: ux::u28,
+    request_id: ux::u7,
+    number_of_chunks: u16,
+    this_chunk: u16,
+    header: &'a [u8],
+    body: &'a [u8],
+}
+
+impl<'a> Chunk<'a> {
+    fn read(data: &'a [u8]) -> Option<Self> {
+        if !is_property_data_status(*data.get(STATUS_INDEX)?) {
+            return None;
+        }
+        let chunk_index = chunk_index(data)?;
+        Some(Chunk {
+            source: ux::u28::from_u7s(data.get(SOURCE_INDEX..SOURCE_INDEX + 4)?),
+            request_id: ux::u7::new(data.get(REQUEST_ID_INDEX)? & 0x7F),
+            number_of_chunks: read_u14(data, chunk_index + NUMBER_OF_CHUNKS_OFFSET)? as u16,
+            this_chunk: read_u14(data, chunk_index + THIS_CHUNK_OFFSET)? as u16,
+            header: &data[header_range(data)?],
+            body: &data[body_range(data)?],
+        })
+    }
+
+    // a number of chunks of zero signals an unknown number of chunks,
+    // in which case the last chunk reports its own index as the number of chunks
+    fn is_last(&self) -> bool {
+        self.number_of_chunks == self.this_chunk
+    }
+}
+
+fn copy<B: Bytes + BufferMut, R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+    buffer: &mut B,
+    index: usize,
+    data: &[u8],
+    resize: &R,
+) -> Result<(), BufferOverflow> {
+    resize(buffer, index + data.len())?;
+    buffer.buffer_mut()[index..index + data.len()].copy_from_slice(data);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ci::{GetPropertyDataReply, Notify, PropertyExchangeCapabilities};
+    use pretty_assertions::assert_eq;
+
+    fn u7s(data: &[u8]) -> std::vec::Vec<ux::u7> {
+        data.iter().map(|b| ux::u7::new(*b)).collect()
+    }
+
+    fn chunk(
+        request_id: u8,
+        header: &[u8],
+        number_of_chunks: u16,
+        this_chunk: u16,
+        body: &[u8],
+    ) -> GetPropertyDataReply<0x2, std::vec::Vec<u8>> {
+        let mut message = GetPropertyDataReply::<0x2, std::vec::Vec<u8>>::new();
+        message.set_request_id(ux::u7::new(request_id));
+        message.set_header(&u7s(header));
+        message.set_number_of_chunks(ux::u14::new(number_of_chunks));
+        message.set_this_chunk(ux::u14::new(this_chunk));
+        message.set_body(&u7s(body));
+        message
+    }
+
+    #[test]
+    fn single_chunk() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        let data = assembler
+            .push(&chunk(0x1, b"{}", 1, 1, b"[1]"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.request_id(), ux::u7::new(0x1));
+        assert_eq!(data.header(), b"{}");
+        assert_eq!(data.body(), b"[1]");
+        assert!(!assembler.in_progress(ux::u28::new(0x0), ux::u7::new(0x1)));
+    }
+
+    #[test]
+    fn multiple_chunks() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&chunk(0x1, b"{}", 3, 1, b"ab")), Ok(None));
+        assert!(assembler.in_progress(ux::u28::new(0x0), ux::u7::new(0x1)));
+        assert_eq!(assembler.push(&chunk(0x1, b"", 3, 2, b"cd")), Ok(None));
+        let data = assembler
+            .push(&chunk(0x1, b"", 3, 3, b"ef"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.header(), b"{}");
+        assert_eq!(data.body(), b"abcdef");
+        assert!(!assembler.in_progress(ux::u28::new(0x0), ux::u7::new(0x1)));
+    }
+
+    #[test]
+    fn interleaved_requests() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 2>::new();
+        assert_eq!(assembler.push(&chunk(0x1, b"{1}", 2, 1, b"a")), Ok(None));
+        assert_eq!(assembler.push(&chunk(0x2, b"{2}", 2, 1, b"x")), Ok(None));
+        let data = assembler
+            .push(&chunk(0x2, b"", 2, 2, b"y"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.header(), b"{2}");
+        assert_eq!(data.body(), b"xy");
+        let data = assembler
+            .push(&chunk(0x1, b"", 2, 2, b"b"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.header(), b"{1}");
+        assert_eq!(data.body(), b"ab");
+    }
+
+    #[test]
+    fn same_request_id_from_different_devices() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 2>::new();
+        let from = |source: u32, mut message: GetPropertyDataReply<0x2, std::vec::Vec<u8>>| {
+            message.set_source(ux::u28::new(source));
+            message
+        };
+        assert_eq!(
+            assembler.push(&from(0xA, chunk(0x1, b"{a}", 2, 1, b"a"))),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.push(&from(0xB, chunk(0x1, b"{b}", 2, 1, b"x"))),
+            Ok(None)
+        );
+        assert!(assembler.in_progress(ux::u28::new(0xA), ux::u7::new(0x1)));
+        assert!(assembler.in_progress(ux::u28::new(0xB), ux::u7::new(0x1)));
+
+        let data = assembler
+            .push(&from(0xA, chunk(0x1, b"", 2, 2, b"b")))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.source(), ux::u28::new(0xA));
+        assert_eq!(data.header(), b"{a}");
+        assert_eq!(data.body(), b"ab");
+        let data = assembler
+            .push(&from(0xB, chunk(0x1, b"", 2, 2, b"y")))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.source(), ux::u28::new(0xB));
+        assert_eq!(data.header(), b"{b}");
+        assert_eq!(data.body(), b"xy");
+    }
+
+    #[test]
+    fn unknown_number_of_chunks() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&chunk(0x1, b"{}", 0, 1, b"a")), Ok(None));
+        assert_eq!(assembler.push(&chunk(0x1, b"", 0, 2, b"b")), Ok(None));
+        let data = assembler
+            .push(&chunk(0x1, b"", 3, 3, b"c"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.body(), b"abc");
+    }
+
+    #[test]
+    fn chunk_without_request() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(
+            assembler.push(&chunk(0x1, b"", 2, 2, b"b")),
+            Err(ChunkError::UnexpectedChunk)
+        );
+    }
+
+    #[test]
+    fn chunk_out_of_sequence() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&chunk(0x1, b"{}", 3, 1, b"a")), Ok(None));
+        assert_eq!(
+            assembler.push(&chunk(0x1, b"", 3, 3, b"c")),
+            Err(ChunkError::UnexpectedChunk)
+        );
+        assert!(!assembler.in_progress(ux::u28::new(0x0), ux::u7::new(0x1)));
+    }
+
+    #[test]
+    fn first_chunk_restarts_request() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&chunk(0x1, b"{a}", 2, 1, b"a")), Ok(None));
+        assert_eq!(assembler.push(&chunk(0x1, b"{b}", 2, 1, b"b")), Ok(None));
+        let data = assembler
+            .push(&chunk(0x1, b"", 2, 2, b"c"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.header(), b"{b}");
+        assert_eq!(data.body(), b"bc");
+    }
+
+    #[test]
+    fn too_many_requests() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&chunk(0x1, b"{}", 2, 1, b"a")), Ok(None));
+        assert_eq!(
+            assembler.push(&chunk(0x2, b"{}", 2, 1, b"a")),
+            Err(ChunkError::TooManyRequests)
+        );
+        assert!(assembler.in_progress(ux::u28::new(0x0), ux::u7::new(0x1)));
+    }
+
+    #[test]
+    fn not_property_data() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        let message = PropertyExchangeCapabilities::<0x2, std::vec::Vec<u8>>::new();
+        assert_eq!(assembler.push(&message), Err(ChunkError::NotPropertyData));
+    }
+
+    #[test]
+    fn other_property_data_messages() {
+        let mut assembler = ChunkAssembler::<std::vec::Vec<u8>, 1>::new();
+        let mut message = Notify::<0x2, std::vec::Vec<u8>>::new();
+        message.set_header(&u7s(b"{}"));
+        let data = assembler.push(&message).unwrap().unwrap();
+        assert_eq!(data.header(), b"{}");
+        assert!(data.body().is_empty());
+    }
+
+    #[test]
+    fn try_push_array() {
+        let mut assembler = ChunkAssembler::<[u8; 4], 1>::new();
+        assert_eq!(
+            assembler.try_push(&chunk(0x1, b"{}", 2, 1, b"ab")),
+            Ok(None)
+        );
+        let data = assembler
+            .try_push(&chunk(0x1, b"", 2, 2, b"cd"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.header(), b"{}");
+        assert_eq!(data.body(), b"abcd");
+    }
+
+    #[test]
+    fn try_push_overflow() {
+        let mut assembler = ChunkAssembler::<[u8; 4], 1>::new();
+        assert_eq!(
+            assembler.try_push(&chunk(0x1, b"{}", 2, 1, b"abc")),
+            Ok(None)
+        );
+        assert_eq!(
+            assembler.try_push(&chunk(0x1, b"", 2, 2, b"de")),
+            Err(ChunkError::BufferOverflow)
+        );
+        assert!(!assembler.in_progress(ux::u28::new(0x0), ux::u7::new(0x1)));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Bytes, Ump},
+    error::{BufferOverflow, Error, InvalidData},
+    sysex7::Sysex7,
+    ux::u4,
+    Grouped, Sysex,
+};
+
+const UNIVERSAL_SYSEX_NON_REALTIME: u8 = 0x7E;
+const UNIVERSAL_SYSEX_SUB_ID_1_MIDI_CI: u8 = 0x0D;
+const SYSEX_START: u8 = 0xF0;
+const SYSEX_END: u8 = 0xF7;
+
+const ERR_NOT_CI: &str = "Expected universal sysex header for MIDI-CI (0x7E, <device id>, 0x0D)";
+
+/// Wrap a MIDI-CI message into a [Sysex7] UMP message on the given group.
+///
+/// Accepts any CI message, or any other [Bytes] backed [Sysex7] message.
+///
+/// ```rust
+/// use midi2::{prelude::*, ci};
+///
+/// let mut query = ci::DiscoveryQuery::<0x2, Vec<u8>>::new();
+/// query.set_source(ux::u28::new(0x1234567));
+///
+/// let ump: sysex7::Sysex7<Vec<u32>> = ci::to_ump(&query, u4::new(0x3));
+///
+/// assert_eq!(ump.group(), u4::new(0x3));
+/// assert_eq!(&ump.data()[..2], &[0x3316_7E7F, 0x0D70_0267]);
+/// ```
+pub fn to_ump<M, A, B>(message: &M, group: u4) -> Sysex7<B>
+where
+    M: core::ops::Deref<Target = Sysex7<A>>,
+    A: Bytes,
+    B: Ump + BufferMut + BufferDefault + BufferResize,
+{
+    let mut ump = Sysex7::<B>::new();
+    ump.set_payload(message.payload());
+    ump.set_group(group);
+    ump
+}
+
+/// Wrap a MIDI-CI message into a [Sysex7] UMP message on the given group.
+///
+/// Fails if the message doesn't fit into the fixed size buffer.
+///
+/// ```rust
+/// use midi2::{prelude::*, ci};
+///
+/// let query = ci::DiscoveryQuery::<0x2, Vec<u8>>::new();
+///
+/// let ump = ci::try_to_ump::<_, _, [u32; 10]>(&query, u4::new(0x3));
+/// assert!(ump.is_ok());
+///
+/// let ump = ci::try_to_ump::<_, _, [u32; 8]>(&query, u4::new(0x3));
+/// assert!(ump.is_err());
+/// ```
+pub fn try_to_ump<M, A, B>(message: &M, group: u4) -> Result<Sysex7<B>, BufferOverflow>
+where
+    M: core::ops::Deref<Target = Sysex7<A>>,
+    A: Bytes,
+    B: Ump + BufferMut + BufferDefault + BufferTryResize,
+{
+    let mut ump = Sysex7::<B>::try_new()?;
+    ump.try_set_payload(message.payload())?;
+    ump.set_group(group);
+    Ok(ump)
+}
+
+/// Whether the sysex message starts with the universal sysex
+/// header of a MIDI-CI message.
+///
+/// Works with both [Ump] and [Bytes] backed messages.
+pub fn is_ci<B: crate::buffer::Buffer>(message: &Sysex7<B>) -> bool {
+    let mut payload = message.payload().map(u8::from);
+    payload.next() == Some(UNIVERSAL_SYSEX_NON_REALTIME)
+        && payload.next().is_some()
+        && payload.next() == Some(UNIVERSAL_SYSEX_SUB_ID_1_MIDI_CI)
+}
+
+/// Parse a MIDI-CI message out of a complete [Sysex7] UMP message.
+///
+/// The sysex data is copied into the provided buffer,
+/// and the message returned borrows from it.
+/// Fails with [Error::InvalidData] if the message isn't MIDI-CI, or doesn't
+/// parse as the requested message type, and with [Error::BufferOverflow]
+/// if the buffer is too small to hold the sysex data.
+///
+/// ```rust
+/// use midi2::{prelude::*, ci};
+///
+/// let query = ci::DiscoveryQuery::<0x2, Vec<u8>>::new();
+/// let ump: sysex7::Sysex7<Vec<u32>> = ci::to_ump(&query, u4::new(0x3));
+///
+/// let mut buffer = [0x0; 64];
+/// let parsed = ci::try_from_ump::<ci::DiscoveryQuery<0x2, _>, _>(&ump, &mut buffer)
+///     .expect("Valid CI message");
+///
+/// assert_eq!(parsed.data(), query.data());
+/// ```
+pub fn try_from_ump<'a, M, A>(message: &Sysex7<A>, buffer: &'a mut [u8]) -> Result<M, Error>
+where
+    M: core::convert::TryFrom<&'a [u8], Error = InvalidData>,
+    A: Ump,
+{
+    if !is_ci(message) {
+        return Err(InvalidData(ERR_NOT_CI).into());
+    }
+
+    let payload = message.payload();
+    let size = payload.len() + 2;
+    if buffer.len() < size {
+        return Err(BufferOverflow.into());
+    }
+
+    buffer[0] = SYSEX_START;
+    for (byte, value) in buffer[1..].iter_mut().zip(payload) {
+        *byte = value.into();
+    }
+    buffer[size - 1] = SYSEX_END;
+
+    Ok(M::try_from(&buffer[..size])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ci::DiscoveryQuery, Data};
+    use pretty_assertions::assert_eq;
+
+    fn query() -> DiscoveryQuery<0x2, std::vec::Vec<u8>> {
+        let mut message = DiscoveryQuery::<0x2, std::vec::Vec<u8>>::new();
+        message.set_source(crate::ux::u28::new(0xB48D9D9));
+        message.set_max_sysex_size(crate::ux::u28::new(0x200));
+        message
+    }
+
+    #[test]
+    fn to_ump_vec() {
+        let ump: Sysex7<std::vec::Vec<u32>> = to_ump(&query(), u4::new(0x5));
+        assert_eq!(
+            ump.data(),
+            &[
+                0x3516_7E7F,
+                0x0D70_0259,
+                0x3526_3323,
+                0x5A7F_7F7F,
+                0x3526_7F00,
+                0x0000_0000,
+                0x3526_0000,
+                0x0000_0000,
+                0x3536_0000,
+                0x0400_0000,
+            ]
+        );
+    }
+
+    #[test]
+    fn try_to_ump_array() {
+        let ump = try_to_ump::<_, _, [u32; 10]>(&query(), u4::new(0x5)).unwrap();
+        let expected: Sysex7<std::vec::Vec<u32>> = to_ump(&query(), u4::new(0x5));
+        assert_eq!(ump.data(), expected.data());
+    }
+
+    #[test]
+    fn try_to_ump_overflow() {
+        assert_eq!(
+            try_to_ump::<_, _, [u32; 8]>(&query(), u4::new(0x5)),
+            Err(BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn is_ci_ump() {
+        let ump: Sysex7<std::vec::Vec<u32>> = to_ump(&query(), u4::new(0x5));
+        assert!(is_ci(&ump));
+    }
+
+    #[test]
+    fn is_ci_bytes() {
+        assert!(is_ci(&query()));
+    }
+
+    #[test]
+    fn is_not_ci() {
+        let message = Sysex7::try_from(&[0x3003_7E7F_u32, 0x0600_0000][..]).unwrap();
+        assert!(!is_ci(&message));
+    }
+
+    #[test]
+    fn is_not_ci_short() {
+        let message = Sysex7::try_from(&[0x3002_7E7F_u32, 0x0000_0000][..]).unwrap();
+        assert!(!is_ci(&message));
+    }
+
+    #[test]
+    fn round_trip() {
+        let ump: Sysex7<std::vec::Vec<u32>> = to_ump(&query(), u4::new(0x5));
+        let mut buffer = [0x0; 64];
+        let parsed = try_from_ump::<DiscoveryQuery<0x2, _>, _>(&ump, &mut buffer).unwrap();
+        assert_eq!(parsed.data(), query().data());
+    }
+
+    #[test]
+    fn try_from_ump_not_ci() {
+        let message = Sysex7::try_from(&[0x3003_7E7F_u32, 0x0600_0000][..]).unwrap();
+        let mut buffer = [0x0; 64];
+        assert_eq!(
+            try_from_ump::<DiscoveryQuery<0x2, _>, _>(&message, &mut buffer),
+            Err(Error::InvalidData(InvalidData(ERR_NOT_CI)))
+        );
+    }
+
+    #[test]
+    fn try_from_ump_buffer_too_small() {
+        let ump: Sysex7<std::vec::Vec<u32>> = to_ump(&query(), u4::new(0x5));
+        let mut buffer = [0x0; 31];
+        assert_eq!(
+            try_from_ump::<DiscoveryQuery<0x2, _>, _>(&ump, &mut buffer),
+            Err(Error::BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn try_from_ump_wrong_message() {
+        let ump: Sysex7<std::vec::Vec<u32>> = to_ump(&query(), u4::new(0x5));
+        let mut buffer = [0x0; 64];
+        assert!(try_from_ump::<DiscoveryQuery<0x1, _>, _>(&ump, &mut buffer).is_err());
+    }
+}
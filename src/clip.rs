@@ -0,0 +1,12 @@
+#![doc = include_str!("clip/README.md")]
+
+mod reader;
+mod writer;
+
+pub use reader::*;
+pub use writer::*;
+
+/// The identifier at the start of every MIDI clip file.
+const FILE_HEADER: &[u8] = b"SMF2CLIP";
+
+const ERR_UNEXPECTED_END_OF_DATA: &str = "Unexpected end of data";
@@ -0,0 +1,468 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Ump},
+    clip::{ERR_UNEXPECTED_END_OF_DATA, FILE_HEADER},
+    detail::{helpers, BitOps},
+    error::{BufferOverflow, Error, InvalidData},
+    ump_stream::{EndOfClip, StartOfClip},
+    ump_stream_parser::{format, packet_size, Format},
+    utility::{DeltaClockstamp, DeltaClockstampTpq},
+    UmpMessage,
+};
+
+const ERR_MISSING_FILE_HEADER: &str = "Clip file doesn't start with SMF2CLIP";
+const ERR_MISSING_TICKS_PER_QUARTER_NOTE: &str =
+    "Clip configuration header has no delta clockstamp ticks per quarter note";
+const ERR_MISSING_START_OF_CLIP: &str = "Clip file has no start of clip";
+const ERR_MISSING_END_OF_CLIP: &str = "Clip file has no end of clip";
+
+/// A message of a clip along with its time in ticks since the start of the clip.
+pub type ClipEvent<'a> = (u64, UmpMessage<&'a [u32]>);
+
+/// Reads the messages of a MIDI clip file from a byte slice.
+///
+/// The file header and the configuration header are validated on construction.
+/// The configuration header must declare the ticks per quarter note
+/// and be followed by a start of clip message.
+///
+/// Each message is returned with its time in ticks since the start of the clip.
+/// Messages of the configuration header are returned with a time of zero.
+/// The delta clockstamps, the ticks per quarter note and the start and end of clip
+/// messages describe the structure of the file and aren't returned.
+/// Reading stops at the end of clip message.
+///
+/// The words of each message are copied out of the file into the buffer
+/// owned by the reader, and the returned [UmpMessage] borrows from that buffer.
+/// Readers backed by a resizable buffer accept messages of any length via
+/// [read](ClipReader::read).
+/// Readers backed by a fixed size buffer use [try_read](ClipReader::try_read),
+/// which reports a [BufferOverflow](crate::error::Error::BufferOverflow) when a
+/// message doesn't fit. That message is skipped.
+///
+/// ## Fixed Size Buffers
+///
+/// ```rust
+/// use midi2::{prelude::*, clip::ClipReader};
+///
+/// let data = [
+///     b'S', b'M', b'F', b'2', b'C', b'L', b'I', b'P',
+///     0x00, 0x40, 0x00, 0x00, // delta clockstamp
+///     0x00, 0x30, 0x01, 0xE0, // ticks per quarter note
+///     0x00, 0x40, 0x00, 0x00,
+///     0xF0, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // start of clip
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x40, 0x01, 0xE0,
+///     0x20, 0x90, 0x3C, 0x7F, // note on
+///     0x00, 0x40, 0x00, 0x00,
+///     0xF0, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // end of clip
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+/// ];
+///
+/// let mut reader = ClipReader::<[u32; 4]>::new(&data[..]).unwrap();
+/// assert_eq!(reader.ticks_per_quarter_note(), 480);
+///
+/// let (ticks, message) = reader.try_read().unwrap().unwrap();
+/// assert_eq!(ticks, 480);
+/// assert_eq!(message.data(), &[0x2090_3C7F]);
+///
+/// assert_eq!(reader.try_read(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClipReader<'a, B: Ump + BufferMut> {
+    data: &'a [u8],
+    position: usize,
+    buffer: B,
+    ticks_per_quarter_note: u16,
+    ticks: u64,
+    started: bool,
+    finished: bool,
+}
+
+impl<'a, B: Ump + BufferMut + BufferDefault> ClipReader<'a, B> {
+    /// Create a reader backed by a default constructed buffer.
+    pub fn new(data: &'a [u8]) -> Result<Self, InvalidData> {
+        Self::new_with_buffer(data, B::default())
+    }
+}
+
+impl<'a, B: Ump + BufferMut> ClipReader<'a, B> {
+    /// Create a reader which copies messages into the provided buffer.
+    pub fn new_with_buffer(data: &'a [u8], buffer: B) -> Result<Self, InvalidData> {
+        let Some(data) = data.strip_prefix(FILE_HEADER) else {
+            return Err(InvalidData(ERR_MISSING_FILE_HEADER));
+        };
+
+        let mut ticks_per_quarter_note = None;
+        let mut position = 0;
+        loop {
+            let Some(packet) = read_packet(data, &mut position) else {
+                return Err(InvalidData(ERR_MISSING_START_OF_CLIP));
+            };
+            let packet = packet?;
+            if let Ok(tpq) = DeltaClockstampTpq::try_from(packet.as_slice()) {
+                ticks_per_quarter_note = Some(tpq.time_data());
+            }
+            if StartOfClip::try_from(packet.as_slice()).is_ok() {
+                break;
+            }
+        }
+
+        Ok(ClipReader {
+            data,
+            position: 0,
+            buffer,
+            ticks_per_quarter_note: ticks_per_quarter_note
+                .ok_or(InvalidData(ERR_MISSING_TICKS_PER_QUARTER_NOTE))?,
+            ticks: 0,
+            started: false,
+            finished: false,
+        })
+    }
+
+    /// The resolution of the delta clockstamps, as declared in the configuration header.
+    pub fn ticks_per_quarter_note(&self) -> u16 {
+        self.ticks_per_quarter_note
+    }
+
+    /// The time in ticks of the most recent delta clockstamp since the start of the clip.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    fn read_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        resize: R,
+    ) -> Option<Result<ClipEvent<'_>, Error>> {
+        if self.finished {
+            return None;
+        }
+
+        let mut size = 0;
+        let mut in_progress = false;
+        let mut overflow = false;
+        let mut time = self.ticks;
+        let mut ump_type = 0;
+
+        loop {
+            let previous_position = self.position;
+            let packet = match read_packet(self.data, &mut self.position) {
+                None => return self.fail(InvalidData(ERR_MISSING_END_OF_CLIP)),
+                Some(Err(e)) => return self.fail(e),
+                Some(Ok(packet)) => packet,
+            };
+            let packet = packet.as_slice();
+
+            if let Ok(delta) = DeltaClockstamp::try_from(packet) {
+                if self.started {
+                    self.ticks += u64::from(delta.time_data());
+                }
+                continue;
+            }
+            if DeltaClockstampTpq::try_from(packet).is_ok() {
+                continue;
+            }
+            if !in_progress {
+                if StartOfClip::try_from(packet).is_ok() {
+                    self.started = true;
+                    continue;
+                }
+                if EndOfClip::try_from(packet).is_ok() {
+                    self.finished = true;
+                    return None;
+                }
+            }
+
+            match (in_progress, format(packet)) {
+                (false, None | Some(Format::Complete)) => {
+                    time = self.ticks;
+                }
+                (false, Some(Format::Start)) => {
+                    time = self.ticks;
+                    ump_type = u8::from(packet[0].nibble(0));
+                    in_progress = true;
+                }
+                (false, Some(Format::Continue | Format::End)) => {
+                    return Some(Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_BEGIN).into()));
+                }
+                (true, Some(Format::Continue)) if u8::from(packet[0].nibble(0)) == ump_type => {}
+                (true, Some(Format::End)) if u8::from(packet[0].nibble(0)) == ump_type => {
+                    in_progress = false;
+                }
+                (true, _) => {
+                    // leave the interrupting packet to be read as the next message
+                    self.position = previous_position;
+                    return Some(Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_END).into()));
+                }
+            }
+
+            if !overflow {
+                match resize(&mut self.buffer, size + packet.len()) {
+                    Ok(()) => {
+                        self.buffer.buffer_mut()[size..size + packet.len()].copy_from_slice(packet);
+                        size += packet.len();
+                    }
+                    Err(_) => overflow = true,
+                }
+            }
+
+            if !in_progress {
+                break;
+            }
+        }
+
+        if overflow {
+            return Some(Err(Error::BufferOverflow));
+        }
+
+        Some(
+            UmpMessage::try_from(&self.buffer.buffer()[..size])
+                .map(|message| (time, message))
+                .map_err(Error::from),
+        )
+    }
+
+    fn fail(&mut self, error: InvalidData) -> Option<Result<ClipEvent<'_>, Error>> {
+        self.finished = true;
+        Some(Err(error.into()))
+    }
+}
+
+impl<'a, B: Ump + BufferMut + BufferResize> ClipReader<'a, B> {
+    /// Read the next message of the clip along with its time in ticks.
+    ///
+    /// Returns `None` once the end of the clip has been read.
+    /// A file which ends before the end of clip message fails with an error,
+    /// after which reading stops.
+    pub fn read(&mut self) -> Option<Result<ClipEvent<'_>, InvalidData>> {
+        self.read_impl(|buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .map(|result| {
+            result.map_err(|e| match e {
+                Error::InvalidData(e) => e,
+                Error::BufferOverflow => unreachable!("Resizable buffers should not overflow"),
+            })
+        })
+    }
+}
+
+impl<'a, B: Ump + BufferMut + BufferTryResize> ClipReader<'a, B> {
+    /// Read the next message of the clip along with its time in ticks.
+    ///
+    /// Returns `None` once the end of the clip has been read.
+    /// Fails with [BufferOverflow](crate::error::Error::BufferOverflow)
+    /// when a message exceeds the capacity of the buffer.
+    pub fn try_read(&mut self) -> Option<Result<ClipEvent<'_>, Error>> {
+        self.read_impl(|buffer, size| buffer.try_resize(size))
+    }
+}
+
+/// A single packet read from the big endian words of the file.
+struct ClipPacket {
+    words: [u32; 4],
+    size: usize,
+}
+
+impl ClipPacket {
+    fn as_slice(&self) -> &[u32] {
+        &self.words[..self.size]
+    }
+}
+
+fn read_packet(data: &[u8], position: &mut usize) -> Option<Result<ClipPacket, InvalidData>> {
+    let remaining = &data[*position..];
+    let first = remaining.first()?;
+    let size = packet_size(first >> 4);
+    if remaining.len() < 4 * size {
+        *position = data.len();
+        return Some(Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA)));
+    }
+
+    let mut words = [0x0; 4];
+    for (word, bytes) in words.iter_mut().zip(remaining[..4 * size].chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    *position += 4 * size;
+    Some(Ok(ClipPacket { words, size }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+    use std::vec::Vec;
+
+    fn clip(words: &[u32]) -> Vec<u8> {
+        let mut data = FILE_HEADER.to_vec();
+        for word in words {
+            data.extend_from_slice(&word.to_be_bytes());
+        }
+        data
+    }
+
+    const HEADER: [u32; 7] = [
+        0x0040_0000,
+        0x0030_0060,
+        0x0040_0000,
+        0xF020_0000,
+        0x0,
+        0x0,
+        0x0,
+    ];
+    const END: [u32; 5] = [0x0040_0000, 0xF021_0000, 0x0, 0x0, 0x0];
+
+    fn read_all(data: &[u8]) -> Vec<Result<(u64, Vec<u32>), InvalidData>> {
+        let mut reader = ClipReader::<Vec<u32>>::new(data).unwrap();
+        let mut messages = Vec::new();
+        while let Some(result) = reader.read() {
+            messages.push(result.map(|(ticks, message)| (ticks, message.data().to_vec())));
+        }
+        messages
+    }
+
+    #[test]
+    fn missing_file_header() {
+        assert_eq!(
+            ClipReader::<Vec<u32>>::new(b"SMF2CLIX").err(),
+            Some(InvalidData(ERR_MISSING_FILE_HEADER)),
+        );
+    }
+
+    #[test]
+    fn missing_ticks_per_quarter_note() {
+        let data = clip(&[0x0040_0000, 0xF020_0000, 0x0, 0x0, 0x0]);
+        assert_eq!(
+            ClipReader::<Vec<u32>>::new(&data[..]).err(),
+            Some(InvalidData(ERR_MISSING_TICKS_PER_QUARTER_NOTE)),
+        );
+    }
+
+    #[test]
+    fn missing_start_of_clip() {
+        let data = clip(&[0x0040_0000, 0x0030_0060]);
+        assert_eq!(
+            ClipReader::<Vec<u32>>::new(&data[..]).err(),
+            Some(InvalidData(ERR_MISSING_START_OF_CLIP)),
+        );
+    }
+
+    #[test]
+    fn ticks_per_quarter_note() {
+        let data = clip(&HEADER);
+        let reader = ClipReader::<Vec<u32>>::new(&data[..]).unwrap();
+        assert_eq!(reader.ticks_per_quarter_note(), 96);
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn accumulates_delta_clockstamps() {
+        let mut words = HEADER.to_vec();
+        words.extend_from_slice(&[0x0040_0010, 0x4090_3C00, 0xFFFF_0000]);
+        words.extend_from_slice(&[0x0040_FFFF, 0x0040_0001, 0x4080_3C00, 0x0]);
+        words.extend_from_slice(&END);
+        assert_eq!(
+            read_all(&clip(&words)[..]),
+            std::vec![
+                Ok((0x10, std::vec![0x4090_3C00, 0xFFFF_0000])),
+                Ok((0x10 + 0x1_0000, std::vec![0x4080_3C00, 0x0])),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "flex-data")]
+    fn configuration_messages_have_no_time() {
+        let mut words = std::vec![0x0040_0000, 0x0030_0060];
+        words.extend_from_slice(&[0x0040_0000, 0xD010_0000, 0x0007_A120, 0x0, 0x0]);
+        words.extend_from_slice(&HEADER[2..]);
+        words.extend_from_slice(&END);
+        assert_eq!(
+            read_all(&clip(&words)[..]),
+            std::vec![Ok((0, std::vec![0xD010_0000, 0x0007_A120]))],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn multi_packet_messages() {
+        let mut words = HEADER.to_vec();
+        words.extend_from_slice(&[0x0040_0004, 0x3016_0001, 0x0203_0405]);
+        words.extend_from_slice(&[0x0040_0000, 0x3032_0607, 0x0]);
+        words.extend_from_slice(&END);
+        assert_eq!(
+            read_all(&clip(&words)[..]),
+            std::vec![Ok((
+                4,
+                std::vec![0x3016_0001, 0x0203_0405, 0x3032_0607, 0x0]
+            ))],
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "channel-voice1"))]
+    fn interrupted_multi_packet_message() {
+        let mut words = HEADER.to_vec();
+        words.extend_from_slice(&[0x0040_0000, 0x3016_0001, 0x0203_0405]);
+        words.extend_from_slice(&[0x0040_0000, 0x2090_3C7F]);
+        words.extend_from_slice(&END);
+        assert_eq!(
+            read_all(&clip(&words)[..]),
+            std::vec![
+                Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_END)),
+                Ok((0, std::vec![0x2090_3C7F])),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn missing_end_of_clip() {
+        let mut words = HEADER.to_vec();
+        words.extend_from_slice(&[0x0040_0000, 0x2090_3C7F]);
+        assert_eq!(
+            read_all(&clip(&words)[..]),
+            std::vec![
+                Ok((0, std::vec![0x2090_3C7F])),
+                Err(InvalidData(ERR_MISSING_END_OF_CLIP)),
+            ],
+        );
+    }
+
+    #[test]
+    fn truncated_packet() {
+        let mut data = clip(&HEADER);
+        data.extend_from_slice(&[0x40, 0x90, 0x3C, 0x00]);
+        assert_eq!(
+            read_all(&data[..]),
+            std::vec![Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA))],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn stops_at_end_of_clip() {
+        let mut words = HEADER.to_vec();
+        words.extend_from_slice(&END);
+        words.extend_from_slice(&[0x0040_0000, 0x2090_3C7F]);
+        assert_eq!(read_all(&clip(&words)[..]), Vec::new());
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn fixed_size_buffer_overflow() {
+        let mut words = HEADER.to_vec();
+        words.extend_from_slice(&[0x0040_0000, 0x3016_0001, 0x0203_0405]);
+        words.extend_from_slice(&[0x0040_0000, 0x3026_0607, 0x0809_0A0B]);
+        words.extend_from_slice(&[0x0040_0000, 0x3032_0C0D, 0x0]);
+        words.extend_from_slice(&[0x0040_0002, 0x3002_0E0F, 0x0]);
+        words.extend_from_slice(&END);
+        let data = clip(&words);
+        let mut reader = ClipReader::<[u32; 4]>::new(&data[..]).unwrap();
+        assert_eq!(reader.try_read(), Some(Err(Error::BufferOverflow)));
+        let (ticks, message) = reader.try_read().unwrap().unwrap();
+        assert_eq!(ticks, 2);
+        assert_eq!(message.data(), &[0x3002_0E0F, 0x0]);
+        assert_eq!(reader.try_read(), None);
+    }
+}
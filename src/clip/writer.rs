@@ -0,0 +1,375 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Bytes},
+    clip::FILE_HEADER,
+    detail::BitOps,
+    error::BufferOverflow,
+    ump_stream::{EndOfClip, StartOfClip},
+    ump_stream_parser::packet_size,
+    utility::{DeltaClockstamp, DeltaClockstampTpq},
+    Data, Packets,
+};
+
+// the largest delta time of a single delta clockstamp
+const MAX_DELTA_CLOCKSTAMP: u32 = 0xFFFF;
+
+/// Writes a MIDI clip file into a byte buffer.
+///
+/// The file header, the configuration header declaring the ticks per quarter note
+/// and the start of clip message are written along with the first message.
+/// The clip is completed with [end_clip](ClipWriter::end_clip), which writes
+/// the end of clip message.
+///
+/// Every packet is preceded by a delta clockstamp. Delta times which exceed the
+/// range of a single delta clockstamp are split over several consecutive clockstamps.
+/// The packets of a multi packet message, e.g. a `Sysex7<B>`, follow one another
+/// with delta times of zero.
+///
+/// Writers backed by a resizable buffer grow as needed. Writers backed by a
+/// fixed size buffer use the `try_` methods, which fail when the data doesn't fit,
+/// in which case nothing is written.
+///
+/// Writing to a clip which has already ended panics.
+///
+/// ## Fixed Size Buffers
+///
+/// ```rust
+/// use midi2::{prelude::*, error::BufferOverflow, clip::ClipWriter};
+///
+/// let mut writer = ClipWriter::<[u8; 56]>::new(480);
+///
+/// let note_on = channel_voice1::NoteOn::<[u32; 4]>::new();
+/// assert_eq!(writer.try_write(0, &note_on), Ok(()));
+/// assert_eq!(writer.data().len(), 44);
+///
+/// // no room for the end of clip
+/// assert_eq!(writer.try_end_clip(0), Err(BufferOverflow));
+/// assert_eq!(writer.data().len(), 44);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClipWriter<B: Bytes + BufferMut> {
+    buffer: B,
+    size: usize,
+    ticks_per_quarter_note: u16,
+    ended: bool,
+}
+
+impl<B: Bytes + BufferMut + BufferDefault> ClipWriter<B> {
+    /// Create a writer backed by a default constructed buffer.
+    pub fn new(ticks_per_quarter_note: u16) -> Self {
+        Self::new_with_buffer(B::default(), ticks_per_quarter_note)
+    }
+}
+
+impl<B: Bytes + BufferMut> ClipWriter<B> {
+    /// Create a writer which writes into the provided buffer.
+    pub fn new_with_buffer(buffer: B, ticks_per_quarter_note: u16) -> Self {
+        ClipWriter {
+            buffer,
+            size: 0,
+            ticks_per_quarter_note,
+            ended: false,
+        }
+    }
+
+    /// The bytes written so far.
+    pub fn data(&self) -> &[u8] {
+        &self.buffer.buffer()[..self.size]
+    }
+
+    pub fn ticks_per_quarter_note(&self) -> u16 {
+        self.ticks_per_quarter_note
+    }
+
+    /// Whether the end of clip message has been written.
+    pub fn is_ended(&self) -> bool {
+        self.ended
+    }
+
+    fn write_impl<M: Packets, R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        delta: u32,
+        message: &M,
+        resize: R,
+    ) -> Result<(), BufferOverflow> {
+        assert!(!self.ended, "The clip has already ended");
+
+        // every packet after the first is preceded by a delta clockstamp of zero
+        let number_of_packets = message.packets().len();
+        let number_of_words: usize = message.packets().map(|p| size_of_packet(&p)).sum();
+        let message_size = 4 * (number_of_words + number_of_packets.saturating_sub(1));
+        let size = self.size + self.header_size() + delta_size(delta) + message_size;
+        resize(&mut self.buffer, size)?;
+
+        self.write_header();
+        self.write_delta(delta);
+        for (i, packet) in message.packets().enumerate() {
+            if i != 0 {
+                self.write_delta(0);
+            }
+            self.write_packet(&packet);
+        }
+        Ok(())
+    }
+
+    fn end_clip_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        delta: u32,
+        resize: R,
+    ) -> Result<(), BufferOverflow> {
+        assert!(!self.ended, "The clip has already ended");
+
+        let end_of_clip = EndOfClip::<[u32; 4]>::new();
+        let size = self.size
+            + self.header_size()
+            + delta_size(delta)
+            + 4 * size_of_packet(end_of_clip.data());
+        resize(&mut self.buffer, size)?;
+
+        self.write_header();
+        self.write_delta(delta);
+        self.write_packet(end_of_clip.data());
+        self.ended = true;
+        Ok(())
+    }
+
+    // the size of the file and configuration headers and the start of clip,
+    // if they have not been written yet
+    fn header_size(&self) -> usize {
+        if self.size == 0 {
+            let tpq = DeltaClockstampTpq::<[u32; 1]>::new();
+            let start_of_clip = StartOfClip::<[u32; 4]>::new();
+            FILE_HEADER.len()
+                + delta_size(0)
+                + 4 * size_of_packet(tpq.data())
+                + delta_size(0)
+                + 4 * size_of_packet(start_of_clip.data())
+        } else {
+            0
+        }
+    }
+
+    fn write_header(&mut self) {
+        if self.size != 0 {
+            return;
+        }
+        let mut tpq = DeltaClockstampTpq::<[u32; 1]>::new();
+        tpq.set_time_data(self.ticks_per_quarter_note);
+
+        self.write_bytes(FILE_HEADER);
+        self.write_delta(0);
+        self.write_packet(tpq.data());
+        self.write_delta(0);
+        self.write_packet(StartOfClip::<[u32; 4]>::new().data());
+    }
+
+    fn write_delta(&mut self, mut delta: u32) {
+        loop {
+            let time_data = delta.min(MAX_DELTA_CLOCKSTAMP);
+            let mut message = DeltaClockstamp::<[u32; 1]>::new();
+            message.set_time_data(time_data as u16);
+            self.write_packet(message.data());
+            delta -= time_data;
+            if delta == 0 {
+                break;
+            }
+        }
+    }
+
+    // messages may be represented with fewer words than their packet size,
+    // the missing words are written as zeros
+    fn write_packet(&mut self, packet: &[u32]) {
+        for i in 0..size_of_packet(packet) {
+            let word = packet.get(i).copied().unwrap_or(0x0);
+            self.write_bytes(&word.to_be_bytes());
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.buffer_mut()[self.size..self.size + bytes.len()].copy_from_slice(bytes);
+        self.size += bytes.len();
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferResize> ClipWriter<B> {
+    /// Append the message to the clip, `delta` ticks after the previous message.
+    pub fn write<M: Packets>(&mut self, delta: u32, message: &M) {
+        self.write_impl(delta, message, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .expect("Resizable buffers should not fail here")
+    }
+
+    /// End the clip with an end of clip message, `delta` ticks after the previous message.
+    ///
+    /// The delta time of the end of clip message sets the length of the clip.
+    pub fn end_clip(&mut self, delta: u32) {
+        self.end_clip_impl(delta, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .expect("Resizable buffers should not fail here")
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferTryResize> ClipWriter<B> {
+    /// Append the message to the clip, `delta` ticks after the previous message.
+    ///
+    /// Fails if the message doesn't fit in the remaining space of the buffer,
+    /// in which case nothing is written.
+    pub fn try_write<M: Packets>(&mut self, delta: u32, message: &M) -> Result<(), BufferOverflow> {
+        self.write_impl(delta, message, |buffer, size| buffer.try_resize(size))
+    }
+
+    /// End the clip with an end of clip message, `delta` ticks after the previous message.
+    ///
+    /// Fails if the end of clip doesn't fit in the remaining space of the buffer,
+    /// in which case nothing is written.
+    pub fn try_end_clip(&mut self, delta: u32) -> Result<(), BufferOverflow> {
+        self.end_clip_impl(delta, |buffer, size| buffer.try_resize(size))
+    }
+}
+
+// the number of words of the packet as written to the file
+fn size_of_packet(packet: &[u32]) -> usize {
+    packet_size(u8::from(packet[0].nibble(0)))
+}
+
+// the number of bytes of the delta clockstamps of the given delta time
+fn delta_size(delta: u32) -> usize {
+    4 * (delta.max(1) as usize).div_ceil(MAX_DELTA_CLOCKSTAMP as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clip::ClipReader, ux::u7};
+    use pretty_assertions::assert_eq;
+    use std::vec::Vec;
+
+    fn words(data: &[u8]) -> Vec<u32> {
+        data[FILE_HEADER.len()..]
+            .chunks_exact(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+
+    #[test]
+    fn empty() {
+        let writer = ClipWriter::<Vec<u8>>::new(96);
+        assert_eq!(writer.data(), &[0u8; 0]);
+    }
+
+    #[test]
+    fn header() {
+        let mut writer = ClipWriter::<Vec<u8>>::new(96);
+        writer.end_clip(0);
+        assert!(writer.is_ended());
+        assert_eq!(&writer.data()[..8], b"SMF2CLIP");
+        assert_eq!(
+            words(writer.data()),
+            std::vec![
+                0x0040_0000,
+                0x0030_0060,
+                0x0040_0000,
+                0xF020_0000,
+                0x0,
+                0x0,
+                0x0,
+                0x0040_0000,
+                0xF021_0000,
+                0x0,
+                0x0,
+                0x0,
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn delta_clockstamps() {
+        let mut writer = ClipWriter::<Vec<u8>>::new(96);
+        let mut note_on = crate::channel_voice2::NoteOn::<[u32; 4]>::new();
+        note_on.set_note_number(u7::new(0x3C));
+        writer.write(0x10, &note_on);
+        writer.end_clip(0x1_0000);
+        assert_eq!(
+            words(writer.data())[7..],
+            [
+                0x0040_0010,
+                0x4090_3C00,
+                0x0,
+                0x0040_FFFF,
+                0x0040_0001,
+                0xF021_0000,
+                0x0,
+                0x0,
+                0x0,
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn multi_packet_messages() {
+        let mut writer = ClipWriter::<Vec<u8>>::new(96);
+        let mut sysex = crate::sysex7::Sysex7::<Vec<u32>>::new();
+        crate::Sysex::set_payload(&mut sysex, (0..8).map(u7::new));
+        writer.write(2, &sysex);
+        assert_eq!(
+            words(writer.data())[7..],
+            [
+                0x0040_0002,
+                0x3016_0001,
+                0x0203_0405,
+                0x0040_0000,
+                0x3032_0607,
+                0x0,
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice1")]
+    fn fixed_size_buffer() {
+        let mut writer = ClipWriter::<[u8; 52]>::new(96);
+        let note_on = crate::channel_voice1::NoteOn::<[u32; 4]>::new();
+        assert_eq!(writer.try_write(0, &note_on), Ok(()));
+        assert_eq!(writer.try_write(0, &note_on), Ok(()));
+        assert_eq!(writer.try_write(0, &note_on), Err(BufferOverflow));
+        assert_eq!(writer.data().len(), 52);
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_after_end() {
+        let mut writer = ClipWriter::<Vec<u8>>::new(96);
+        writer.end_clip(0);
+        writer.end_clip(0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "sysex7"))]
+    fn round_trip() {
+        let mut writer = ClipWriter::<Vec<u8>>::new(480);
+        let mut note_on = crate::channel_voice1::NoteOn::<[u32; 4]>::new();
+        note_on.set_note_number(u7::new(0x3C));
+        note_on.set_velocity(u7::new(0x7F));
+        let mut sysex = crate::sysex7::Sysex7::<Vec<u32>>::new();
+        crate::Sysex::set_payload(&mut sysex, (0..20).map(u7::new));
+        writer.write(0, &note_on);
+        writer.write(0x2_0000, &sysex);
+        writer.end_clip(480);
+
+        let mut reader = ClipReader::<Vec<u32>>::new(writer.data()).unwrap();
+        assert_eq!(reader.ticks_per_quarter_note(), 480);
+        let (ticks, message) = reader.read().unwrap().unwrap();
+        assert_eq!(ticks, 0);
+        assert_eq!(message.data(), note_on.data());
+        let (ticks, message) = reader.read().unwrap().unwrap();
+        assert_eq!(ticks, 0x2_0000);
+        assert_eq!(message.data(), sysex.data());
+        assert_eq!(reader.read(), None);
+        assert_eq!(reader.ticks(), 0x2_0000 + 480);
+    }
+}
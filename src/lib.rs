@@ -11,14 +11,22 @@ pub mod channel_voice1;
 pub mod channel_voice2;
 #[cfg(feature = "ci")]
 pub mod ci;
+#[cfg(feature = "clip")]
+pub mod clip;
 #[cfg(feature = "flex-data")]
 pub mod flex_data;
+#[cfg(feature = "mixed-data-set")]
+pub mod mixed_data_set;
+#[cfg(feature = "smf")]
+pub mod smf;
 #[cfg(feature = "sysex7")]
 pub mod sysex7;
 #[cfg(feature = "sysex8")]
 pub mod sysex8;
 #[cfg(feature = "system-common")]
 pub mod system_common;
+#[cfg(all(feature = "channel-voice1", feature = "channel-voice2"))]
+pub mod translation;
 #[cfg(feature = "ump-stream")]
 pub mod ump_stream;
 #[cfg(feature = "utility")]
@@ -27,17 +35,43 @@ pub mod utility;
 pub mod buffer;
 pub mod error;
 
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+mod bytes_stream_parser;
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+mod bytes_stream_writer;
 mod detail;
 mod message;
 mod packet;
 mod packets;
 mod traits;
+mod ump_stream_parser;
 
 pub use ux;
 
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+pub use bytes_stream_parser::BytesStreamParser;
+#[cfg(any(
+    feature = "channel-voice1",
+    feature = "sysex7",
+    feature = "system-common"
+))]
+pub use bytes_stream_writer::BytesStreamWriter;
 pub use message::*;
 pub use packets::*;
 pub use traits::*;
+pub use ump_stream_parser::UmpStreamParser;
 
 pub mod num {
     pub use ux::*;
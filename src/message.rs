@@ -17,6 +17,8 @@ pub enum UmpMessage<B: crate::buffer::Ump> {
     ChannelVoice1(crate::channel_voice1::ChannelVoice1<B>),
     #[cfg(feature = "channel-voice2")]
     ChannelVoice2(crate::channel_voice2::ChannelVoice2<B>),
+    #[cfg(feature = "mixed-data-set")]
+    MixedDataSet(crate::mixed_data_set::MixedDataSet<B>),
     #[cfg(feature = "sysex7")]
     Sysex7(crate::sysex7::Sysex7<B>),
     #[cfg(feature = "sysex8")]
@@ -52,6 +54,12 @@ impl<'a> core::convert::TryFrom<&'a [u32]> for UmpMessage<&'a [u32]> {
             crate::channel_voice2::UMP_MESSAGE_TYPE => {
                 ChannelVoice2(crate::channel_voice2::ChannelVoice2::try_from(buffer)?)
             }
+            #[cfg(feature = "mixed-data-set")]
+            crate::mixed_data_set::UMP_MESSAGE_TYPE
+                if crate::mixed_data_set::is_mixed_data_set(buffer) =>
+            {
+                MixedDataSet(crate::mixed_data_set::MixedDataSet::try_from(buffer)?)
+            }
             #[cfg(feature = "sysex7")]
             crate::sysex7::UMP_MESSAGE_TYPE => Sysex7(crate::sysex7::Sysex7::try_from(buffer)?),
             #[cfg(feature = "sysex8")]
@@ -206,6 +214,24 @@ mod tests {
         };
     }
 
+    #[cfg(feature = "mixed-data-set")]
+    #[test]
+    fn mixed_data_set() {
+        use crate::mixed_data_set::MixedDataSet;
+
+        let buffer = [0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002];
+        let message = UmpMessage::try_from(&buffer[..]);
+        let Ok(UmpMessage::MixedDataSet(MixedDataSet::Header(_))) = message else {
+            panic!();
+        };
+
+        let buffer = [0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E];
+        let message = UmpMessage::try_from(&buffer[..]);
+        let Ok(UmpMessage::MixedDataSet(MixedDataSet::Payload(_))) = message else {
+            panic!();
+        };
+    }
+
     #[cfg(feature = "sysex7")]
     #[test]
     fn sysex7() {
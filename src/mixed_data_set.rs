@@ -0,0 +1,150 @@
+#![doc = include_str!("mixed_data_set/README.md")]
+
+use crate::detail::BitOps;
+
+mod assembler;
+mod header;
+mod packet;
+mod payload;
+
+pub use assembler::*;
+pub use header::*;
+pub use packet::Packet;
+pub use payload::*;
+
+pub(crate) const UMP_MESSAGE_TYPE: u8 = 0x5;
+
+#[derive(
+    derive_more::From,
+    midi2_proc::Data,
+    midi2_proc::Packets,
+    midi2_proc::Grouped,
+    midi2_proc::RebufferFrom,
+    midi2_proc::RebufferFromArray,
+    midi2_proc::TryRebufferFrom,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+)]
+#[non_exhaustive]
+pub enum MixedDataSet<B: crate::buffer::Ump> {
+    Header(header::MixedDataSetHeader<B>),
+    Payload(payload::MixedDataSetPayload<B>),
+}
+
+impl<'a> core::convert::TryFrom<&'a [u32]> for MixedDataSet<&'a [u32]> {
+    type Error = crate::error::InvalidData;
+    fn try_from(buffer: &'a [u32]) -> Result<Self, Self::Error> {
+        if buffer.is_empty() {
+            return Err(crate::error::InvalidData(
+                crate::detail::common_err_strings::ERR_SLICE_TOO_SHORT,
+            ));
+        };
+        Ok(match u8::from(buffer[0].nibble(2)) {
+            header::STATUS => header::MixedDataSetHeader::try_from(buffer)?.into(),
+            payload::STATUS => payload::MixedDataSetPayload::try_from(buffer)?.into(),
+            _ => Err(crate::error::InvalidData(
+                "Unknown mixed data set message status",
+            ))?,
+        })
+    }
+}
+
+impl<B: crate::buffer::Ump> MixedDataSet<B> {
+    /// Identifies the mixed data set which the message belongs to.
+    pub fn mds_id(&self) -> crate::ux::u4 {
+        match self {
+            MixedDataSet::Header(m) => m.mds_id(),
+            MixedDataSet::Payload(m) => m.mds_id(),
+        }
+    }
+}
+
+/// Whether the packet is a mixed data set message rather than
+/// system exclusive 8bit data. Both share the same ump message type.
+pub(crate) fn is_mixed_data_set(packet: &[u32]) -> bool {
+    matches!(
+        packet
+            .first()
+            .map(|w| (u8::from(w.nibble(0)), u8::from(w.nibble(2)))),
+        Some((UMP_MESSAGE_TYPE, header::STATUS | payload::STATUS))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn from_header_data() {
+        let buffer = [0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002];
+        assert_eq!(
+            MixedDataSet::try_from(&buffer[..]),
+            Ok(MixedDataSet::Header(
+                MixedDataSetHeader::try_from(&buffer[..]).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn from_payload_data() {
+        let buffer = [0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E];
+        assert_eq!(
+            MixedDataSet::try_from(&buffer[..]),
+            Ok(MixedDataSet::Payload(
+                MixedDataSetPayload::try_from(&buffer[..]).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn from_sysex8_data() {
+        assert_eq!(
+            MixedDataSet::try_from(&[0x5001_0000, 0x0, 0x0, 0x0][..]),
+            Err(crate::error::InvalidData(
+                "Unknown mixed data set message status"
+            )),
+        );
+    }
+
+    #[test]
+    fn mds_id() {
+        let buffer = [0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E];
+        assert_eq!(
+            MixedDataSet::try_from(&buffer[..]).unwrap().mds_id(),
+            crate::ux::u4::new(0x2)
+        );
+    }
+
+    #[test]
+    fn group() {
+        use crate::Grouped;
+
+        let buffer = [0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002];
+        assert_eq!(
+            MixedDataSet::try_from(&buffer[..]).unwrap().group(),
+            crate::ux::u4::new(0x4)
+        );
+    }
+
+    #[test]
+    fn packets() {
+        use crate::Packets;
+
+        let buffer = [0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E];
+        let message = MixedDataSet::try_from(&buffer[..]).unwrap();
+        let mut packets = message.packets();
+        assert_eq!(&*packets.next().unwrap(), &buffer[..]);
+        assert_eq!(packets.next(), None);
+    }
+
+    #[test]
+    fn is_mixed_data_set_packet() {
+        assert!(is_mixed_data_set(&[0x5080_0000, 0x0, 0x0, 0x0]));
+        assert!(is_mixed_data_set(&[0x5090_0000, 0x0, 0x0, 0x0]));
+        assert!(!is_mixed_data_set(&[0x5000_0000, 0x0, 0x0, 0x0]));
+        assert!(!is_mixed_data_set(&[0x4080_0000, 0x0, 0x0, 0x0]));
+    }
+}
@@ -0,0 +1,532 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Bytes, Ump},
+    error::BufferOverflow,
+    mixed_data_set::{MixedDataSet, MixedDataSetHeader, PAYLOAD_SIZE},
+    ux::u4,
+    Grouped,
+};
+
+/// Errors reported by the [MixedDataSetAssembler].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssemblyError {
+    /// A payload message arrived for a mixed data set with no chunk in progress.
+    /// The message is discarded.
+    UnexpectedPayload,
+    /// The header doesn't follow on from the previous chunk of the mixed data set.
+    /// The header and the unfinished mixed data set are discarded.
+    UnexpectedChunk,
+    /// The first chunk of a new mixed data set arrived while all the slots
+    /// of the assembler were taken. The header is discarded.
+    TooManyDataSets,
+    /// The data doesn't fit into the storage of the assembler.
+    /// The unfinished mixed data set is discarded.
+    BufferOverflow,
+}
+
+impl core::convert::From<BufferOverflow> for AssemblyError {
+    fn from(_value: BufferOverflow) -> Self {
+        AssemblyError::BufferOverflow
+    }
+}
+
+/// The identifiers and complete data of a mixed data set,
+/// rebuilt from its chunks by the [MixedDataSetAssembler].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixedData<B: Bytes> {
+    group: u4,
+    mds_id: u4,
+    manufacturer_id: u16,
+    device_id: u16,
+    sub_id_1: u16,
+    sub_id_2: u16,
+    data: B,
+    size: usize,
+}
+
+impl<B: Bytes> MixedData<B> {
+    pub fn group(&self) -> u4 {
+        self.group
+    }
+
+    pub fn mds_id(&self) -> u4 {
+        self.mds_id
+    }
+
+    pub fn manufacturer_id(&self) -> u16 {
+        self.manufacturer_id
+    }
+
+    pub fn device_id(&self) -> u16 {
+        self.device_id
+    }
+
+    pub fn sub_id_1(&self) -> u16 {
+        self.sub_id_1
+    }
+
+    pub fn sub_id_2(&self) -> u16 {
+        self.sub_id_2
+    }
+
+    /// The valid payload bytes of all the chunks, in order.
+    pub fn data(&self) -> &[u8] {
+        &self.data.buffer()[..self.size]
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<B: Bytes + BufferMut> {
+    active: bool,
+    group: u4,
+    mds_id: u4,
+    manufacturer_id: u16,
+    device_id: u16,
+    sub_id_1: u16,
+    sub_id_2: u16,
+    number_of_chunks: u16,
+    this_chunk: u16,
+    // payload bytes of the current chunk which are yet to arrive
+    remaining: usize,
+    data: B,
+    size: usize,
+}
+
+/// Rebuilds large binary objects from the chunks of mixed data sets.
+///
+/// Each chunk is opened by a [MixedDataSetHeader](crate::mixed_data_set::MixedDataSetHeader)
+/// announcing the number of valid bytes in the chunk, followed by as many
+/// [MixedDataSetPayload](crate::mixed_data_set::MixedDataSetPayload) messages
+/// as it takes to carry them.
+/// Chunks of up to `N` mixed data sets can be interleaved, each mixed data set
+/// identified by its group and mds id.
+///
+/// ```rust
+/// use midi2::{prelude::*, mixed_data_set::*};
+///
+/// let mut header = MixedDataSetHeader::<[u32; 4]>::new();
+/// header.set_mds_id(u4::new(0x1));
+/// header.set_number_of_valid_bytes(16);
+/// header.set_number_of_chunks(1);
+/// header.set_this_chunk(1);
+/// header.set_manufacturer_id(0x41);
+///
+/// let mut first = MixedDataSetPayload::<[u32; 4]>::new();
+/// first.set_mds_id(u4::new(0x1));
+/// first.set_payload(*b"Sample data 01");
+///
+/// let mut second = MixedDataSetPayload::<[u32; 4]>::new();
+/// second.set_mds_id(u4::new(0x1));
+/// second.set_payload(*b"23\0\0\0\0\0\0\0\0\0\0\0\0");
+///
+/// let mut assembler = MixedDataSetAssembler::<Vec<u8>, 4>::new();
+/// assert_eq!(assembler.push(&header.into()), Ok(None));
+/// assert_eq!(assembler.push(&first.into()), Ok(None));
+///
+/// let data = assembler.push(&second.into()).unwrap().expect("Mixed data set complete");
+/// assert_eq!(data.manufacturer_id(), 0x41);
+/// assert_eq!(data.data(), b"Sample data 0123");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MixedDataSetAssembler<B: Bytes + BufferMut, const N: usize> {
+    slots: [Slot<B>; N],
+}
+
+impl<B: Bytes + BufferMut + BufferDefault, const N: usize> MixedDataSetAssembler<B, N> {
+    pub fn new() -> Self {
+        MixedDataSetAssembler {
+            slots: core::array::from_fn(|_| Slot {
+                active: false,
+                group: Default::default(),
+                mds_id: Default::default(),
+                manufacturer_id: 0,
+                device_id: 0,
+                sub_id_1: 0,
+                sub_id_2: 0,
+                number_of_chunks: 0,
+                this_chunk: 0,
+                remaining: 0,
+                data: B::default(),
+                size: 0,
+            }),
+        }
+    }
+
+    /// Discard every mixed data set in progress.
+    pub fn reset(&mut self) {
+        for slot in self.slots.iter_mut() {
+            slot.active = false;
+        }
+    }
+
+    /// Whether chunks of the given mixed data set are awaited.
+    pub fn in_progress(&self, group: u4, mds_id: u4) -> bool {
+        self.position(group, mds_id).is_some()
+    }
+
+    /// Feed the next mixed data set message to the assembler.
+    ///
+    /// Returns the data once the last payload of the last chunk arrives.
+    pub fn push<A: Ump>(
+        &mut self,
+        message: &MixedDataSet<A>,
+    ) -> Result<Option<MixedData<B>>, AssemblyError>
+    where
+        B: BufferResize,
+    {
+        self.push_impl(message, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+    }
+
+    /// Feed the next mixed data set message to the assembler.
+    ///
+    /// Returns the data once the last payload of the last chunk arrives.
+    /// Fails with [AssemblyError::BufferOverflow] when the data
+    /// doesn't fit into the fixed size buffers.
+    pub fn try_push<A: Ump>(
+        &mut self,
+        message: &MixedDataSet<A>,
+    ) -> Result<Option<MixedData<B>>, AssemblyError>
+    where
+        B: BufferTryResize,
+    {
+        self.push_impl(message, |buffer, size| buffer.try_resize(size))
+    }
+
+    fn position(&self, group: u4, mds_id: u4) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|s| s.active && s.group == group && s.mds_id == mds_id)
+    }
+
+    fn push_impl<A: Ump, R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        message: &MixedDataSet<A>,
+        resize: R,
+    ) -> Result<Option<MixedData<B>>, AssemblyError> {
+        let index = self.position(message.group(), message.mds_id());
+        match message {
+            MixedDataSet::Header(header) => self.header(index, header, &resize),
+            MixedDataSet::Payload(payload) => {
+                let Some(index) = index.filter(|i| self.slots[*i].remaining > 0) else {
+                    return Err(AssemblyError::UnexpectedPayload);
+                };
+                self.payload(index, &payload.payload(), &resize)
+            }
+        }
+    }
+
+    fn header<A: Ump, R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        index: Option<usize>,
+        header: &MixedDataSetHeader<A>,
+        resize: &R,
+    ) -> Result<Option<MixedData<B>>, AssemblyError> {
+        let index = if header.this_chunk() == 1 {
+            // a first chunk restarts any mixed data set in progress with the same id
+            if let Some(index) = index {
+                self.slots[index].active = false;
+            }
+            let index = self
+                .slots
+                .iter()
+                .position(|s| !s.active)
+                .ok_or(AssemblyError::TooManyDataSets)?;
+            let slot = &mut self.slots[index];
+            slot.group = header.group();
+            slot.mds_id = header.mds_id();
+            slot.manufacturer_id = header.manufacturer_id();
+            slot.device_id = header.device_id();
+            slot.sub_id_1 = header.sub_id_1();
+            slot.sub_id_2 = header.sub_id_2();
+            slot.number_of_chunks = header.number_of_chunks();
+            slot.size = 0;
+            slot.active = true;
+            index
+        } else {
+            let Some(index) = index else {
+                return Err(AssemblyError::UnexpectedChunk);
+            };
+            let slot = &self.slots[index];
+            if slot.remaining != 0 || slot.this_chunk + 1 != header.this_chunk() {
+                self.slots[index].active = false;
+                return Err(AssemblyError::UnexpectedChunk);
+            }
+            index
+        };
+
+        let slot = &mut self.slots[index];
+        slot.this_chunk = header.this_chunk();
+        slot.remaining = header.number_of_valid_bytes() as usize;
+        if slot.remaining == 0 {
+            return Ok(self.finish(index, resize));
+        }
+        Ok(None)
+    }
+
+    fn payload<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        index: usize,
+        payload: &[u8; PAYLOAD_SIZE],
+        resize: &R,
+    ) -> Result<Option<MixedData<B>>, AssemblyError> {
+        let slot = &mut self.slots[index];
+        let bytes = &payload[..slot.remaining.min(PAYLOAD_SIZE)];
+        if let Err(e) = resize(&mut slot.data, slot.size + bytes.len()) {
+            slot.active = false;
+            return Err(e.into());
+        }
+        slot.data.buffer_mut()[slot.size..slot.size + bytes.len()].copy_from_slice(bytes);
+        slot.size += bytes.len();
+        slot.remaining -= bytes.len();
+
+        if slot.remaining == 0 {
+            return Ok(self.finish(index, resize));
+        }
+        Ok(None)
+    }
+
+    // called once the current chunk is complete
+    fn finish<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        index: usize,
+        resize: &R,
+    ) -> Option<MixedData<B>> {
+        let slot = &mut self.slots[index];
+        if slot.this_chunk != slot.number_of_chunks {
+            return None;
+        }
+
+        slot.active = false;
+        let mut data = core::mem::replace(&mut slot.data, B::default());
+        // shrink resizable buffers down to the data
+        let _ = resize(&mut data, slot.size);
+        Some(MixedData {
+            group: slot.group,
+            mds_id: slot.mds_id,
+            manufacturer_id: slot.manufacturer_id,
+            device_id: slot.device_id,
+            sub_id_1: slot.sub_id_1,
+            sub_id_2: slot.sub_id_2,
+            data,
+            size: slot.size,
+        })
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferDefault, const N: usize> core::default::Default
+    for MixedDataSetAssembler<B, N>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mixed_data_set::MixedDataSetPayload;
+    use pretty_assertions::assert_eq;
+
+    fn header(
+        mds_id: u8,
+        number_of_valid_bytes: u16,
+        number_of_chunks: u16,
+        this_chunk: u16,
+    ) -> MixedDataSet<[u32; 4]> {
+        let mut message = MixedDataSetHeader::<[u32; 4]>::new();
+        message.set_mds_id(u4::new(mds_id));
+        message.set_number_of_valid_bytes(number_of_valid_bytes);
+        message.set_number_of_chunks(number_of_chunks);
+        message.set_this_chunk(this_chunk);
+        message.set_manufacturer_id(0x41);
+        message.set_device_id(0x10);
+        message.set_sub_id_1(0x1);
+        message.set_sub_id_2(0x2);
+        message.into()
+    }
+
+    fn payload(mds_id: u8, first_byte: u8) -> MixedDataSet<[u32; 4]> {
+        let mut message = MixedDataSetPayload::<[u32; 4]>::new();
+        message.set_mds_id(u4::new(mds_id));
+        message.set_payload(core::array::from_fn(|i| first_byte + i as u8));
+        message.into()
+    }
+
+    fn bytes(first_byte: u8, len: usize) -> std::vec::Vec<u8> {
+        (0..len).map(|i| first_byte + i as u8).collect()
+    }
+
+    #[test]
+    fn single_chunk() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 20, 1, 1)), Ok(None));
+        assert!(assembler.in_progress(u4::new(0x0), u4::new(0x0)));
+        assert_eq!(assembler.push(&payload(0x0, 0x0)), Ok(None));
+        let data = assembler.push(&payload(0x0, 0xE)).unwrap().unwrap();
+        assert_eq!(data.data(), &bytes(0x0, 20)[..]);
+        assert_eq!(data.mds_id(), u4::new(0x0));
+        assert_eq!(data.manufacturer_id(), 0x41);
+        assert_eq!(data.device_id(), 0x10);
+        assert_eq!(data.sub_id_1(), 0x1);
+        assert_eq!(data.sub_id_2(), 0x2);
+        assert!(!assembler.in_progress(u4::new(0x0), u4::new(0x0)));
+    }
+
+    #[test]
+    fn multiple_chunks() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 14, 2, 1)), Ok(None));
+        assert_eq!(assembler.push(&payload(0x0, 0x0)), Ok(None));
+        assert_eq!(assembler.push(&header(0x0, 3, 2, 2)), Ok(None));
+        let data = assembler.push(&payload(0x0, 0xE)).unwrap().unwrap();
+        assert_eq!(data.data(), &bytes(0x0, 17)[..]);
+    }
+
+    #[test]
+    fn interleaved_data_sets() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 2>::new();
+        assert_eq!(assembler.push(&header(0x1, 4, 1, 1)), Ok(None));
+        assert_eq!(assembler.push(&header(0x2, 4, 1, 1)), Ok(None));
+        let second = assembler.push(&payload(0x2, 0x20)).unwrap().unwrap();
+        let first = assembler.push(&payload(0x1, 0x10)).unwrap().unwrap();
+        assert_eq!(first.mds_id(), u4::new(0x1));
+        assert_eq!(first.data(), &[0x10, 0x11, 0x12, 0x13]);
+        assert_eq!(second.mds_id(), u4::new(0x2));
+        assert_eq!(second.data(), &[0x20, 0x21, 0x22, 0x23]);
+    }
+
+    #[test]
+    fn groups_are_independent() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 2>::new();
+        let mut other_group = header(0x0, 4, 1, 1);
+        other_group.set_group(u4::new(0x3));
+        assert_eq!(assembler.push(&header(0x0, 4, 1, 1)), Ok(None));
+        assert_eq!(assembler.push(&other_group), Ok(None));
+        assert!(assembler.in_progress(u4::new(0x3), u4::new(0x0)));
+        let data = assembler.push(&payload(0x0, 0x0)).unwrap().unwrap();
+        assert_eq!(data.group(), u4::new(0x0));
+        assert!(assembler.in_progress(u4::new(0x3), u4::new(0x0)));
+    }
+
+    #[test]
+    fn empty_data_set() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        let data = assembler.push(&header(0x0, 0, 1, 1)).unwrap().unwrap();
+        assert_eq!(data.data(), &[0u8; 0]);
+    }
+
+    #[test]
+    fn payload_without_header() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(
+            assembler.push(&payload(0x0, 0x0)),
+            Err(AssemblyError::UnexpectedPayload)
+        );
+    }
+
+    #[test]
+    fn payload_beyond_chunk() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 4, 2, 1)), Ok(None));
+        assert_eq!(assembler.push(&payload(0x0, 0x0)), Ok(None));
+        assert_eq!(
+            assembler.push(&payload(0x0, 0x0)),
+            Err(AssemblyError::UnexpectedPayload)
+        );
+    }
+
+    #[test]
+    fn chunk_out_of_sequence() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 4, 3, 1)), Ok(None));
+        assert_eq!(assembler.push(&payload(0x0, 0x0)), Ok(None));
+        assert_eq!(
+            assembler.push(&header(0x0, 4, 3, 3)),
+            Err(AssemblyError::UnexpectedChunk)
+        );
+        assert!(!assembler.in_progress(u4::new(0x0), u4::new(0x0)));
+    }
+
+    #[test]
+    fn header_before_chunk_complete() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 20, 2, 1)), Ok(None));
+        assert_eq!(assembler.push(&payload(0x0, 0x0)), Ok(None));
+        assert_eq!(
+            assembler.push(&header(0x0, 4, 2, 2)),
+            Err(AssemblyError::UnexpectedChunk)
+        );
+    }
+
+    #[test]
+    fn first_chunk_restarts_data_set() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 20, 1, 1)), Ok(None));
+        assert_eq!(assembler.push(&payload(0x0, 0x0)), Ok(None));
+        assert_eq!(assembler.push(&header(0x0, 2, 1, 1)), Ok(None));
+        let data = assembler.push(&payload(0x0, 0x30)).unwrap().unwrap();
+        assert_eq!(data.data(), &[0x30, 0x31]);
+    }
+
+    #[test]
+    fn too_many_data_sets() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 4, 1, 1)), Ok(None));
+        assert_eq!(
+            assembler.push(&header(0x1, 4, 1, 1)),
+            Err(AssemblyError::TooManyDataSets)
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        assert_eq!(assembler.push(&header(0x0, 4, 1, 1)), Ok(None));
+        assembler.reset();
+        assert!(!assembler.in_progress(u4::new(0x0), u4::new(0x0)));
+    }
+
+    #[test]
+    fn try_push_array() {
+        let mut assembler = MixedDataSetAssembler::<[u8; 32], 1>::new();
+        assert_eq!(assembler.try_push(&header(0x0, 6, 1, 1)), Ok(None));
+        let data = assembler.try_push(&payload(0x0, 0x0)).unwrap().unwrap();
+        assert_eq!(data.data(), &bytes(0x0, 6)[..]);
+    }
+
+    #[test]
+    fn try_push_overflow() {
+        let mut assembler = MixedDataSetAssembler::<[u8; 16], 1>::new();
+        assert_eq!(assembler.try_push(&header(0x0, 20, 1, 1)), Ok(None));
+        assert_eq!(assembler.try_push(&payload(0x0, 0x0)), Ok(None));
+        assert_eq!(
+            assembler.try_push(&payload(0x0, 0x0)),
+            Err(AssemblyError::BufferOverflow)
+        );
+        assert!(!assembler.in_progress(u4::new(0x0), u4::new(0x0)));
+    }
+
+    #[test]
+    fn from_ump_data() {
+        let buffer = [
+            0x5080_0003,
+            0x0001_0001,
+            0x0041_0010,
+            0x0001_0002,
+            0x5090_0102,
+            0x0300_0000,
+            0x0000_0000,
+            0x0000_0000,
+        ];
+        let mut assembler = MixedDataSetAssembler::<std::vec::Vec<u8>, 1>::new();
+        let mut result = None;
+        for packet in buffer.chunks_exact(4) {
+            result = assembler
+                .push(&MixedDataSet::try_from(packet).unwrap())
+                .unwrap();
+        }
+        assert_eq!(result.unwrap().data(), &[0x01, 0x02, 0x03]);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    detail::{common_properties, schema},
+    mixed_data_set::{self, UMP_MESSAGE_TYPE},
+    ux::u4,
+};
+
+pub(crate) const STATUS: u8 = 0x8;
+
+/// Opens a chunk of a mixed data set.
+///
+/// Announces the number of payload bytes carried by the following
+/// [MixedDataSetPayload](crate::mixed_data_set::MixedDataSetPayload) messages,
+/// the position of the chunk within the mixed data set and the
+/// identifiers of the data.
+#[midi2_proc::generate_message(Via(mixed_data_set::MixedDataSet), FixedSize, MinSizeUmp(4))]
+struct MixedDataSetHeader {
+    #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
+    ump_type: (),
+    #[property(common_properties::ChannelVoiceStatusProperty<STATUS>)]
+    status: (),
+    #[property(common_properties::GroupProperty)]
+    group: u4,
+    #[property(common_properties::UmpSchemaProperty<u4, schema::Ump<0x000F_0000, 0x0, 0x0, 0x0>>)]
+    mds_id: u4,
+    #[property(common_properties::UmpSchemaProperty<u16, schema::Ump<0x0000_FFFF, 0x0, 0x0, 0x0>>)]
+    number_of_valid_bytes: u16,
+    #[property(common_properties::UmpSchemaProperty<u16, schema::Ump<0x0, 0xFFFF_0000, 0x0, 0x0>>)]
+    number_of_chunks: u16,
+    #[property(common_properties::UmpSchemaProperty<u16, schema::Ump<0x0, 0x0000_FFFF, 0x0, 0x0>>)]
+    this_chunk: u16,
+    #[property(common_properties::UmpSchemaProperty<u16, schema::Ump<0x0, 0x0, 0xFFFF_0000, 0x0>>)]
+    manufacturer_id: u16,
+    #[property(common_properties::UmpSchemaProperty<u16, schema::Ump<0x0, 0x0, 0x0000_FFFF, 0x0>>)]
+    device_id: u16,
+    #[property(common_properties::UmpSchemaProperty<u16, schema::Ump<0x0, 0x0, 0x0, 0xFFFF_0000>>)]
+    sub_id_1: u16,
+    #[property(common_properties::UmpSchemaProperty<u16, schema::Ump<0x0, 0x0, 0x0, 0x0000_FFFF>>)]
+    sub_id_2: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Grouped;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn builder() {
+        let mut message = MixedDataSetHeader::<[u32; 4]>::new();
+        message.set_group(u4::new(0x4));
+        message.set_mds_id(u4::new(0x2));
+        message.set_number_of_valid_bytes(0x1C);
+        message.set_number_of_chunks(0x2);
+        message.set_this_chunk(0x1);
+        message.set_manufacturer_id(0x41);
+        message.set_device_id(0x10);
+        message.set_sub_id_1(0x1);
+        message.set_sub_id_2(0x2);
+        assert_eq!(
+            message,
+            MixedDataSetHeader([0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002]),
+        );
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(
+            MixedDataSetHeader::<[u32; 4]>::new(),
+            MixedDataSetHeader([0x5080_0000, 0x0, 0x0, 0x0]),
+        );
+    }
+
+    #[test]
+    fn group() {
+        assert_eq!(
+            MixedDataSetHeader::try_from(&[0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002][..])
+                .unwrap()
+                .group(),
+            u4::new(0x4),
+        );
+    }
+
+    #[test]
+    fn mds_id() {
+        assert_eq!(
+            MixedDataSetHeader::try_from(&[0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002][..])
+                .unwrap()
+                .mds_id(),
+            u4::new(0x2),
+        );
+    }
+
+    #[test]
+    fn number_of_valid_bytes() {
+        assert_eq!(
+            MixedDataSetHeader::try_from(&[0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002][..])
+                .unwrap()
+                .number_of_valid_bytes(),
+            0x1C,
+        );
+    }
+
+    #[test]
+    fn chunks() {
+        let message =
+            MixedDataSetHeader::try_from(&[0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002][..])
+                .unwrap();
+        assert_eq!(message.number_of_chunks(), 0x2);
+        assert_eq!(message.this_chunk(), 0x1);
+    }
+
+    #[test]
+    fn ids() {
+        let message =
+            MixedDataSetHeader::try_from(&[0x5482_001C, 0x0002_0001, 0x0041_0010, 0x0001_0002][..])
+                .unwrap();
+        assert_eq!(message.manufacturer_id(), 0x41);
+        assert_eq!(message.device_id(), 0x10);
+        assert_eq!(message.sub_id_1(), 0x1);
+        assert_eq!(message.sub_id_2(), 0x2);
+    }
+
+    #[test]
+    fn payload_status() {
+        assert_eq!(
+            MixedDataSetHeader::try_from(&[0x5492_001C, 0x0, 0x0, 0x0][..]),
+            Err(crate::error::InvalidData("Incorrect message status")),
+        );
+    }
+
+    #[test]
+    fn short_slice() {
+        assert!(MixedDataSetHeader::try_from(&[0x5482_001C, 0x0002_0001][..]).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{error, mixed_data_set};
+
+#[derive(Eq, PartialEq, Clone, midi2_proc::Debug)]
+pub struct Packet(pub(crate) [u32; 4]);
+
+impl crate::traits::BufferAccess<[u32; 4]> for Packet {
+    fn buffer_access(&self) -> &[u32; 4] {
+        &self.0
+    }
+    fn buffer_access_mut(&mut self) -> &mut [u32; 4]
+    where
+        [u32; 4]: crate::buffer::BufferMut,
+    {
+        &mut self.0
+    }
+}
+
+impl<'a> core::convert::TryFrom<&'a [u32]> for Packet {
+    type Error = error::InvalidData;
+    fn try_from(data: &'a [u32]) -> Result<Self, Self::Error> {
+        if data.len() < 4 {
+            return Err(error::InvalidData(
+                crate::detail::common_err_strings::ERR_SLICE_TOO_SHORT,
+            ));
+        }
+
+        use crate::detail::BitOps;
+        if u8::from(data[0].nibble(0)) != mixed_data_set::UMP_MESSAGE_TYPE {
+            return Err(error::InvalidData(
+                crate::detail::common_err_strings::ERR_INCORRECT_UMP_MESSAGE_TYPE,
+            ));
+        }
+
+        if !mixed_data_set::is_mixed_data_set(data) {
+            return Err(error::InvalidData("Invalid mixed data set status"));
+        }
+
+        Ok(Packet({
+            let mut buffer = [0x0; 4];
+            buffer.copy_from_slice(&data[..4]);
+            buffer
+        }))
+    }
+}
+
+impl core::ops::Deref for Packet {
+    type Target = [u32];
+    fn deref(&self) -> &Self::Target {
+        &self.0[..]
+    }
+}
+
+impl crate::Grouped<[u32; 4]> for Packet {
+    fn group(&self) -> crate::ux::u4 {
+        use crate::detail::BitOps;
+        self.0[0].nibble(1)
+    }
+    fn set_group(&mut self, group: crate::ux::u4)
+    where
+        [u32; 4]: crate::buffer::BufferMut,
+    {
+        use crate::detail::BitOps;
+        self.0[0].set_nibble(1, group);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn construction() {
+        assert!(Packet::try_from(&[0x5080_0000, 0x0, 0x0, 0x0][..]).is_ok());
+    }
+
+    #[test]
+    fn construction_short_slice() {
+        assert_eq!(
+            Packet::try_from(&[0x5080_0000, 0x0, 0x0][..]),
+            Err(error::InvalidData(
+                crate::detail::common_err_strings::ERR_SLICE_TOO_SHORT
+            )),
+        );
+    }
+
+    #[test]
+    fn construction_incorrect_ump_type() {
+        assert_eq!(
+            Packet::try_from(&[0x0080_0000, 0x0, 0x0, 0x0][..]),
+            Err(error::InvalidData(
+                crate::detail::common_err_strings::ERR_INCORRECT_UMP_MESSAGE_TYPE
+            )),
+        );
+    }
+
+    #[test]
+    fn construction_sysex8_status() {
+        assert_eq!(
+            Packet::try_from(&[0x5000_0000, 0x0, 0x0, 0x0][..]),
+            Err(error::InvalidData("Invalid mixed data set status")),
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{
+    detail::{common_properties, property, schema},
+    mixed_data_set::{self, UMP_MESSAGE_TYPE},
+    ux::u4,
+};
+
+pub(crate) const STATUS: u8 = 0x9;
+
+/// The number of payload bytes carried by each [MixedDataSetPayload] message.
+pub const PAYLOAD_SIZE: usize = 14;
+
+/// Carries the payload bytes of a chunk of a mixed data set.
+///
+/// Every message carries [PAYLOAD_SIZE] bytes. The number of valid bytes
+/// in the last message of a chunk follows from the
+/// [number of valid bytes](crate::mixed_data_set::MixedDataSetHeader::number_of_valid_bytes)
+/// announced by the header of the chunk. The remaining bytes are padding.
+#[midi2_proc::generate_message(Via(mixed_data_set::MixedDataSet), FixedSize, MinSizeUmp(4))]
+struct MixedDataSetPayload {
+    #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
+    ump_type: (),
+    #[property(common_properties::ChannelVoiceStatusProperty<STATUS>)]
+    status: (),
+    #[property(common_properties::GroupProperty)]
+    group: u4,
+    #[property(common_properties::UmpSchemaProperty<u4, schema::Ump<0x000F_0000, 0x0, 0x0, 0x0>>)]
+    mds_id: u4,
+    #[property(PayloadProperty)]
+    payload: [u8; PAYLOAD_SIZE],
+}
+
+struct PayloadProperty;
+
+impl<B: crate::buffer::Ump> property::Property<B> for PayloadProperty {
+    type Type = [u8; PAYLOAD_SIZE];
+}
+
+impl<'a, B: crate::buffer::Ump> property::ReadProperty<'a, B> for PayloadProperty {
+    fn read(buffer: &'a B) -> Self::Type {
+        use crate::detail::BitOps;
+        let buffer = buffer.buffer();
+        core::array::from_fn(|i| {
+            let index = i + 2;
+            buffer[index / 4].octet(index % 4)
+        })
+    }
+    fn validate(_buffer: &B) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+}
+
+impl<B: crate::buffer::Ump + crate::buffer::BufferMut> property::WriteProperty<B>
+    for PayloadProperty
+{
+    fn write(buffer: &mut B, payload: Self::Type) {
+        use crate::detail::BitOps;
+        let buffer = buffer.buffer_mut();
+        for (i, byte) in payload.into_iter().enumerate() {
+            let index = i + 2;
+            buffer[index / 4].set_octet(index % 4, byte);
+        }
+    }
+    fn validate(_v: &Self::Type) -> Result<(), crate::error::InvalidData> {
+        Ok(())
+    }
+    fn default() -> Self::Type {
+        Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Grouped;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn builder() {
+        let mut message = MixedDataSetPayload::<[u32; 4]>::new();
+        message.set_group(u4::new(0x4));
+        message.set_mds_id(u4::new(0x2));
+        message.set_payload([
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        ]);
+        assert_eq!(
+            message,
+            MixedDataSetPayload([0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E]),
+        );
+    }
+
+    #[test]
+    fn new() {
+        assert_eq!(
+            MixedDataSetPayload::<[u32; 4]>::new(),
+            MixedDataSetPayload([0x5090_0000, 0x0, 0x0, 0x0]),
+        );
+    }
+
+    #[test]
+    fn group() {
+        assert_eq!(
+            MixedDataSetPayload::try_from(
+                &[0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E][..]
+            )
+            .unwrap()
+            .group(),
+            u4::new(0x4),
+        );
+    }
+
+    #[test]
+    fn mds_id() {
+        assert_eq!(
+            MixedDataSetPayload::try_from(
+                &[0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E][..]
+            )
+            .unwrap()
+            .mds_id(),
+            u4::new(0x2),
+        );
+    }
+
+    #[test]
+    fn payload() {
+        assert_eq!(
+            MixedDataSetPayload::try_from(
+                &[0x5492_0102, 0x0304_0506, 0x0708_090A, 0x0B0C_0D0E][..]
+            )
+            .unwrap()
+            .payload(),
+            [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E],
+        );
+    }
+
+    #[test]
+    fn header_status() {
+        assert_eq!(
+            MixedDataSetPayload::try_from(&[0x5482_0000, 0x0, 0x0, 0x0][..]),
+            Err(crate::error::InvalidData("Incorrect message status")),
+        );
+    }
+}
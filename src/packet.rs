@@ -7,6 +7,7 @@ pub enum Packet {
     ChannelVoice1(crate::channel_voice1::Packet),
     ChannelVoice2(crate::channel_voice2::Packet),
     FlexData(crate::flex_data::Packet),
+    MixedDataSet(crate::mixed_data_set::Packet),
     Sysex7(crate::sysex7::Packet),
     Sysex8(crate::sysex8::Packet),
     SystemCommon(crate::system_common::Packet),
@@ -21,6 +22,7 @@ impl core::ops::Deref for Packet {
             Self::ChannelVoice1(p) => p.deref(),
             Self::ChannelVoice2(p) => p.deref(),
             Self::FlexData(p) => p.deref(),
+            Self::MixedDataSet(p) => p.deref(),
             Self::Sysex7(p) => p.deref(),
             Self::Sysex8(p) => p.deref(),
             Self::SystemCommon(p) => p.deref(),
@@ -34,8 +36,8 @@ impl<'a> core::convert::TryFrom<&'a [u32]> for Packet {
     type Error = crate::error::InvalidData;
     fn try_from(data: &'a [u32]) -> Result<Self, Self::Error> {
         use crate::{
-            channel_voice1, channel_voice2, detail::BitOps, flex_data, sysex7, sysex8,
-            system_common, ump_stream, utility,
+            channel_voice1, channel_voice2, detail::BitOps, flex_data, mixed_data_set, sysex7,
+            sysex8, system_common, ump_stream, utility,
         };
 
         if data.is_empty() {
@@ -47,6 +49,9 @@ impl<'a> core::convert::TryFrom<&'a [u32]> for Packet {
             channel_voice2::UMP_MESSAGE_TYPE => Ok(channel_voice2::Packet::try_from(data)?.into()),
             flex_data::UMP_MESSAGE_TYPE => Ok(flex_data::Packet::try_from(data)?.into()),
             sysex7::UMP_MESSAGE_TYPE => Ok(sysex7::Packet::try_from(data)?.into()),
+            mixed_data_set::UMP_MESSAGE_TYPE if mixed_data_set::is_mixed_data_set(data) => {
+                Ok(mixed_data_set::Packet::try_from(data)?.into())
+            }
             sysex8::UMP_MESSAGE_TYPE => Ok(sysex8::Packet::try_from(data)?.into()),
             system_common::UMP_MESSAGE_TYPE => Ok(system_common::Packet::try_from(data)?.into()),
             ump_stream::UMP_MESSAGE_TYPE => Ok(ump_stream::Packet::try_from(data)?.into()),
@@ -0,0 +1,229 @@
+#![doc = include_str!("smf/README.md")]
+
+use crate::error::InvalidData;
+
+mod event;
+mod reader;
+mod writer;
+
+pub use event::*;
+pub use reader::*;
+pub use writer::*;
+
+const HEADER_CHUNK_TYPE: &[u8] = b"MThd";
+const TRACK_CHUNK_TYPE: &[u8] = b"MTrk";
+const HEADER_SIZE: usize = 6;
+
+const ERR_UNEXPECTED_END_OF_DATA: &str = "Unexpected end of data";
+const ERR_VARIABLE_LENGTH_QUANTITY_TOO_LONG: &str = "Variable length quantity exceeds four bytes";
+
+/// The organisation of the tracks of a standard MIDI file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Format 0: a single track holding every channel.
+    SingleTrack,
+    /// Format 1: several tracks played back simultaneously.
+    Simultaneous,
+    /// Format 2: several independent single track patterns.
+    Sequential,
+}
+
+impl Format {
+    fn from_u16(value: u16) -> Result<Self, InvalidData> {
+        match value {
+            0 => Ok(Format::SingleTrack),
+            1 => Ok(Format::Simultaneous),
+            2 => Ok(Format::Sequential),
+            _ => Err(InvalidData("Unknown standard midi file format")),
+        }
+    }
+
+    fn to_u16(self) -> u16 {
+        match self {
+            Format::SingleTrack => 0,
+            Format::Simultaneous => 1,
+            Format::Sequential => 2,
+        }
+    }
+}
+
+/// The meaning of the delta times of the events in a standard MIDI file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Division {
+    /// Delta times count subdivisions of a quarter note.
+    TicksPerQuarterNote(ux::u15),
+    /// Delta times count subdivisions of a SMPTE frame.
+    Smpte {
+        /// One of 24, 25, 29 (30 drop frame) or 30.
+        frames_per_second: u8,
+        ticks_per_frame: u8,
+    },
+}
+
+impl Division {
+    fn from_u16(value: u16) -> Self {
+        if value & 0x8000 == 0 {
+            Division::TicksPerQuarterNote(ux::u15::new(value))
+        } else {
+            Division::Smpte {
+                frames_per_second: ((value >> 8) as u8 as i8).unsigned_abs(),
+                ticks_per_frame: value as u8,
+            }
+        }
+    }
+
+    fn to_u16(self) -> u16 {
+        match self {
+            Division::TicksPerQuarterNote(ticks) => u16::from(ticks),
+            Division::Smpte {
+                frames_per_second,
+                ticks_per_frame,
+            } => u16::from_be_bytes([
+                (frames_per_second as i8).wrapping_neg() as u8,
+                ticks_per_frame,
+            ]),
+        }
+    }
+}
+
+/// Reads a variable length quantity from the front of the data.
+/// Returns the value and the number of bytes it occupied.
+fn read_variable_length_quantity(data: &[u8]) -> Result<(u32, usize), InvalidData> {
+    let mut value = 0_u32;
+    for (i, byte) in data.iter().enumerate().take(4) {
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    if data.len() < 4 {
+        Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA))
+    } else {
+        Err(InvalidData(ERR_VARIABLE_LENGTH_QUANTITY_TOO_LONG))
+    }
+}
+
+/// Encodes a variable length quantity into the end of the buffer.
+/// Returns the encoded bytes.
+///
+/// Panics if the value doesn't fit into 28 bits.
+fn write_variable_length_quantity(value: u32, buffer: &mut [u8; 4]) -> &[u8] {
+    assert!(
+        value <= 0x0FFF_FFFF,
+        "Variable length quantities are limited to 28 bits"
+    );
+    let mut index = 3;
+    let mut value = value;
+    buffer[index] = (value & 0x7F) as u8;
+    while value > 0x7F {
+        value >>= 7;
+        index -= 1;
+        buffer[index] = (value & 0x7F) as u8 | 0x80;
+    }
+    &buffer[index..]
+}
+
+struct Chunk<'a> {
+    chunk_type: &'a [u8],
+    data: &'a [u8],
+}
+
+/// Splits the front chunk off the data.
+/// Returns the chunk and the remaining data.
+fn read_chunk(data: &[u8]) -> Result<(Chunk<'_>, &[u8]), InvalidData> {
+    if data.len() < 8 {
+        return Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA));
+    }
+    let length = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+    let chunk_type = &data[..4];
+    let data = &data[8..];
+    if data.len() < length {
+        return Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA));
+    }
+    Ok((
+        Chunk {
+            chunk_type,
+            data: &data[..length],
+        },
+        &data[length..],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn variable_length_quantity() {
+        assert_eq!(read_variable_length_quantity(&[0x00]), Ok((0x0, 1)));
+        assert_eq!(read_variable_length_quantity(&[0x7F]), Ok((0x7F, 1)));
+        assert_eq!(read_variable_length_quantity(&[0x81, 0x00]), Ok((0x80, 2)));
+        assert_eq!(
+            read_variable_length_quantity(&[0xC0, 0x80, 0x00]),
+            Ok((0x10_0000, 3))
+        );
+        assert_eq!(
+            read_variable_length_quantity(&[0xFF, 0xFF, 0xFF, 0x7F, 0x00]),
+            Ok((0x0FFF_FFFF, 4))
+        );
+    }
+
+    #[test]
+    fn write_variable_length_quantities() {
+        let mut buffer = [0x0; 4];
+        for (value, encoded) in [
+            (0x0, &[0x00][..]),
+            (0x7F, &[0x7F][..]),
+            (0x80, &[0x81, 0x00][..]),
+            (0x2000, &[0xC0, 0x00][..]),
+            (0x10_0000, &[0xC0, 0x80, 0x00][..]),
+            (0x0FFF_FFFF, &[0xFF, 0xFF, 0xFF, 0x7F][..]),
+        ] {
+            assert_eq!(write_variable_length_quantity(value, &mut buffer), encoded);
+            assert_eq!(
+                read_variable_length_quantity(encoded),
+                Ok((value, encoded.len()))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_variable_length_quantity_too_large() {
+        write_variable_length_quantity(0x1000_0000, &mut [0x0; 4]);
+    }
+
+    #[test]
+    fn variable_length_quantity_too_long() {
+        assert_eq!(
+            read_variable_length_quantity(&[0x80, 0x80, 0x80, 0x80, 0x00]),
+            Err(InvalidData(ERR_VARIABLE_LENGTH_QUANTITY_TOO_LONG))
+        );
+    }
+
+    #[test]
+    fn variable_length_quantity_truncated() {
+        assert_eq!(
+            read_variable_length_quantity(&[0x81]),
+            Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA))
+        );
+    }
+
+    #[test]
+    fn division() {
+        assert_eq!(
+            Division::from_u16(0x01E0),
+            Division::TicksPerQuarterNote(ux::u15::new(480))
+        );
+        assert_eq!(
+            Division::from_u16(0xE728),
+            Division::Smpte {
+                frames_per_second: 25,
+                ticks_per_frame: 40,
+            }
+        );
+        assert_eq!(Division::from_u16(0xE728).to_u16(), 0xE728);
+        assert_eq!(Division::from_u16(0x01E0).to_u16(), 0x01E0);
+    }
+}
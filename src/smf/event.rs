@@ -0,0 +1,414 @@
+use crate::{error::InvalidData, BytesMessage, Data};
+
+const ERR_INVALID_META_EVENT_LENGTH: &str = "Invalid meta event length";
+
+/// An event of a track in a standard MIDI file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// A channel voice message.
+    Midi(MidiEvent<'a>),
+    /// A system exclusive message or a packet of arbitrary bytes.
+    Sysex(SysexEvent<'a>),
+    /// Information about the sequence which isn't sent to devices.
+    Meta(MetaEvent<'a>),
+}
+
+/// A channel voice message of a track.
+///
+/// Messages carrying their own status byte borrow their data from the file.
+/// Tracks may omit the status byte of consecutive messages (running status),
+/// in which case the message is rebuilt with its status byte
+/// into a small fixed size buffer.
+///
+/// Events compare equal when their messages have the same bytes,
+/// however they are stored.
+#[derive(Debug, Clone)]
+pub enum MidiEvent<'a> {
+    Borrowed(BytesMessage<&'a [u8]>),
+    RunningStatus(BytesMessage<[u8; 3]>),
+}
+
+impl<'a> MidiEvent<'a> {
+    /// The message, borrowing from the file or from the event.
+    pub fn message(&self) -> BytesMessage<&[u8]> {
+        match self {
+            MidiEvent::Borrowed(message) => message.clone(),
+            MidiEvent::RunningStatus(message) => {
+                BytesMessage::try_from(message.data()).expect("Valid data")
+            }
+        }
+    }
+
+    /// The bytes of the message, including the status byte.
+    pub fn data(&self) -> &[u8] {
+        match self {
+            MidiEvent::Borrowed(message) => message.data(),
+            MidiEvent::RunningStatus(message) => message.data(),
+        }
+    }
+}
+
+impl<'a> core::cmp::PartialEq for MidiEvent<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data() == other.data()
+    }
+}
+
+impl<'a> core::cmp::Eq for MidiEvent<'a> {}
+
+/// A system exclusive event, borrowing its data from the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SysexEvent<'a> {
+    pub(crate) escape: bool,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> SysexEvent<'a> {
+    /// A system exclusive message, or its first packet.
+    /// The data follows the leading `0xF0` byte of the message
+    /// and includes the trailing `0xF7` byte, if any.
+    pub fn new(data: &'a [u8]) -> Self {
+        SysexEvent {
+            escape: false,
+            data,
+        }
+    }
+
+    /// An escape event carrying arbitrary bytes to be sent as they are, such as
+    /// the continuation packets of a system exclusive message split over several events.
+    pub fn new_escape(data: &'a [u8]) -> Self {
+        SysexEvent { escape: true, data }
+    }
+
+    pub fn is_escape(&self) -> bool {
+        self.escape
+    }
+
+    /// The bytes of the event as they appear in the file.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The payload of the system exclusive message,
+    /// without the trailing `0xF7` byte.
+    pub fn payload(&self) -> &'a [u8] {
+        match self.data.split_last() {
+            Some((0xF7, payload)) => payload,
+            _ => self.data,
+        }
+    }
+
+    /// Whether the event ends the system exclusive message.
+    pub fn is_complete(&self) -> bool {
+        self.data.last() == Some(&0xF7)
+    }
+}
+
+/// A meta event, borrowing its data from the file.
+///
+/// Text data is usually, but not necessarily, ASCII.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetaEvent<'a> {
+    SequenceNumber(u16),
+    Text(&'a [u8]),
+    Copyright(&'a [u8]),
+    TrackName(&'a [u8]),
+    InstrumentName(&'a [u8]),
+    Lyric(&'a [u8]),
+    Marker(&'a [u8]),
+    CuePoint(&'a [u8]),
+    ProgramName(&'a [u8]),
+    DeviceName(&'a [u8]),
+    ChannelPrefix(ux::u4),
+    Port(ux::u7),
+    EndOfTrack,
+    /// Microseconds per quarter note.
+    Tempo(ux::u24),
+    SmpteOffset {
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+        frames: u8,
+        fractional_frames: u8,
+    },
+    TimeSignature {
+        numerator: u8,
+        /// The denominator as a power of two, e.g. 3 for eighth notes.
+        denominator: u8,
+        clocks_per_metronome_click: u8,
+        thirty_second_notes_per_quarter_note: u8,
+    },
+    KeySignature {
+        /// Negative for flats, positive for sharps.
+        sharps_flats: i8,
+        minor: bool,
+    },
+    SequencerSpecific(&'a [u8]),
+    Unknown {
+        kind: u8,
+        data: &'a [u8],
+    },
+}
+
+impl<'a> MetaEvent<'a> {
+    pub(crate) fn from_data(kind: u8, data: &'a [u8]) -> Result<Self, InvalidData> {
+        use MetaEvent::*;
+
+        let expect_length = |length: usize| {
+            if data.len() == length {
+                Ok(())
+            } else {
+                Err(InvalidData(ERR_INVALID_META_EVENT_LENGTH))
+            }
+        };
+
+        Ok(match kind {
+            0x00 => {
+                expect_length(2)?;
+                SequenceNumber(u16::from_be_bytes([data[0], data[1]]))
+            }
+            0x01 => Text(data),
+            0x02 => Copyright(data),
+            0x03 => TrackName(data),
+            0x04 => InstrumentName(data),
+            0x05 => Lyric(data),
+            0x06 => Marker(data),
+            0x07 => CuePoint(data),
+            0x08 => ProgramName(data),
+            0x09 => DeviceName(data),
+            0x20 => {
+                expect_length(1)?;
+                ChannelPrefix(ux::u4::new(data[0] & 0x0F))
+            }
+            0x21 => {
+                expect_length(1)?;
+                Port(ux::u7::new(data[0] & 0x7F))
+            }
+            0x2F => {
+                expect_length(0)?;
+                EndOfTrack
+            }
+            0x51 => {
+                expect_length(3)?;
+                Tempo(ux::u24::new(u32::from_be_bytes([
+                    0x0, data[0], data[1], data[2],
+                ])))
+            }
+            0x54 => {
+                expect_length(5)?;
+                SmpteOffset {
+                    hours: data[0],
+                    minutes: data[1],
+                    seconds: data[2],
+                    frames: data[3],
+                    fractional_frames: data[4],
+                }
+            }
+            0x58 => {
+                expect_length(4)?;
+                TimeSignature {
+                    numerator: data[0],
+                    denominator: data[1],
+                    clocks_per_metronome_click: data[2],
+                    thirty_second_notes_per_quarter_note: data[3],
+                }
+            }
+            0x59 => {
+                expect_length(2)?;
+                KeySignature {
+                    sharps_flats: data[0] as i8,
+                    minor: data[1] != 0,
+                }
+            }
+            0x7F => SequencerSpecific(data),
+            kind => Unknown { kind, data },
+        })
+    }
+
+    /// The kind byte and data of the meta event.
+    /// Fixed size data is encoded into the scratch buffer.
+    pub(crate) fn encode<'b>(&'b self, scratch: &'b mut [u8; 5]) -> (u8, &'b [u8]) {
+        use MetaEvent::*;
+
+        match *self {
+            SequenceNumber(number) => (0x00, fixed(scratch, &number.to_be_bytes())),
+            Text(data) => (0x01, data),
+            Copyright(data) => (0x02, data),
+            TrackName(data) => (0x03, data),
+            InstrumentName(data) => (0x04, data),
+            Lyric(data) => (0x05, data),
+            Marker(data) => (0x06, data),
+            CuePoint(data) => (0x07, data),
+            ProgramName(data) => (0x08, data),
+            DeviceName(data) => (0x09, data),
+            ChannelPrefix(channel) => (0x20, fixed(scratch, &[u8::from(channel)])),
+            Port(port) => (0x21, fixed(scratch, &[u8::from(port)])),
+            EndOfTrack => (0x2F, &[]),
+            Tempo(tempo) => (0x51, fixed(scratch, &u32::from(tempo).to_be_bytes()[1..])),
+            SmpteOffset {
+                hours,
+                minutes,
+                seconds,
+                frames,
+                fractional_frames,
+            } => (
+                0x54,
+                fixed(
+                    scratch,
+                    &[hours, minutes, seconds, frames, fractional_frames],
+                ),
+            ),
+            TimeSignature {
+                numerator,
+                denominator,
+                clocks_per_metronome_click,
+                thirty_second_notes_per_quarter_note,
+            } => (
+                0x58,
+                fixed(
+                    scratch,
+                    &[
+                        numerator,
+                        denominator,
+                        clocks_per_metronome_click,
+                        thirty_second_notes_per_quarter_note,
+                    ],
+                ),
+            ),
+            KeySignature {
+                sharps_flats,
+                minor,
+            } => (0x59, fixed(scratch, &[sharps_flats as u8, minor as u8])),
+            SequencerSpecific(data) => (0x7F, data),
+            Unknown { kind, data } => (kind, data),
+        }
+    }
+}
+
+fn fixed<'b>(scratch: &'b mut [u8; 5], data: &[u8]) -> &'b [u8] {
+    scratch[..data.len()].copy_from_slice(data);
+    &scratch[..data.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn tempo() {
+        assert_eq!(
+            MetaEvent::from_data(0x51, &[0x07, 0xA1, 0x20]),
+            Ok(MetaEvent::Tempo(ux::u24::new(500_000)))
+        );
+    }
+
+    #[test]
+    fn time_signature() {
+        assert_eq!(
+            MetaEvent::from_data(0x58, &[0x06, 0x03, 0x24, 0x08]),
+            Ok(MetaEvent::TimeSignature {
+                numerator: 6,
+                denominator: 3,
+                clocks_per_metronome_click: 36,
+                thirty_second_notes_per_quarter_note: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn key_signature() {
+        assert_eq!(
+            MetaEvent::from_data(0x59, &[0xFD, 0x01]),
+            Ok(MetaEvent::KeySignature {
+                sharps_flats: -3,
+                minor: true,
+            })
+        );
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            MetaEvent::from_data(0x03, b"Piano"),
+            Ok(MetaEvent::TrackName(b"Piano"))
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(
+            MetaEvent::from_data(0x4B, &[0x01]),
+            Ok(MetaEvent::Unknown {
+                kind: 0x4B,
+                data: &[0x01],
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_length() {
+        assert_eq!(
+            MetaEvent::from_data(0x51, &[0x07, 0xA1]),
+            Err(InvalidData(ERR_INVALID_META_EVENT_LENGTH))
+        );
+        assert_eq!(
+            MetaEvent::from_data(0x2F, &[0x00]),
+            Err(InvalidData(ERR_INVALID_META_EVENT_LENGTH))
+        );
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let events = [
+            MetaEvent::SequenceNumber(0x1234),
+            MetaEvent::Lyric(b"la"),
+            MetaEvent::ChannelPrefix(ux::u4::new(0x9)),
+            MetaEvent::Port(ux::u7::new(0x2)),
+            MetaEvent::EndOfTrack,
+            MetaEvent::Tempo(ux::u24::new(500_000)),
+            MetaEvent::SmpteOffset {
+                hours: 1,
+                minutes: 2,
+                seconds: 3,
+                frames: 4,
+                fractional_frames: 5,
+            },
+            MetaEvent::TimeSignature {
+                numerator: 7,
+                denominator: 3,
+                clocks_per_metronome_click: 12,
+                thirty_second_notes_per_quarter_note: 8,
+            },
+            MetaEvent::KeySignature {
+                sharps_flats: -6,
+                minor: false,
+            },
+            MetaEvent::SequencerSpecific(&[0x00, 0x21, 0x09]),
+            MetaEvent::Unknown {
+                kind: 0x60,
+                data: &[0x1],
+            },
+        ];
+        for event in events {
+            let mut scratch = [0x0; 5];
+            let (kind, data) = event.encode(&mut scratch);
+            assert_eq!(MetaEvent::from_data(kind, data), Ok(event));
+        }
+    }
+
+    #[test]
+    fn sysex_payload() {
+        let event = SysexEvent::new(&[0x43, 0x12, 0x00, 0xF7]);
+        assert_eq!(event.payload(), &[0x43, 0x12, 0x00]);
+        assert!(event.is_complete());
+        assert!(!event.is_escape());
+    }
+
+    #[test]
+    fn sysex_packet() {
+        let event = SysexEvent::new(&[0x43, 0x12]);
+        assert_eq!(event.payload(), &[0x43, 0x12]);
+        assert!(!event.is_complete());
+    }
+}
@@ -0,0 +1,533 @@
+use crate::{
+    error::InvalidData,
+    smf::{
+        read_chunk, read_variable_length_quantity, Chunk, Division, Event, Format, MetaEvent,
+        MidiEvent, SysexEvent, ERR_UNEXPECTED_END_OF_DATA, HEADER_CHUNK_TYPE, HEADER_SIZE,
+        TRACK_CHUNK_TYPE,
+    },
+    BytesMessage,
+};
+
+const ERR_MISSING_HEADER_CHUNK: &str = "Standard midi file doesn't start with a header chunk";
+const ERR_UNEXPECTED_DATA_BYTE: &str = "Data byte without a preceding status byte";
+const ERR_UNEXPECTED_STATUS: &str = "Unexpected status byte in track";
+
+/// A standard MIDI file, read from a byte slice.
+///
+/// Events borrow their data from the input. The exception are channel voice
+/// messages whose status byte the track omits (running status): they are rebuilt
+/// into a fixed three byte buffer (see [MidiEvent]).
+///
+/// System exclusive data is represented by a [SysexEvent] rather than a `Sysex7`
+/// message, because the file stores it behind a length prefix
+/// instead of a leading `0xF0` byte.
+///
+/// Chunks of unknown types are skipped, as the standard requires.
+///
+/// ```rust
+/// use midi2::{prelude::*, smf::{Smf, Event, MetaEvent, Format}};
+///
+/// let data = [
+///     b'M', b'T', b'h', b'd', 0x00, 0x00, 0x00, 0x06,
+///     0x00, 0x00, 0x00, 0x01, 0x01, 0xE0,
+///     b'M', b'T', b'r', b'k', 0x00, 0x00, 0x00, 0x0F,
+///     0x00, 0x90, 0x3C, 0x64,
+///     0x83, 0x60, 0x3C, 0x00, // running status
+///     0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20,
+/// ];
+///
+/// let smf = Smf::try_from(&data[..]).unwrap();
+/// assert_eq!(smf.format(), Format::SingleTrack);
+///
+/// let track = smf.tracks().next().unwrap().unwrap();
+/// let mut events = track.events();
+///
+/// let (delta, Event::Midi(message)) = events.next().unwrap().unwrap() else { panic!() };
+/// assert_eq!(delta, 0);
+/// assert_eq!(message.data(), &[0x90, 0x3C, 0x64]);
+///
+/// let (delta, Event::Midi(message)) = events.next().unwrap().unwrap() else { panic!() };
+/// assert_eq!(delta, 480);
+/// assert_eq!(message.data(), &[0x90, 0x3C, 0x00]);
+///
+/// assert_eq!(
+///     events.next(),
+///     Some(Ok((0, Event::Meta(MetaEvent::Tempo(u24::new(500_000)))))),
+/// );
+/// assert_eq!(events.next(), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Smf<'a> {
+    format: Format,
+    number_of_tracks: u16,
+    division: Division,
+    chunks: &'a [u8],
+}
+
+impl<'a> Smf<'a> {
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// The number of tracks announced by the header chunk.
+    pub fn number_of_tracks(&self) -> u16 {
+        self.number_of_tracks
+    }
+
+    pub fn division(&self) -> Division {
+        self.division
+    }
+
+    /// The track chunks of the file, in order.
+    pub fn tracks(&self) -> Tracks<'a> {
+        Tracks { data: self.chunks }
+    }
+}
+
+impl<'a> core::convert::TryFrom<&'a [u8]> for Smf<'a> {
+    type Error = InvalidData;
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (
+            Chunk {
+                chunk_type,
+                data: header,
+            },
+            chunks,
+        ) = read_chunk(data)?;
+        if chunk_type != HEADER_CHUNK_TYPE {
+            return Err(InvalidData(ERR_MISSING_HEADER_CHUNK));
+        }
+        if header.len() < HEADER_SIZE {
+            return Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA));
+        }
+        Ok(Smf {
+            format: Format::from_u16(u16::from_be_bytes([header[0], header[1]]))?,
+            number_of_tracks: u16::from_be_bytes([header[2], header[3]]),
+            division: Division::from_u16(u16::from_be_bytes([header[4], header[5]])),
+            chunks,
+        })
+    }
+}
+
+/// Iterates over the track chunks of a [Smf].
+#[derive(Debug, Clone)]
+pub struct Tracks<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> core::iter::Iterator for Tracks<'a> {
+    type Item = Result<Track<'a>, InvalidData>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.data.is_empty() {
+            match read_chunk(self.data) {
+                Ok((chunk, remaining)) => {
+                    self.data = remaining;
+                    if chunk.chunk_type == TRACK_CHUNK_TYPE {
+                        return Some(Ok(Track { data: chunk.data }));
+                    }
+                }
+                Err(e) => {
+                    self.data = &[];
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a> core::iter::FusedIterator for Tracks<'a> {}
+
+/// A track chunk of a [Smf].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Track<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Track<'a> {
+    /// The raw event data of the track.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The events of the track, paired with their delta time in ticks.
+    pub fn events(&self) -> TrackEvents<'a> {
+        TrackEvents {
+            data: self.data,
+            running_status: None,
+        }
+    }
+}
+
+/// Iterates over the events of a [Track], paired with their delta time in ticks.
+///
+/// Iteration ends after the end of track meta event,
+/// or after the first error.
+#[derive(Debug, Clone)]
+pub struct TrackEvents<'a> {
+    data: &'a [u8],
+    running_status: Option<u8>,
+}
+
+impl<'a> TrackEvents<'a> {
+    fn read_event(&mut self) -> Result<(u32, Event<'a>), InvalidData> {
+        let (delta, mut index) = read_variable_length_quantity(self.data)?;
+        let data = self.data;
+        let byte = *data
+            .get(index)
+            .ok_or(InvalidData(ERR_UNEXPECTED_END_OF_DATA))?;
+
+        let event = match byte {
+            0xF0 | 0xF7 => {
+                self.running_status = None;
+                let (length, size) = read_variable_length_quantity(&data[index + 1..])?;
+                let start = index + 1 + size;
+                let payload = data
+                    .get(start..start + length as usize)
+                    .ok_or(InvalidData(ERR_UNEXPECTED_END_OF_DATA))?;
+                index = start + length as usize;
+                Event::Sysex(SysexEvent {
+                    escape: byte == 0xF7,
+                    data: payload,
+                })
+            }
+            0xFF => {
+                self.running_status = None;
+                let kind = *data
+                    .get(index + 1)
+                    .ok_or(InvalidData(ERR_UNEXPECTED_END_OF_DATA))?;
+                let (length, size) = read_variable_length_quantity(&data[index + 2..])?;
+                let start = index + 2 + size;
+                let payload = data
+                    .get(start..start + length as usize)
+                    .ok_or(InvalidData(ERR_UNEXPECTED_END_OF_DATA))?;
+                index = start + length as usize;
+                Event::Meta(MetaEvent::from_data(kind, payload)?)
+            }
+            0x80..=0xEF => {
+                self.running_status = Some(byte);
+                let size = channel_message_data(byte, &data[index + 1..])?.len();
+                let message = BytesMessage::try_from(&data[index..index + 1 + size])?;
+                index += 1 + size;
+                Event::Midi(MidiEvent::Borrowed(message))
+            }
+            0x00..=0x7F => {
+                let status = self
+                    .running_status
+                    .ok_or(InvalidData(ERR_UNEXPECTED_DATA_BYTE))?;
+                let message_data = channel_message_data(status, &data[index..])?;
+                let size = message_data.len();
+
+                let mut buffer = [0x0; 3];
+                buffer[0] = status;
+                buffer[1..1 + size].copy_from_slice(message_data);
+                let message = BytesMessage::try_from(&buffer[..1 + size])?;
+                let message = crate::TryRebufferInto::try_rebuffer_into(message)
+                    .expect("Channel voice messages fit into three bytes");
+                index += size;
+                Event::Midi(MidiEvent::RunningStatus(message))
+            }
+            _ => return Err(InvalidData(ERR_UNEXPECTED_STATUS)),
+        };
+
+        self.data = &data[index..];
+        Ok((delta, event))
+    }
+}
+
+impl<'a> core::iter::Iterator for TrackEvents<'a> {
+    type Item = Result<(u32, Event<'a>), InvalidData>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let result = self.read_event();
+        if matches!(result, Err(_) | Ok((_, Event::Meta(MetaEvent::EndOfTrack)))) {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+
+impl<'a> core::iter::FusedIterator for TrackEvents<'a> {}
+
+// the data bytes of a channel voice message with the given status
+fn channel_message_data(status: u8, data: &[u8]) -> Result<&[u8], InvalidData> {
+    let size = match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        _ => 2,
+    };
+    let data = data
+        .get(..size)
+        .ok_or(InvalidData(ERR_UNEXPECTED_END_OF_DATA))?;
+    if data.iter().any(|b| b & 0x80 != 0) {
+        return Err(InvalidData(ERR_UNEXPECTED_STATUS));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+    use std::vec::Vec;
+
+    fn smf(format: u16, division: u16, tracks: &[&[u8]]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"MThd");
+        data.extend_from_slice(&6_u32.to_be_bytes());
+        data.extend_from_slice(&format.to_be_bytes());
+        data.extend_from_slice(&(tracks.len() as u16).to_be_bytes());
+        data.extend_from_slice(&division.to_be_bytes());
+        for track in tracks {
+            data.extend_from_slice(b"MTrk");
+            data.extend_from_slice(&(track.len() as u32).to_be_bytes());
+            data.extend_from_slice(track);
+        }
+        data
+    }
+
+    fn events(track: &[u8]) -> Vec<Result<(u32, Event<'_>), InvalidData>> {
+        Track { data: track }.events().collect()
+    }
+
+    fn midi(data: &[u8]) -> Event<'_> {
+        Event::Midi(MidiEvent::Borrowed(BytesMessage::try_from(data).unwrap()))
+    }
+
+    #[test]
+    fn header() {
+        let data = smf(1, 0x01E0, &[&[], &[]]);
+        let smf = Smf::try_from(&data[..]).unwrap();
+        assert_eq!(smf.format(), Format::Simultaneous);
+        assert_eq!(smf.number_of_tracks(), 2);
+        assert_eq!(
+            smf.division(),
+            Division::TicksPerQuarterNote(ux::u15::new(480))
+        );
+        assert_eq!(smf.tracks().count(), 2);
+    }
+
+    #[test]
+    fn smpte_division() {
+        let data = smf(0, 0xE250, &[]);
+        assert_eq!(
+            Smf::try_from(&data[..]).unwrap().division(),
+            Division::Smpte {
+                frames_per_second: 30,
+                ticks_per_frame: 80,
+            }
+        );
+    }
+
+    #[test]
+    fn missing_header() {
+        let data = [b'M', b'T', b'r', b'k', 0x0, 0x0, 0x0, 0x0];
+        assert_eq!(
+            Smf::try_from(&data[..]),
+            Err(InvalidData(ERR_MISSING_HEADER_CHUNK))
+        );
+    }
+
+    #[test]
+    fn unknown_format() {
+        let data = smf(3, 0x01E0, &[]);
+        assert!(Smf::try_from(&data[..]).is_err());
+    }
+
+    #[test]
+    fn truncated_header() {
+        let data = smf(0, 0x01E0, &[]);
+        assert_eq!(
+            Smf::try_from(&data[..10]),
+            Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA))
+        );
+    }
+
+    #[test]
+    fn skip_unknown_chunks() {
+        let mut data = smf(0, 0x01E0, &[]);
+        data.extend_from_slice(&[b'X', b'F', b'I', b'H', 0x0, 0x0, 0x0, 0x2, 0x1, 0x2]);
+        data.extend_from_slice(&[b'M', b'T', b'r', b'k', 0x0, 0x0, 0x0, 0x4]);
+        data.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+        let smf = Smf::try_from(&data[..]).unwrap();
+        let tracks: Vec<_> = smf.tracks().collect();
+        assert_eq!(
+            tracks,
+            std::vec![Ok(Track {
+                data: &[0x00, 0xFF, 0x2F, 0x00]
+            })]
+        );
+    }
+
+    #[test]
+    fn truncated_track_chunk() {
+        let mut data = smf(0, 0x01E0, &[&[0x00, 0xFF, 0x2F, 0x00]]);
+        data.pop();
+        let smf = Smf::try_from(&data[..]).unwrap();
+        let mut tracks = smf.tracks();
+        assert_eq!(
+            tracks.next(),
+            Some(Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA)))
+        );
+        assert_eq!(tracks.next(), None);
+    }
+
+    #[test]
+    fn channel_messages() {
+        assert_eq!(
+            events(&[0x00, 0x90, 0x3C, 0x64, 0x10, 0xC2, 0x05, 0x20, 0xE0, 0x00, 0x40]),
+            std::vec![
+                Ok((0x00, midi(&[0x90, 0x3C, 0x64]))),
+                Ok((0x10, midi(&[0xC2, 0x05]))),
+                Ok((0x20, midi(&[0xE0, 0x00, 0x40]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn running_status() {
+        assert_eq!(
+            events(&[0x00, 0x90, 0x3C, 0x64, 0x81, 0x00, 0x3C, 0x00, 0x00, 0xD1, 0x10, 0x00, 0x20]),
+            std::vec![
+                Ok((0x00, midi(&[0x90, 0x3C, 0x64]))),
+                Ok((0x80, midi(&[0x90, 0x3C, 0x00]))),
+                Ok((0x00, midi(&[0xD1, 0x10]))),
+                Ok((0x00, midi(&[0xD1, 0x20]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn meta_event_cancels_running_status() {
+        assert_eq!(
+            events(&[0x00, 0x90, 0x3C, 0x64, 0x00, 0xFF, 0x01, 0x00, 0x00, 0x3C, 0x00]),
+            std::vec![
+                Ok((0x00, midi(&[0x90, 0x3C, 0x64]))),
+                Ok((0x00, Event::Meta(MetaEvent::Text(&[])))),
+                Err(InvalidData(ERR_UNEXPECTED_DATA_BYTE)),
+            ]
+        );
+    }
+
+    #[test]
+    fn sysex() {
+        assert_eq!(
+            events(&[0x00, 0xF0, 0x03, 0x43, 0x12, 0xF7, 0x00, 0xF7, 0x02, 0xF3, 0x01]),
+            std::vec![
+                Ok((0x00, Event::Sysex(SysexEvent::new(&[0x43, 0x12, 0xF7])))),
+                Ok((0x00, Event::Sysex(SysexEvent::new_escape(&[0xF3, 0x01])))),
+            ]
+        );
+    }
+
+    #[test]
+    fn meta_events() {
+        assert_eq!(
+            events(&[
+                0x00, 0xFF, 0x03, 0x05, b'P', b'i', b'a', b'n', b'o', 0x00, 0xFF, 0x58, 0x04, 0x04,
+                0x02, 0x18, 0x08, 0x00, 0xFF, 0x59, 0x02, 0x02, 0x00,
+            ]),
+            std::vec![
+                Ok((0x00, Event::Meta(MetaEvent::TrackName(b"Piano")))),
+                Ok((
+                    0x00,
+                    Event::Meta(MetaEvent::TimeSignature {
+                        numerator: 4,
+                        denominator: 2,
+                        clocks_per_metronome_click: 24,
+                        thirty_second_notes_per_quarter_note: 8,
+                    })
+                )),
+                Ok((
+                    0x00,
+                    Event::Meta(MetaEvent::KeySignature {
+                        sharps_flats: 2,
+                        minor: false,
+                    })
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn end_of_track() {
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x2F, 0x00, 0x00, 0x90, 0x3C, 0x64]),
+            std::vec![Ok((0x00, Event::Meta(MetaEvent::EndOfTrack)))]
+        );
+    }
+
+    #[test]
+    fn long_delta_time() {
+        assert_eq!(
+            events(&[0xFF, 0xFF, 0xFF, 0x7F, 0x90, 0x3C, 0x64]),
+            std::vec![Ok((0x0FFF_FFFF, midi(&[0x90, 0x3C, 0x64])))]
+        );
+    }
+
+    #[test]
+    fn truncated_event() {
+        assert_eq!(
+            events(&[0x00, 0x90, 0x3C]),
+            std::vec![Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA))]
+        );
+        assert_eq!(
+            events(&[0x00, 0xFF, 0x03, 0x05, b'P']),
+            std::vec![Err(InvalidData(ERR_UNEXPECTED_END_OF_DATA))]
+        );
+    }
+
+    #[test]
+    fn system_common_status() {
+        assert_eq!(
+            events(&[0x00, 0xF2, 0x00, 0x00]),
+            std::vec![Err(InvalidData(ERR_UNEXPECTED_STATUS))]
+        );
+    }
+
+    #[test]
+    fn borrows_from_input() {
+        let data = smf(0, 0x01E0, &[&[0x00, 0xFF, 0x01, 0x02, b'h', b'i']]);
+        let smf = Smf::try_from(&data[..]).unwrap();
+        let track = smf.tracks().next().unwrap().unwrap();
+        let Some(Ok((_, Event::Meta(MetaEvent::Text(text))))) = track.events().next() else {
+            panic!();
+        };
+        assert_eq!(text.as_ptr(), data[data.len() - 2..].as_ptr());
+    }
+
+    #[test]
+    fn midi_message_data() {
+        let Some(Ok((_, Event::Midi(message)))) = Track {
+            data: &[0x00, 0xB3, 0x07, 0x64],
+        }
+        .events()
+        .next() else {
+            panic!();
+        };
+        assert_eq!(message.data(), &[0xB3, 0x07, 0x64]);
+    }
+
+    #[test]
+    fn midi_message_borrows_from_file() {
+        let data = [0x00, 0xC3, 0x05, 0x00, 0x06];
+        let events: Vec<_> = Track { data: &data }
+            .events()
+            .map(|e| e.unwrap().1)
+            .collect();
+
+        let Event::Midi(MidiEvent::Borrowed(ref message)) = events[0] else {
+            panic!();
+        };
+        assert_eq!(message.data().as_ptr(), data[1..].as_ptr());
+
+        // running status
+        let Event::Midi(MidiEvent::RunningStatus(ref message)) = events[1] else {
+            panic!();
+        };
+        assert_eq!(message.data(), &[0xC3, 0x06]);
+        assert_eq!(events[1], midi(&[0xC3, 0x06]));
+    }
+}
@@ -0,0 +1,614 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Bytes},
+    bytes_stream_parser::message_size,
+    error::BufferOverflow,
+    smf::{
+        write_variable_length_quantity, Division, Format, MetaEvent, HEADER_CHUNK_TYPE,
+        HEADER_SIZE, TRACK_CHUNK_TYPE,
+    },
+    Data,
+};
+
+const SYSEX_START: u8 = 0xF0;
+const ESCAPE: u8 = 0xF7;
+const META: u8 = 0xFF;
+const CHUNK_HEADER_SIZE: usize = 8;
+const NUMBER_OF_TRACKS_INDEX: usize = 10;
+
+/// Writes a standard MIDI file into a byte buffer.
+///
+/// The header chunk is written along with the first track.
+/// Tracks are written one after another: events are appended to the
+/// current track, which is opened by the first event and closed
+/// with [end_track](SmfWriter::end_track).
+/// Every event is preceded by its delta time in ticks.
+///
+/// Accepts any message represented with bytes, e.g. [BytesMessage](crate::BytesMessage),
+/// `ChannelVoice1<B>` or `Sysex7<B>`.
+/// - Sysex messages are written as `F0` events with their length prefix.
+/// - System common and realtime messages have no place in a standard MIDI file
+///   and are written as `F7` escape events.
+///
+/// When running status is enabled the status byte of a channel voice message
+/// is omitted if it matches the status of the previous event of the track.
+/// Sysex, escape and meta events cancel the running status.
+///
+/// Writers backed by a resizable buffer grow as needed. Writers backed by a
+/// fixed size buffer use the `try_` methods, which fail when the data doesn't fit,
+/// in which case nothing is written.
+///
+/// Delta times are limited to 28 bits. Larger delta times panic.
+///
+/// ## Basic Usage
+///
+/// ```rust
+/// use midi2::{prelude::*, smf::{Division, Format, MetaEvent, SmfWriter}};
+///
+/// let mut writer = SmfWriter::<Vec<u8>>::new(
+///     Format::SingleTrack,
+///     Division::TicksPerQuarterNote(u15::new(96)),
+/// );
+/// writer.set_running_status(true);
+///
+/// let mut note_on = channel_voice1::NoteOn::<[u8; 3]>::new();
+/// note_on.set_note_number(u7::new(0x3C));
+/// note_on.set_velocity(u7::new(0x7F));
+///
+/// writer.write_meta(0, MetaEvent::TrackName(b"Take 1"));
+/// writer.write(0, &note_on);
+/// note_on.set_velocity(u7::new(0x0));
+/// writer.write(96, &note_on);
+/// writer.end_track(0);
+///
+/// assert_eq!(
+///     writer.data(),
+///     &[
+///         b'M', b'T', b'h', b'd', 0x00, 0x00, 0x00, 0x06,
+///         0x00, 0x00, 0x00, 0x01, 0x00, 0x60,
+///         b'M', b'T', b'r', b'k', 0x00, 0x00, 0x00, 0x15,
+///         0x00, 0xFF, 0x03, 0x06, b'T', b'a', b'k', b'e', b' ', b'1',
+///         0x00, 0x90, 0x3C, 0x7F,
+///         0x60, 0x3C, 0x00,
+///         0x00, 0xFF, 0x2F, 0x00,
+///     ][..],
+/// );
+/// ```
+///
+/// ## Fixed Size Buffers
+///
+/// ```rust
+/// use midi2::{prelude::*, error::BufferOverflow, smf::{Division, Format, SmfWriter}};
+///
+/// let mut writer = SmfWriter::<[u8; 30]>::new(
+///     Format::SingleTrack,
+///     Division::TicksPerQuarterNote(u15::new(96)),
+/// );
+///
+/// let note_on = channel_voice1::NoteOn::<[u8; 3]>::new();
+/// assert_eq!(writer.try_write(0, &note_on), Ok(()));
+/// assert_eq!(writer.try_end_track(0), Ok(()));
+/// assert_eq!(writer.data().len(), 30);
+///
+/// // no room for another track
+/// assert_eq!(writer.try_write(0, &note_on), Err(BufferOverflow));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmfWriter<B: Bytes + BufferMut> {
+    buffer: B,
+    size: usize,
+    format: Format,
+    division: Division,
+    number_of_tracks: u16,
+    // the index of the chunk header of the open track
+    track_start: Option<usize>,
+    running_status_enabled: bool,
+    running_status: Option<u8>,
+}
+
+impl<B: Bytes + BufferMut + BufferDefault> SmfWriter<B> {
+    /// Create a writer backed by a default constructed buffer.
+    ///
+    /// Running status is disabled by default.
+    pub fn new(format: Format, division: Division) -> Self {
+        Self::new_with_buffer(B::default(), format, division)
+    }
+}
+
+impl<B: Bytes + BufferMut> SmfWriter<B> {
+    /// Create a writer which writes into the provided buffer.
+    ///
+    /// Running status is disabled by default.
+    pub fn new_with_buffer(buffer: B, format: Format, division: Division) -> Self {
+        SmfWriter {
+            buffer,
+            size: 0,
+            format,
+            division,
+            number_of_tracks: 0,
+            track_start: None,
+            running_status_enabled: false,
+            running_status: None,
+        }
+    }
+
+    /// The bytes written so far.
+    ///
+    /// The length of the current track is only filled in
+    /// once the track is ended.
+    pub fn data(&self) -> &[u8] {
+        &self.buffer.buffer()[..self.size]
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn division(&self) -> Division {
+        self.division
+    }
+
+    /// The number of tracks begun so far.
+    pub fn number_of_tracks(&self) -> u16 {
+        self.number_of_tracks
+    }
+
+    pub fn running_status(&self) -> bool {
+        self.running_status_enabled
+    }
+
+    pub fn set_running_status(&mut self, enabled: bool) {
+        self.running_status_enabled = enabled;
+        self.running_status = None;
+    }
+
+    fn write_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        delta: u32,
+        data: &[u8],
+        resize: R,
+    ) -> Result<(), BufferOverflow> {
+        let Some(&status) = data.first() else {
+            return Ok(());
+        };
+
+        let mut delta_buffer = [0x0; 4];
+        let delta = write_variable_length_quantity(delta, &mut delta_buffer);
+        let mut length_buffer = [0x0; 4];
+
+        let (event_type, length, bytes, running_status): (&[u8], &[u8], &[u8], _) = match status {
+            SYSEX_START => (
+                &[SYSEX_START],
+                write_variable_length_quantity(data.len() as u32 - 1, &mut length_buffer),
+                &data[1..],
+                None,
+            ),
+            0xF1..=0xFF => {
+                let bytes = &data[..message_size(status)];
+                (
+                    &[ESCAPE],
+                    write_variable_length_quantity(bytes.len() as u32, &mut length_buffer),
+                    bytes,
+                    None,
+                )
+            }
+            _ => {
+                let bytes = &data[..message_size(status)];
+                if self.running_status_enabled && self.running_status == Some(status) {
+                    (&[], &[], &bytes[1..], Some(status))
+                } else {
+                    (&[], &[], bytes, Some(status))
+                }
+            }
+        };
+
+        self.append(&[delta, event_type, length, bytes], resize)?;
+        self.running_status = running_status;
+        Ok(())
+    }
+
+    fn write_meta_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        delta: u32,
+        event: MetaEvent,
+        resize: R,
+    ) -> Result<(), BufferOverflow> {
+        let mut delta_buffer = [0x0; 4];
+        let delta = write_variable_length_quantity(delta, &mut delta_buffer);
+        let mut scratch = [0x0; 5];
+        let (kind, data) = event.encode(&mut scratch);
+        let mut length_buffer = [0x0; 4];
+        let length = write_variable_length_quantity(data.len() as u32, &mut length_buffer);
+
+        self.append(&[delta, &[META, kind], length, data], resize)?;
+        self.running_status = None;
+
+        if event == MetaEvent::EndOfTrack {
+            self.close_track();
+        }
+        Ok(())
+    }
+
+    // appends the parts of an event to the current track,
+    // opening a new track (and writing the header chunk) if required
+    fn append<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        parts: &[&[u8]],
+        resize: R,
+    ) -> Result<(), BufferOverflow> {
+        let header_size = match (self.size, self.track_start) {
+            (_, Some(_)) => 0,
+            (0, None) => 2 * CHUNK_HEADER_SIZE + HEADER_SIZE,
+            (_, None) => CHUNK_HEADER_SIZE,
+        };
+        let event_size: usize = parts.iter().map(|p| p.len()).sum();
+        resize(&mut self.buffer, self.size + header_size + event_size)?;
+
+        if self.size == 0 {
+            self.write_bytes(HEADER_CHUNK_TYPE);
+            self.write_bytes(&(HEADER_SIZE as u32).to_be_bytes());
+            self.write_bytes(&self.format.to_u16().to_be_bytes());
+            self.write_bytes(&0_u16.to_be_bytes());
+            self.write_bytes(&self.division.to_u16().to_be_bytes());
+        }
+        if self.track_start.is_none() {
+            self.track_start = Some(self.size);
+            self.write_bytes(TRACK_CHUNK_TYPE);
+            self.write_bytes(&0_u32.to_be_bytes());
+            self.number_of_tracks += 1;
+            let number_of_tracks = self.number_of_tracks.to_be_bytes();
+            self.buffer.buffer_mut()[NUMBER_OF_TRACKS_INDEX..NUMBER_OF_TRACKS_INDEX + 2]
+                .copy_from_slice(&number_of_tracks);
+            self.running_status = None;
+        }
+        for part in parts {
+            self.write_bytes(part);
+        }
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.buffer_mut()[self.size..self.size + bytes.len()].copy_from_slice(bytes);
+        self.size += bytes.len();
+    }
+
+    // fills in the length of the current track
+    fn close_track(&mut self) {
+        let Some(start) = self.track_start.take() else {
+            return;
+        };
+        let length = (self.size - start - CHUNK_HEADER_SIZE) as u32;
+        self.buffer.buffer_mut()[start + 4..start + CHUNK_HEADER_SIZE]
+            .copy_from_slice(&length.to_be_bytes());
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferResize> SmfWriter<B> {
+    /// Append the message to the current track.
+    pub fn write<U: Bytes, M: Data<U>>(&mut self, delta: u32, message: &M) {
+        self.write_impl(delta, message.data(), |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .expect("Resizable buffers should not fail here")
+    }
+
+    /// Append the meta event to the current track.
+    ///
+    /// An [EndOfTrack](MetaEvent::EndOfTrack) event ends the track.
+    pub fn write_meta(&mut self, delta: u32, event: MetaEvent) {
+        self.write_meta_impl(delta, event, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+        .expect("Resizable buffers should not fail here")
+    }
+
+    /// End the current track with an end of track meta event.
+    ///
+    /// The delta time of the end of track event sets the length of the track.
+    pub fn end_track(&mut self, delta: u32) {
+        self.write_meta(delta, MetaEvent::EndOfTrack)
+    }
+}
+
+impl<B: Bytes + BufferMut + BufferTryResize> SmfWriter<B> {
+    /// Append the message to the current track.
+    ///
+    /// Fails if the event doesn't fit in the remaining space of the buffer,
+    /// in which case nothing is written.
+    pub fn try_write<U: Bytes, M: Data<U>>(
+        &mut self,
+        delta: u32,
+        message: &M,
+    ) -> Result<(), BufferOverflow> {
+        self.write_impl(delta, message.data(), |buffer, size| {
+            buffer.try_resize(size)
+        })
+    }
+
+    /// Append the meta event to the current track.
+    ///
+    /// An [EndOfTrack](MetaEvent::EndOfTrack) event ends the track.
+    /// Fails if the event doesn't fit in the remaining space of the buffer,
+    /// in which case nothing is written.
+    pub fn try_write_meta(&mut self, delta: u32, event: MetaEvent) -> Result<(), BufferOverflow> {
+        self.write_meta_impl(delta, event, |buffer, size| buffer.try_resize(size))
+    }
+
+    /// End the current track with an end of track meta event.
+    ///
+    /// The delta time of the end of track event sets the length of the track.
+    /// Fails if the event doesn't fit in the remaining space of the buffer,
+    /// in which case the track stays open.
+    pub fn try_end_track(&mut self, delta: u32) -> Result<(), BufferOverflow> {
+        self.try_write_meta(delta, MetaEvent::EndOfTrack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        smf::{Event, Smf, SysexEvent},
+        BytesMessage,
+    };
+    use pretty_assertions::assert_eq;
+    use std::vec::Vec;
+
+    fn writer(format: Format) -> SmfWriter<Vec<u8>> {
+        SmfWriter::new(format, Division::TicksPerQuarterNote(ux::u15::new(480)))
+    }
+
+    fn message(data: &[u8]) -> BytesMessage<&[u8]> {
+        BytesMessage::try_from(data).unwrap()
+    }
+
+    // the events of the first track
+    fn track(data: &[u8]) -> &[u8] {
+        &data[22..]
+    }
+
+    #[test]
+    fn header() {
+        let mut writer = writer(Format::Simultaneous);
+        writer.end_track(0);
+        writer.end_track(0);
+        assert_eq!(
+            &writer.data()[..14],
+            &[b'M', b'T', b'h', b'd', 0x0, 0x0, 0x0, 0x6, 0x0, 0x1, 0x0, 0x2, 0x1, 0xE0]
+        );
+        assert_eq!(writer.number_of_tracks(), 2);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(writer(Format::SingleTrack).data(), &[0u8; 0]);
+    }
+
+    #[test]
+    fn channel_messages() {
+        let mut writer = writer(Format::SingleTrack);
+        writer.write(0, &message(&[0x90, 0x3C, 0x64]));
+        writer.write(0x80, &message(&[0x90, 0x3C, 0x00]));
+        writer.write(0x10, &message(&[0xC2, 0x05]));
+        writer.end_track(0);
+        assert_eq!(
+            track(writer.data()),
+            &[
+                0x00, 0x90, 0x3C, 0x64, 0x81, 0x00, 0x90, 0x3C, 0x00, 0x10, 0xC2, 0x05, 0x00, 0xFF,
+                0x2F, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn running_status() {
+        let mut writer = writer(Format::SingleTrack);
+        writer.set_running_status(true);
+        writer.write(0, &message(&[0x90, 0x3C, 0x64]));
+        writer.write(0, &message(&[0x90, 0x3C, 0x00]));
+        writer.write_meta(0, MetaEvent::Marker(b"A"));
+        writer.write(0, &message(&[0x90, 0x3E, 0x64]));
+        writer.write(0, &message(&[0x80, 0x3E, 0x00]));
+        writer.end_track(0);
+        assert_eq!(
+            track(writer.data()),
+            &[
+                0x00, 0x90, 0x3C, 0x64, 0x00, 0x3C, 0x00, 0x00, 0xFF, 0x06, 0x01, b'A', 0x00, 0x90,
+                0x3E, 0x64, 0x00, 0x80, 0x3E, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn running_status_reset_between_tracks() {
+        let mut writer = writer(Format::Simultaneous);
+        writer.set_running_status(true);
+        writer.write(0, &message(&[0x90, 0x3C, 0x64]));
+        writer.end_track(0);
+        writer.write(0, &message(&[0x90, 0x3C, 0x64]));
+        writer.end_track(0);
+
+        let smf = Smf::try_from(writer.data()).unwrap();
+        for track in smf.tracks() {
+            assert_eq!(&track.unwrap().data()[..4], &[0x00, 0x90, 0x3C, 0x64]);
+        }
+    }
+
+    #[test]
+    fn sysex() {
+        let mut writer = writer(Format::SingleTrack);
+        writer.set_running_status(true);
+        writer.write(0, &message(&[0x90, 0x3C, 0x64]));
+        writer.write(0, &message(&[0xF0, 0x43, 0x12, 0x00, 0xF7]));
+        writer.write(0, &message(&[0x90, 0x3C, 0x00]));
+        writer.end_track(0);
+        assert_eq!(
+            track(writer.data()),
+            &[
+                0x00, 0x90, 0x3C, 0x64, 0x00, 0xF0, 0x04, 0x43, 0x12, 0x00, 0xF7, 0x00, 0x90, 0x3C,
+                0x00, 0x00, 0xFF, 0x2F, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn sysex7() {
+        use crate::{sysex7::Sysex7, Sysex};
+
+        let mut message = Sysex7::<Vec<u8>>::new();
+        message.set_payload((0..200).map(|i| ux::u7::new(i % 0x80)));
+
+        let mut writer = writer(Format::SingleTrack);
+        writer.write(0, &message);
+        writer.end_track(0);
+
+        let data = track(writer.data());
+        assert_eq!(&data[..4], &[0x00, 0xF0, 0x81, 0x49]);
+        assert_eq!(
+            data[4..204],
+            (0..200).map(|i| i % 0x80).collect::<Vec<u8>>()
+        );
+        assert_eq!(data[204], 0xF7);
+    }
+
+    #[test]
+    #[cfg(feature = "system-common")]
+    fn system_common_messages_are_escaped() {
+        let mut writer = writer(Format::SingleTrack);
+        writer.write(0, &message(&[0xF2, 0x10, 0x20]));
+        writer.write(0, &message(&[0xF8]));
+        writer.end_track(0);
+        assert_eq!(
+            track(writer.data()),
+            &[0x00, 0xF7, 0x03, 0xF2, 0x10, 0x20, 0x00, 0xF7, 0x01, 0xF8, 0x00, 0xFF, 0x2F, 0x00]
+        );
+    }
+
+    #[test]
+    fn meta_events() {
+        let mut writer = writer(Format::SingleTrack);
+        writer.write_meta(0, MetaEvent::Tempo(ux::u24::new(500_000)));
+        writer.write_meta(
+            0,
+            MetaEvent::KeySignature {
+                sharps_flats: -2,
+                minor: true,
+            },
+        );
+        writer.end_track(0x100);
+        assert_eq!(
+            track(writer.data()),
+            &[
+                0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, 0x00, 0xFF, 0x59, 0x02, 0xFE, 0x01, 0x82,
+                0x00, 0xFF, 0x2F, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn track_length() {
+        let mut writer = writer(Format::SingleTrack);
+        writer.write(0, &message(&[0x90, 0x3C, 0x64]));
+        assert_eq!(&writer.data()[18..22], &[0x0, 0x0, 0x0, 0x0]);
+        writer.end_track(0);
+        assert_eq!(&writer.data()[18..22], &[0x0, 0x0, 0x0, 0x8]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut writer = writer(Format::Simultaneous);
+        writer.set_running_status(true);
+        writer.write_meta(0, MetaEvent::TrackName(b"Drums"));
+        writer.write(0, &message(&[0x99, 0x24, 0x64]));
+        writer.write(240, &message(&[0x99, 0x24, 0x00]));
+        writer.end_track(240);
+        writer.write(0, &message(&[0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7]));
+        writer.end_track(0);
+
+        let smf = Smf::try_from(writer.data()).unwrap();
+        assert_eq!(smf.format(), Format::Simultaneous);
+        assert_eq!(smf.number_of_tracks(), 2);
+
+        let tracks: Vec<_> = smf.tracks().map(|t| t.unwrap()).collect();
+        let events: Vec<_> = tracks[0].events().map(|e| e.unwrap()).collect();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], (0, Event::Meta(MetaEvent::TrackName(b"Drums"))));
+        assert_eq!(events[2].0, 240);
+        let Event::Midi(ref note_off) = events[2].1 else {
+            panic!();
+        };
+        assert_eq!(note_off.data(), &[0x99, 0x24, 0x00]);
+        assert_eq!(events[3], (240, Event::Meta(MetaEvent::EndOfTrack)));
+
+        let events: Vec<_> = tracks[1].events().map(|e| e.unwrap()).collect();
+        assert_eq!(
+            events,
+            std::vec![
+                (
+                    0,
+                    Event::Sysex(SysexEvent::new(&[0x7E, 0x7F, 0x09, 0x01, 0xF7]))
+                ),
+                (0, Event::Meta(MetaEvent::EndOfTrack)),
+            ]
+        );
+    }
+
+    #[test]
+    fn try_write_array() {
+        let mut writer = SmfWriter::<[u8; 30]>::new(
+            Format::SingleTrack,
+            Division::TicksPerQuarterNote(ux::u15::new(96)),
+        );
+        assert_eq!(writer.try_write(0, &message(&[0x90, 0x3C, 0x64])), Ok(()));
+        assert_eq!(writer.try_end_track(0), Ok(()));
+        assert_eq!(writer.data().len(), 30);
+        assert_eq!(Smf::try_from(writer.data()).unwrap().tracks().count(), 1);
+    }
+
+    #[test]
+    fn try_write_overflow() {
+        let mut writer = SmfWriter::<[u8; 29]>::new(
+            Format::SingleTrack,
+            Division::TicksPerQuarterNote(ux::u15::new(96)),
+        );
+        assert_eq!(writer.try_write(0, &message(&[0x90, 0x3C, 0x64])), Ok(()));
+        assert_eq!(writer.try_end_track(0), Err(BufferOverflow));
+        assert_eq!(writer.data().len(), 26);
+    }
+
+    #[test]
+    fn try_write_overflow_header() {
+        let mut writer = SmfWriter::<[u8; 16]>::new(
+            Format::SingleTrack,
+            Division::TicksPerQuarterNote(ux::u15::new(96)),
+        );
+        assert_eq!(
+            writer.try_write(0, &message(&[0x90, 0x3C, 0x64])),
+            Err(BufferOverflow)
+        );
+        assert_eq!(writer.data(), &[0u8; 0]);
+        assert_eq!(writer.number_of_tracks(), 0);
+    }
+
+    #[test]
+    fn write_into_slice() {
+        let mut storage = [0x0; 64];
+        let mut writer = SmfWriter::new_with_buffer(
+            &mut storage[..],
+            Format::SingleTrack,
+            Division::TicksPerQuarterNote(ux::u15::new(96)),
+        );
+        writer.try_write(0, &message(&[0x90, 0x3C, 0x64])).unwrap();
+        writer.try_end_track(0).unwrap();
+        assert_eq!(writer.data().len(), 30);
+    }
+
+    #[test]
+    #[should_panic]
+    fn delta_too_large() {
+        let mut writer = writer(Format::SingleTrack);
+        writer.write(0x1000_0000, &message(&[0x90, 0x3C, 0x64]));
+    }
+}
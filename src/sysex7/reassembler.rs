@@ -0,0 +1,370 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Ump},
+    error::BufferOverflow,
+    sysex7::{
+        packet::{Packet, Status},
+        Sysex7,
+    },
+    Grouped,
+};
+
+/// Errors reported by the [Sysex7Reassembler].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReassemblyError {
+    /// A continue packet arrived on a group with no message in progress.
+    /// The packet is discarded.
+    UnexpectedContinue,
+    /// An end packet arrived on a group with no message in progress.
+    /// The packet is discarded.
+    UnexpectedEnd,
+    /// A start or complete packet arrived on a group
+    /// which already has a message in progress.
+    /// The unfinished message is discarded.
+    /// A start packet begins a new message, a complete packet is discarded.
+    Interrupted,
+    /// The message doesn't fit into the storage of the reassembler.
+    /// The unfinished message is discarded.
+    BufferOverflow,
+}
+
+impl core::convert::From<BufferOverflow> for ReassemblyError {
+    fn from(_value: BufferOverflow) -> Self {
+        ReassemblyError::BufferOverflow
+    }
+}
+
+/// Rebuilds [Sysex7] messages from a stream of individual [Packet]s.
+///
+/// Sysex7 packets of different groups may be interleaved,
+/// so the reassembler keeps one message in progress per group.
+/// Messages are built into buffers of type `B`.
+/// Reassemblers backed by resizable buffers accept messages of any length
+/// via [push](Sysex7Reassembler::push), and reassemblers backed by fixed size
+/// buffers accept messages up to the size of the buffer via
+/// [try_push](Sysex7Reassembler::try_push).
+///
+/// ```rust
+/// use midi2::{prelude::*, sysex7::{Packet, Sysex7Reassembler}};
+///
+/// let mut reassembler = Sysex7Reassembler::<Vec<u32>>::new();
+///
+/// let packets = [
+///     [0x3016_0001, 0x0203_0405], // start on group 0
+///     [0x3112_0A0B, 0x0000_0000], // start on group 1
+///     [0x3032_0607, 0x0000_0000], // end on group 0
+///     [0x3131_0C00, 0x0000_0000], // end on group 1
+/// ];
+///
+/// let mut messages = Vec::new();
+/// for packet in packets {
+///     let packet = Packet::try_from(&packet[..]).expect("Valid data");
+///     if let Some(message) = reassembler.push(packet).expect("Valid sequence") {
+///         messages.push(message);
+///     }
+/// }
+///
+/// assert_eq!(messages.len(), 2);
+/// assert_eq!(messages[0].data(), &[0x3016_0001, 0x0203_0405, 0x3032_0607, 0x0000_0000]);
+/// assert_eq!(messages[1].data(), &[0x3112_0A0B, 0x0000_0000, 0x3131_0C00, 0x0000_0000]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sysex7Reassembler<B: Ump + BufferMut> {
+    buffers: [B; 16],
+    sizes: [usize; 16],
+}
+
+impl<B: Ump + BufferMut + BufferDefault> core::default::Default for Sysex7Reassembler<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault> Sysex7Reassembler<B> {
+    pub fn new() -> Self {
+        Sysex7Reassembler {
+            buffers: core::array::from_fn(|_| B::default()),
+            sizes: [0; 16],
+        }
+    }
+
+    /// Discard the messages in progress on every group.
+    pub fn reset(&mut self) {
+        self.sizes = [0; 16];
+    }
+
+    /// Whether a message is in progress on the given group.
+    pub fn in_progress(&self, group: crate::ux::u4) -> bool {
+        self.sizes[usize::from(u8::from(group))] != 0
+    }
+
+    fn push_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        packet: Packet,
+        resize: R,
+    ) -> Result<Option<Sysex7<B>>, ReassemblyError> {
+        let group = usize::from(u8::from(packet.group()));
+        let in_progress = self.sizes[group] != 0;
+
+        match (packet.status(), in_progress) {
+            (Status::Continue, false) => return Err(ReassemblyError::UnexpectedContinue),
+            (Status::End, false) => return Err(ReassemblyError::UnexpectedEnd),
+            (Status::Complete, true) => {
+                self.sizes[group] = 0;
+                return Err(ReassemblyError::Interrupted);
+            }
+            (Status::Start, true) => {
+                self.sizes[group] = 0;
+                self.append(group, &packet, &resize)?;
+                return Err(ReassemblyError::Interrupted);
+            }
+            (Status::Start, false) | (Status::Continue, true) => {
+                self.append(group, &packet, &resize)?;
+                return Ok(None);
+            }
+            (Status::Complete, false) | (Status::End, true) => {
+                self.append(group, &packet, &resize)?;
+            }
+        }
+
+        let size = core::mem::replace(&mut self.sizes[group], 0);
+        let mut buffer = core::mem::replace(&mut self.buffers[group], B::default());
+        // shrink resizable buffers down to the message
+        let _ = resize(&mut buffer, size);
+        // packets are validated on construction and the statuses are
+        // consistent by construction, so the message is always valid
+        debug_assert!(Sysex7::try_from(&buffer.buffer()[..size]).is_ok());
+        Ok(Some(Sysex7(buffer)))
+    }
+
+    fn append<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        group: usize,
+        packet: &Packet,
+        resize: &R,
+    ) -> Result<(), BufferOverflow> {
+        let size = self.sizes[group];
+        let buffer = &mut self.buffers[group];
+        if let Err(e) = resize(buffer, size + 2) {
+            self.sizes[group] = 0;
+            return Err(e);
+        }
+        buffer.buffer_mut()[size..size + 2].copy_from_slice(&packet[..]);
+        // clear any stale data following the message
+        for word in buffer.buffer_mut()[size + 2..].iter_mut() {
+            *word = 0x0;
+        }
+        self.sizes[group] = size + 2;
+        Ok(())
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault + BufferResize> Sysex7Reassembler<B> {
+    /// Feed the next packet to the reassembler.
+    ///
+    /// Returns the message completed by this packet, if any.
+    pub fn push(&mut self, packet: Packet) -> Result<Option<Sysex7<B>>, ReassemblyError> {
+        self.push_impl(packet, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault + BufferTryResize> Sysex7Reassembler<B> {
+    /// Feed the next packet to the reassembler.
+    ///
+    /// Returns the message completed by this packet, if any.
+    /// Fails with [ReassemblyError::BufferOverflow] when the message
+    /// exceeds the capacity of the buffer.
+    pub fn try_push(&mut self, packet: Packet) -> Result<Option<Sysex7<B>>, ReassemblyError> {
+        self.push_impl(packet, |buffer, size| buffer.try_resize(size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ux::u4, Data};
+    use pretty_assertions::assert_eq;
+
+    fn packet(data: [u32; 2]) -> Packet {
+        Packet::try_from(&data[..]).unwrap()
+    }
+
+    #[test]
+    fn complete_packet() {
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        let message = reassembler
+            .push(packet([0x3003_0102, 0x0300_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.data(), &[0x3003_0102, 0x0300_0000]);
+    }
+
+    #[test]
+    fn multi_packet_message() {
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        assert_eq!(
+            reassembler.push(packet([0x3016_0001, 0x0203_0405])),
+            Ok(None)
+        );
+        assert!(reassembler.in_progress(u4::new(0x0)));
+        assert_eq!(
+            reassembler.push(packet([0x3026_0607, 0x0809_0A0B])),
+            Ok(None)
+        );
+        let message = reassembler
+            .push(packet([0x3032_0C0D, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message.data(),
+            &[
+                0x3016_0001,
+                0x0203_0405,
+                0x3026_0607,
+                0x0809_0A0B,
+                0x3032_0C0D,
+                0x0000_0000
+            ]
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0)));
+    }
+
+    #[test]
+    fn interleaved_groups() {
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        assert_eq!(
+            reassembler.push(packet([0x3016_0001, 0x0203_0405])),
+            Ok(None)
+        );
+        assert_eq!(
+            reassembler.push(packet([0x3A16_1011, 0x1213_1415])),
+            Ok(None)
+        );
+        let message = reassembler
+            .push(packet([0x3A31_1600, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message.data(),
+            &[0x3A16_1011, 0x1213_1415, 0x3A31_1600, 0x0000_0000]
+        );
+        let message = reassembler
+            .push(packet([0x3031_0600, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message.data(),
+            &[0x3016_0001, 0x0203_0405, 0x3031_0600, 0x0000_0000]
+        );
+    }
+
+    #[test]
+    fn unexpected_continue() {
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        assert_eq!(
+            reassembler.push(packet([0x3026_0607, 0x0809_0A0B])),
+            Err(ReassemblyError::UnexpectedContinue)
+        );
+    }
+
+    #[test]
+    fn unexpected_end() {
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        assert_eq!(
+            reassembler.push(packet([0x3032_0C0D, 0x0000_0000])),
+            Err(ReassemblyError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn start_interrupts_message() {
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        assert_eq!(
+            reassembler.push(packet([0x3016_0001, 0x0203_0405])),
+            Ok(None)
+        );
+        assert_eq!(
+            reassembler.push(packet([0x3016_1011, 0x1213_1415])),
+            Err(ReassemblyError::Interrupted)
+        );
+        let message = reassembler
+            .push(packet([0x3031_1600, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message.data(),
+            &[0x3016_1011, 0x1213_1415, 0x3031_1600, 0x0000_0000]
+        );
+    }
+
+    #[test]
+    fn complete_interrupts_message() {
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        assert_eq!(
+            reassembler.push(packet([0x3016_0001, 0x0203_0405])),
+            Ok(None)
+        );
+        assert_eq!(
+            reassembler.push(packet([0x3003_0102, 0x0300_0000])),
+            Err(ReassemblyError::Interrupted)
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0)));
+    }
+
+    #[test]
+    fn fixed_size_buffers() {
+        let mut reassembler = Sysex7Reassembler::<[u32; 4]>::new();
+        assert_eq!(
+            reassembler.try_push(packet([0x3016_0001, 0x0203_0405])),
+            Ok(None)
+        );
+        let message = reassembler
+            .try_push(packet([0x3031_0600, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message.data(),
+            &[0x3016_0001, 0x0203_0405, 0x3031_0600, 0x0000_0000]
+        );
+    }
+
+    #[test]
+    fn fixed_size_buffers_overflow() {
+        let mut reassembler = Sysex7Reassembler::<[u32; 4]>::new();
+        assert_eq!(
+            reassembler.try_push(packet([0x3016_0001, 0x0203_0405])),
+            Ok(None)
+        );
+        assert_eq!(
+            reassembler.try_push(packet([0x3026_0607, 0x0809_0A0B])),
+            Ok(None)
+        );
+        assert_eq!(
+            reassembler.try_push(packet([0x3032_0C0D, 0x0000_0000])),
+            Err(ReassemblyError::BufferOverflow)
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0)));
+    }
+
+    #[test]
+    fn payload() {
+        use crate::Sysex;
+        let mut reassembler = Sysex7Reassembler::<std::vec::Vec<u32>>::new();
+        reassembler
+            .push(packet([0x3016_0001, 0x0203_0405]))
+            .unwrap();
+        let message = reassembler
+            .push(packet([0x3031_0600, 0x0000_0000]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message
+                .payload()
+                .map(u8::from)
+                .collect::<std::vec::Vec<_>>(),
+            std::vec![0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6]
+        );
+    }
+}
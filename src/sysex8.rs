@@ -5,8 +5,10 @@ use crate::{
 };
 
 mod packet;
+mod reassembler;
 
 pub use packet::Packet;
+pub use reassembler::*;
 
 pub(crate) const UMP_MESSAGE_TYPE: u8 = 0x5;
 
@@ -0,0 +1,676 @@
+use crate::{
+    buffer::{BufferDefault, BufferMut, BufferResize, BufferTryResize, Ump},
+    detail::BitOps,
+    error::BufferOverflow,
+    sysex8::{
+        packet::{Packet, Status},
+        Sysex8,
+    },
+    ux::u4,
+    Grouped,
+};
+
+/// Errors reported by the [Sysex8Reassembler].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReassemblyError {
+    /// A continue packet arrived for a stream with no message in progress.
+    /// The packet is discarded.
+    UnexpectedContinue,
+    /// An end packet arrived for a stream with no message in progress.
+    /// The packet is discarded.
+    UnexpectedEnd,
+    /// The payload of the message exceeds the maximum payload size
+    /// of the reassembler. The unfinished message is discarded.
+    /// A rejected start or complete packet discards no other message.
+    PayloadTooLarge,
+    /// The message doesn't fit into the storage of the reassembler.
+    /// The unfinished message is discarded.
+    /// A rejected start or complete packet discards no other message.
+    BufferOverflow,
+}
+
+impl core::convert::From<BufferOverflow> for ReassemblyError {
+    fn from(_value: BufferOverflow) -> Self {
+        ReassemblyError::BufferOverflow
+    }
+}
+
+/// Why an unfinished stream was discarded by the [Sysex8Reassembler].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbortReason {
+    /// A start or complete packet arrived for the stream
+    /// before the message in progress was finished.
+    Interrupted,
+    /// The sender terminated the stream with an unexpected end packet.
+    Cancelled,
+    /// No packet arrived for the stream within the timeout.
+    TimedOut,
+    /// The stream was the least recently active one when a new
+    /// stream needed a slot and all slots were taken.
+    Evicted,
+}
+
+/// Notification of an unfinished stream being discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Abort {
+    pub group: u4,
+    pub stream_id: u8,
+    pub reason: AbortReason,
+}
+
+/// The outcome of feeding a packet to the [Sysex8Reassembler].
+///
+/// A single packet may both finish a message and discard another,
+/// e.g. a complete packet which interrupts a message in progress on the same stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reassembled<B: Ump> {
+    message: Option<Sysex8<B>>,
+    aborted: Option<Abort>,
+}
+
+impl<B: Ump> Reassembled<B> {
+    /// The message completed by the packet, if any.
+    pub fn message(&self) -> Option<&Sysex8<B>> {
+        self.message.as_ref()
+    }
+
+    pub fn into_message(self) -> Option<Sysex8<B>> {
+        self.message
+    }
+
+    /// The stream discarded as a consequence of the packet, if any.
+    pub fn aborted(&self) -> Option<Abort> {
+        self.aborted
+    }
+}
+
+impl<B: Ump> core::default::Default for Reassembled<B> {
+    fn default() -> Self {
+        Reassembled {
+            message: None,
+            aborted: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<B: Ump + BufferMut> {
+    active: bool,
+    group: u4,
+    stream_id: u8,
+    size: usize,
+    payload_size: usize,
+    last_tick: u64,
+    buffer: B,
+}
+
+impl<B: Ump + BufferMut> Slot<B> {
+    fn matches(&self, group: u4, stream_id: u8) -> bool {
+        self.active && self.group == group && self.stream_id == stream_id
+    }
+
+    fn abort(&mut self, reason: AbortReason) -> Abort {
+        self.active = false;
+        Abort {
+            group: self.group,
+            stream_id: self.stream_id,
+            reason,
+        }
+    }
+}
+
+/// Rebuilds [Sysex8] messages from a stream of individual [Packet]s.
+///
+/// Every group can carry up to 256 interleaved sysex8 streams, told apart
+/// by their stream id. The reassembler keeps up to `N` messages in progress,
+/// each identified by its group and stream id.
+/// When a new stream starts while all `N` slots are taken, the least recently
+/// active stream is evicted.
+///
+/// Messages whose payload exceeds the maximum payload size are rejected.
+///
+/// Time is measured with a tick counter supplied by the caller
+/// with every packet. Streams without any activity for longer than the timeout
+/// are discarded by [evict_expired](Sysex8Reassembler::evict_expired).
+///
+/// Every discarded stream is reported with an [Abort] notification.
+///
+/// ```rust
+/// use midi2::{prelude::*, sysex8::{AbortReason, Packet, Sysex8Reassembler}};
+///
+/// let mut reassembler = Sysex8Reassembler::<Vec<u32>, 4>::new(1024, 100);
+///
+/// let packets = [
+///     [0x501E_0100, 0x0102_0304, 0x0506_0708, 0x090A_0B0C], // start on stream 0x1
+///     [0x501E_0200, 0x0102_0304, 0x0506_0708, 0x090A_0B0C], // start on stream 0x2
+///     [0x5032_010D, 0x0000_0000, 0x0000_0000, 0x0000_0000], // end on stream 0x1
+/// ];
+///
+/// let mut messages = Vec::new();
+/// for (tick, packet) in packets.into_iter().enumerate() {
+///     let packet = Packet::try_from(&packet[..]).expect("Valid data");
+///     let reassembled = reassembler.push(packet, tick as u64).expect("Valid sequence");
+///     if let Some(message) = reassembled.into_message() {
+///         messages.push(message);
+///     }
+/// }
+///
+/// assert_eq!(messages.len(), 1);
+/// assert_eq!(messages[0].stream_id(), 0x1);
+/// assert_eq!(messages[0].payload().count(), 14);
+///
+/// // stream 0x2 is never finished
+/// let abort = reassembler.evict_expired(200).expect("Stream timed out");
+/// assert_eq!(abort.stream_id, 0x2);
+/// assert_eq!(abort.reason, AbortReason::TimedOut);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sysex8Reassembler<B: Ump + BufferMut, const N: usize> {
+    slots: [Slot<B>; N],
+    max_payload_size: usize,
+    timeout: u64,
+}
+
+impl<B: Ump + BufferMut + BufferDefault, const N: usize> Sysex8Reassembler<B, N> {
+    /// Create a reassembler accepting messages with up to `max_payload_size`
+    /// payload bytes, which discards streams after `timeout` ticks of inactivity.
+    pub fn new(max_payload_size: usize, timeout: u64) -> Self {
+        Sysex8Reassembler {
+            slots: core::array::from_fn(|_| Slot {
+                active: false,
+                group: Default::default(),
+                stream_id: 0,
+                size: 0,
+                payload_size: 0,
+                last_tick: 0,
+                buffer: B::default(),
+            }),
+            max_payload_size,
+            timeout,
+        }
+    }
+
+    pub fn max_payload_size(&self) -> usize {
+        self.max_payload_size
+    }
+
+    pub fn timeout(&self) -> u64 {
+        self.timeout
+    }
+
+    /// Discard the messages in progress on every stream.
+    ///
+    /// No abort notifications are produced.
+    pub fn reset(&mut self) {
+        for slot in self.slots.iter_mut() {
+            slot.active = false;
+        }
+    }
+
+    /// Whether a message is in progress on the given stream.
+    pub fn in_progress(&self, group: u4, stream_id: u8) -> bool {
+        self.slots.iter().any(|s| s.matches(group, stream_id))
+    }
+
+    /// Discard one stream which has been inactive for longer than the timeout.
+    ///
+    /// Returns `None` when no stream has expired.
+    /// Call repeatedly to discard all the expired streams.
+    pub fn evict_expired(&mut self, now: u64) -> Option<Abort> {
+        let timeout = self.timeout;
+        self.slots
+            .iter_mut()
+            .find(|s| s.active && now.wrapping_sub(s.last_tick) > timeout)
+            .map(|s| s.abort(AbortReason::TimedOut))
+    }
+
+    fn push_impl<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        packet: Packet,
+        now: u64,
+        resize: R,
+    ) -> Result<Reassembled<B>, ReassemblyError> {
+        let group = packet.group();
+        let stream_id = packet.stream_id();
+        let index = self.slots.iter().position(|s| s.matches(group, stream_id));
+        let mut reassembled = Reassembled::default();
+
+        match (packet.status(), index) {
+            (Status::Continue, None) => Err(ReassemblyError::UnexpectedContinue),
+            (Status::End, None) => Err(ReassemblyError::UnexpectedEnd),
+            (Status::UnexpectedEnd(_), None) => Ok(reassembled),
+            (Status::UnexpectedEnd(_), Some(index)) => {
+                reassembled.aborted = Some(self.slots[index].abort(AbortReason::Cancelled));
+                Ok(reassembled)
+            }
+            (Status::Complete, index) => {
+                let buffer = self.first_packet(&packet, &resize)?;
+                if let Some(index) = index {
+                    reassembled.aborted = Some(self.slots[index].abort(AbortReason::Interrupted));
+                }
+                reassembled.message = Some(Sysex8(buffer));
+                Ok(reassembled)
+            }
+            (Status::Start, index) => {
+                let buffer = self.first_packet(&packet, &resize)?;
+                let index = match index {
+                    Some(index) => {
+                        reassembled.aborted =
+                            Some(self.slots[index].abort(AbortReason::Interrupted));
+                        index
+                    }
+                    None => self.free_slot(&mut reassembled)?,
+                };
+                let slot = &mut self.slots[index];
+                slot.active = true;
+                slot.group = group;
+                slot.stream_id = stream_id;
+                slot.size = 4;
+                slot.payload_size = payload_size(&packet);
+                slot.last_tick = now;
+                slot.buffer = buffer;
+                Ok(reassembled)
+            }
+            (Status::Continue, Some(index)) => {
+                self.append(index, &packet, now, &resize)?;
+                Ok(reassembled)
+            }
+            (Status::End, Some(index)) => {
+                self.append(index, &packet, now, &resize)?;
+                let slot = &mut self.slots[index];
+                slot.active = false;
+                let mut buffer = core::mem::replace(&mut slot.buffer, B::default());
+                // shrink resizable buffers down to the message
+                let _ = resize(&mut buffer, slot.size);
+                reassembled.message = Some(Sysex8(buffer));
+                Ok(reassembled)
+            }
+        }
+    }
+
+    // checked before any stream in progress is discarded for the packet,
+    // so that a rejected packet leaves the other streams untouched
+    fn first_packet<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &self,
+        packet: &Packet,
+        resize: &R,
+    ) -> Result<B, ReassemblyError> {
+        if payload_size(packet) > self.max_payload_size {
+            return Err(ReassemblyError::PayloadTooLarge);
+        }
+        let mut buffer = B::default();
+        resize(&mut buffer, 4)?;
+        buffer.buffer_mut()[..4].copy_from_slice(&packet[..]);
+        zero_tail(&mut buffer, 4);
+        Ok(buffer)
+    }
+
+    fn free_slot(&mut self, reassembled: &mut Reassembled<B>) -> Result<usize, ReassemblyError> {
+        if let Some(index) = self.slots.iter().position(|s| !s.active) {
+            return Ok(index);
+        }
+        // all slots are taken: evict the least recently active stream
+        let index = self
+            .slots
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, s)| s.last_tick)
+            .map(|(i, _)| i)
+            .ok_or(ReassemblyError::BufferOverflow)?;
+        reassembled.aborted = Some(self.slots[index].abort(AbortReason::Evicted));
+        Ok(index)
+    }
+
+    fn append<R: Fn(&mut B, usize) -> Result<(), BufferOverflow>>(
+        &mut self,
+        index: usize,
+        packet: &Packet,
+        now: u64,
+        resize: &R,
+    ) -> Result<(), ReassemblyError> {
+        let max_payload_size = self.max_payload_size;
+        let slot = &mut self.slots[index];
+
+        let payload_size = slot.payload_size + payload_size(packet);
+        if payload_size > max_payload_size {
+            slot.active = false;
+            return Err(ReassemblyError::PayloadTooLarge);
+        }
+
+        let size = slot.size;
+        if let Err(e) = resize(&mut slot.buffer, size + 4) {
+            slot.active = false;
+            return Err(e.into());
+        }
+        slot.buffer.buffer_mut()[size..size + 4].copy_from_slice(&packet[..]);
+        zero_tail(&mut slot.buffer, size + 4);
+
+        slot.size = size + 4;
+        slot.payload_size = payload_size;
+        slot.last_tick = now;
+        Ok(())
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault + BufferResize, const N: usize> Sysex8Reassembler<B, N> {
+    /// Feed the next packet to the reassembler, along with the current tick.
+    pub fn push(&mut self, packet: Packet, now: u64) -> Result<Reassembled<B>, ReassemblyError> {
+        self.push_impl(packet, now, |buffer, size| {
+            buffer.resize(size);
+            Ok(())
+        })
+    }
+}
+
+impl<B: Ump + BufferMut + BufferDefault + BufferTryResize, const N: usize> Sysex8Reassembler<B, N> {
+    /// Feed the next packet to the reassembler, along with the current tick.
+    ///
+    /// Fails with [ReassemblyError::BufferOverflow] when the message
+    /// exceeds the capacity of the buffer.
+    pub fn try_push(
+        &mut self,
+        packet: Packet,
+        now: u64,
+    ) -> Result<Reassembled<B>, ReassemblyError> {
+        self.push_impl(packet, now, |buffer, size| buffer.try_resize(size))
+    }
+}
+
+fn payload_size(packet: &Packet) -> usize {
+    // the number of bytes includes the stream id
+    (u8::from(packet[0].nibble(3)) as usize).saturating_sub(1)
+}
+
+fn zero_tail<B: Ump + BufferMut>(buffer: &mut B, size: usize) {
+    for word in buffer.buffer_mut()[size..].iter_mut() {
+        *word = 0x0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Data, Sysex};
+    use pretty_assertions::assert_eq;
+
+    fn packet(data: [u32; 4]) -> Packet {
+        Packet::try_from(&data[..]).unwrap()
+    }
+
+    const START_1: [u32; 4] = [0x501E_0100, 0x0102_0304, 0x0506_0708, 0x090A_0B0C];
+    const CONTINUE_1: [u32; 4] = [0x502E_010D, 0x0E0F_1011, 0x1213_1415, 0x1617_1819];
+    const END_1: [u32; 4] = [0x5032_011A, 0x0000_0000, 0x0000_0000, 0x0000_0000];
+    const START_2: [u32; 4] = [0x5013_0200, 0x0100_0000, 0x0000_0000, 0x0000_0000];
+    const END_2: [u32; 4] = [0x5032_0202, 0x0000_0000, 0x0000_0000, 0x0000_0000];
+
+    #[test]
+    fn complete_packet() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        let reassembled = reassembler
+            .push(
+                packet([0x5004_0101, 0x0203_0000, 0x0000_0000, 0x0000_0000]),
+                0,
+            )
+            .unwrap();
+        assert_eq!(reassembled.aborted(), None);
+        assert_eq!(
+            reassembled.message().unwrap().data(),
+            &[0x5004_0101, 0x0203_0000, 0x0000_0000, 0x0000_0000]
+        );
+    }
+
+    #[test]
+    fn multi_packet_message() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        assert_eq!(
+            reassembler.push(packet(START_1), 0),
+            Ok(Reassembled::default())
+        );
+        assert!(reassembler.in_progress(u4::new(0x0), 0x1));
+        assert_eq!(
+            reassembler.push(packet(CONTINUE_1), 1),
+            Ok(Reassembled::default())
+        );
+        let message = reassembler
+            .push(packet(END_1), 2)
+            .unwrap()
+            .into_message()
+            .unwrap();
+        assert_eq!(message.data(), &[START_1, CONTINUE_1, END_1].concat()[..]);
+        assert_eq!(
+            message.payload().collect::<std::vec::Vec<_>>(),
+            (0x0..=0x1A).collect::<std::vec::Vec<u8>>(),
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x1));
+    }
+
+    #[test]
+    fn interleaved_streams() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        reassembler.push(packet(START_2), 1).unwrap();
+        reassembler.push(packet(CONTINUE_1), 2).unwrap();
+        let message = reassembler
+            .push(packet(END_2), 3)
+            .unwrap()
+            .into_message()
+            .unwrap();
+        assert_eq!(message.data(), &[START_2, END_2].concat()[..]);
+        let message = reassembler
+            .push(packet(END_1), 4)
+            .unwrap()
+            .into_message()
+            .unwrap();
+        assert_eq!(message.data(), &[START_1, CONTINUE_1, END_1].concat()[..]);
+    }
+
+    #[test]
+    fn same_stream_id_on_different_groups() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        let mut start_other_group = START_1;
+        start_other_group[0] |= 0x0300_0000;
+        reassembler.push(packet(START_1), 0).unwrap();
+        reassembler.push(packet(start_other_group), 1).unwrap();
+        assert!(reassembler.in_progress(u4::new(0x0), 0x1));
+        assert!(reassembler.in_progress(u4::new(0x3), 0x1));
+    }
+
+    #[test]
+    fn unexpected_continue() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        assert_eq!(
+            reassembler.push(packet(CONTINUE_1), 0),
+            Err(ReassemblyError::UnexpectedContinue)
+        );
+    }
+
+    #[test]
+    fn unexpected_end() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        assert_eq!(
+            reassembler.push(packet(END_1), 0),
+            Err(ReassemblyError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn start_interrupts_stream() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        let reassembled = reassembler.push(packet(START_1), 1).unwrap();
+        assert_eq!(
+            reassembled.aborted(),
+            Some(Abort {
+                group: u4::new(0x0),
+                stream_id: 0x1,
+                reason: AbortReason::Interrupted,
+            })
+        );
+        assert_eq!(reassembled.message(), None);
+        let message = reassembler
+            .push(packet(END_1), 2)
+            .unwrap()
+            .into_message()
+            .unwrap();
+        assert_eq!(message.data(), &[START_1, END_1].concat()[..]);
+    }
+
+    #[test]
+    fn complete_interrupts_stream() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        let reassembled = reassembler
+            .push(
+                packet([0x5002_0101, 0x0000_0000, 0x0000_0000, 0x0000_0000]),
+                1,
+            )
+            .unwrap();
+        assert_eq!(
+            reassembled.aborted().map(|a| a.reason),
+            Some(AbortReason::Interrupted)
+        );
+        assert_eq!(
+            reassembled.message().unwrap().data(),
+            &[0x5002_0101, 0x0000_0000, 0x0000_0000, 0x0000_0000]
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x1));
+    }
+
+    #[test]
+    fn cancelled_stream() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        let reassembled = reassembler
+            .push(
+                packet([0x5031_0100, 0x0000_0000, 0x0000_0000, 0x0000_0000]),
+                1,
+            )
+            .unwrap();
+        assert_eq!(
+            reassembled.aborted().map(|a| a.reason),
+            Some(AbortReason::Cancelled)
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x1));
+    }
+
+    #[test]
+    fn payload_too_large() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(20, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        assert_eq!(
+            reassembler.push(packet(CONTINUE_1), 1),
+            Err(ReassemblyError::PayloadTooLarge)
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x1));
+    }
+
+    #[test]
+    fn rejected_start_keeps_other_streams() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 1>::new(10, 10);
+        reassembler.push(packet(START_2), 0).unwrap();
+        assert_eq!(
+            reassembler.push(packet(START_1), 1),
+            Err(ReassemblyError::PayloadTooLarge)
+        );
+        assert!(reassembler.in_progress(u4::new(0x0), 0x2));
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x1));
+    }
+
+    #[test]
+    fn rejected_complete_keeps_stream_in_progress() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(10, 10);
+        reassembler.push(packet(START_2), 0).unwrap();
+        assert_eq!(
+            reassembler.push(
+                packet([0x500E_0200, 0x0102_0304, 0x0506_0708, 0x090A_0B0C]),
+                1
+            ),
+            Err(ReassemblyError::PayloadTooLarge)
+        );
+        assert!(reassembler.in_progress(u4::new(0x0), 0x2));
+        let reassembled = reassembler.push(packet(END_2), 2).unwrap();
+        assert_eq!(reassembled.aborted(), None);
+        assert!(reassembled.message().is_some());
+    }
+
+    #[test]
+    fn rejected_start_with_fixed_size_buffers() {
+        let mut reassembler = Sysex8Reassembler::<[u32; 2], 1>::new(64, 10);
+        assert_eq!(
+            reassembler.try_push(packet(START_2), 0),
+            Err(ReassemblyError::BufferOverflow)
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x2));
+    }
+
+    #[test]
+    fn timeout() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        reassembler.push(packet(START_2), 5).unwrap();
+        assert_eq!(reassembler.evict_expired(10), None);
+        assert_eq!(
+            reassembler.evict_expired(11),
+            Some(Abort {
+                group: u4::new(0x0),
+                stream_id: 0x1,
+                reason: AbortReason::TimedOut,
+            })
+        );
+        assert_eq!(reassembler.evict_expired(11), None);
+        assert!(reassembler.in_progress(u4::new(0x0), 0x2));
+    }
+
+    #[test]
+    fn activity_postpones_timeout() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 4>::new(64, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        reassembler.push(packet(CONTINUE_1), 8).unwrap();
+        assert_eq!(reassembler.evict_expired(15), None);
+    }
+
+    #[test]
+    fn eviction() {
+        let mut reassembler = Sysex8Reassembler::<std::vec::Vec<u32>, 1>::new(64, 10);
+        reassembler.push(packet(START_1), 0).unwrap();
+        let reassembled = reassembler.push(packet(START_2), 1).unwrap();
+        assert_eq!(
+            reassembled.aborted(),
+            Some(Abort {
+                group: u4::new(0x0),
+                stream_id: 0x1,
+                reason: AbortReason::Evicted,
+            })
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x1));
+        assert!(reassembler.in_progress(u4::new(0x0), 0x2));
+    }
+
+    #[test]
+    fn fixed_size_buffers() {
+        let mut reassembler = Sysex8Reassembler::<[u32; 8], 4>::new(64, 10);
+        reassembler.try_push(packet(START_2), 0).unwrap();
+        let message = reassembler
+            .try_push(packet(END_2), 1)
+            .unwrap()
+            .into_message()
+            .unwrap();
+        assert_eq!(message.data(), &[START_2, END_2].concat()[..]);
+        assert_eq!(
+            message.payload().collect::<std::vec::Vec<_>>(),
+            std::vec![0x00, 0x01, 0x02]
+        );
+    }
+
+    #[test]
+    fn fixed_size_buffers_overflow() {
+        let mut reassembler = Sysex8Reassembler::<[u32; 8], 4>::new(64, 10);
+        reassembler.try_push(packet(START_1), 0).unwrap();
+        reassembler.try_push(packet(CONTINUE_1), 1).unwrap();
+        assert_eq!(
+            reassembler.try_push(packet(END_1), 2),
+            Err(ReassemblyError::BufferOverflow)
+        );
+        assert!(!reassembler.in_progress(u4::new(0x0), 0x1));
+    }
+}
@@ -20,7 +20,7 @@ mod tune_request {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct TuneRequest {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -44,7 +44,7 @@ mod timing_clock {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct TimingClock {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -68,7 +68,7 @@ mod start {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct Start {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -92,7 +92,7 @@ mod cont {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct Continue {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -116,7 +116,7 @@ mod stop {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct Stop {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -140,7 +140,7 @@ mod active_sensing {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct ActiveSensing {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -164,7 +164,7 @@ mod reset {
         Via(system_common::SystemCommon),
         FixedSize,
         MinSizeUmp(1),
-        MinSizeBytes(2)
+        MinSizeBytes(1)
     )]
     struct Reset {
         #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -318,6 +318,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn one_byte_messages_from_byte_data() {
+        use crate::Data;
+
+        for status in [0xF6_u8, 0xF8, 0xFA, 0xFB, 0xFC, 0xFE, 0xFF] {
+            let data = [status];
+            let message = SystemCommon::try_from(&data[..]).unwrap();
+            assert_eq!(message.data(), &data[..]);
+        }
+    }
+
+    #[test]
+    fn one_byte_messages_new_bytes() {
+        use crate::Data;
+
+        assert_eq!(TuneRequest::<std::vec::Vec<u8>>::new().data(), &[0xF6]);
+        assert_eq!(TimingClock::<std::vec::Vec<u8>>::new().data(), &[0xF8]);
+        assert_eq!(Start::<std::vec::Vec<u8>>::new().data(), &[0xFA]);
+        assert_eq!(Continue::<std::vec::Vec<u8>>::new().data(), &[0xFB]);
+        assert_eq!(Stop::<std::vec::Vec<u8>>::new().data(), &[0xFC]);
+        assert_eq!(ActiveSensing::<std::vec::Vec<u8>>::new().data(), &[0xFE]);
+        assert_eq!(Reset::<std::vec::Vec<u8>>::new().data(), &[0xFF]);
+    }
+
     #[test]
     fn from_ump_data() {
         assert_eq!(
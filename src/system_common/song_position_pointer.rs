@@ -12,7 +12,7 @@ pub const STATUS: u8 = 0xF2;
     Via(system_common::SystemCommon),
     FixedSize,
     MinSizeUmp(1),
-    MinSizeBytes(2)
+    MinSizeBytes(3)
 )]
 struct SongPositionPointer {
     #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -80,6 +80,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn too_short_bytes() {
+        // the message carries both bytes of the position
+        assert_eq!(
+            SongPositionPointer::try_from(&[0xF2_u8, 0x7D][..]),
+            Err(crate::error::InvalidData(
+                crate::detail::common_err_strings::ERR_SLICE_TOO_SHORT
+            )),
+        );
+    }
+
     #[test]
     fn packets() {
         use crate::Packets;
@@ -12,7 +12,7 @@ pub const STATUS: u8 = 0xF1;
     Via(system_common::SystemCommon),
     FixedSize,
     MinSizeUmp(1),
-    MinSizeBytes(3)
+    MinSizeBytes(2)
 )]
 struct TimeCode {
     #[property(common_properties::UmpMessageTypeProperty<UMP_MESSAGE_TYPE>)]
@@ -50,6 +50,23 @@ mod tests {
         assert_eq!(message, TimeCode([0xF1, 0x5F, 0x0,]),);
     }
 
+    #[test]
+    fn new_bytes() {
+        use crate::Data;
+
+        assert_eq!(TimeCode::<std::vec::Vec<u8>>::new().data(), &[0xF1, 0x00]);
+    }
+
+    #[test]
+    fn time_code_bytes() {
+        assert_eq!(
+            TimeCode::try_from(&[0xF1_u8, 0x5F][..])
+                .unwrap()
+                .time_code(),
+            u7::new(0x5F),
+        );
+    }
+
     #[test]
     fn group() {
         assert_eq!(
@@ -0,0 +1,381 @@
+#![doc = include_str!("translation/README.md")]
+
+use crate::{
+    buffer::Ump,
+    channel_voice1::{self, ChannelVoice1},
+    channel_voice2::{self, ChannelVoice2},
+    ux::{u14, u7},
+    Channeled, Grouped,
+};
+
+mod parameter_number;
+mod program_change;
+
+pub use parameter_number::*;
+pub use program_change::*;
+
+/// MIDI 2.0 note off velocity equivalent to the MIDI 1.0 default velocity of 64.
+const DEFAULT_NOTE_OFF_VELOCITY: u16 = 0x8000;
+
+/// Upscale a MIDI 1.0 channel voice message to its MIDI 2.0 equivalent.
+///
+/// See the [module docs](crate::translation) for more info.
+pub fn upscale<B: Ump>(message: &ChannelVoice1<B>) -> ChannelVoice2<[u32; 4]> {
+    match message {
+        ChannelVoice1::NoteOn(m) if m.velocity() == u7::new(0) => {
+            let mut translated = channel_voice2::NoteOff::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_note_number(m.note_number());
+            translated.set_velocity(DEFAULT_NOTE_OFF_VELOCITY);
+            translated.into()
+        }
+        ChannelVoice1::NoteOn(m) => {
+            let mut translated = channel_voice2::NoteOn::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_note_number(m.note_number());
+            translated.set_velocity(scale_up(u8::from(m.velocity()).into(), 7, 16) as u16);
+            translated.into()
+        }
+        ChannelVoice1::NoteOff(m) => {
+            let mut translated = channel_voice2::NoteOff::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_note_number(m.note_number());
+            translated.set_velocity(scale_up(u8::from(m.velocity()).into(), 7, 16) as u16);
+            translated.into()
+        }
+        ChannelVoice1::KeyPressure(m) => {
+            let mut translated = channel_voice2::KeyPressure::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_note_number(m.note_number());
+            translated.set_key_pressure_data(scale_up(u8::from(m.pressure()).into(), 7, 32));
+            translated.into()
+        }
+        ChannelVoice1::ControlChange(m) => {
+            let mut translated = channel_voice2::ControlChange::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_control(m.control());
+            translated.set_control_change_data(scale_up(u8::from(m.control_data()).into(), 7, 32));
+            translated.into()
+        }
+        ChannelVoice1::ProgramChange(m) => {
+            let mut translated = channel_voice2::ProgramChange::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_program(m.program());
+            translated.into()
+        }
+        ChannelVoice1::ChannelPressure(m) => {
+            let mut translated = channel_voice2::ChannelPressure::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_channel_pressure_data(scale_up(u8::from(m.pressure()).into(), 7, 32));
+            translated.into()
+        }
+        ChannelVoice1::PitchBend(m) => {
+            let mut translated = channel_voice2::ChannelPitchBend::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_pitch_bend_data(scale_up(u16::from(m.bend()).into(), 14, 32));
+            translated.into()
+        }
+    }
+}
+
+/// Downscale a MIDI 2.0 channel voice message to its MIDI 1.0 equivalent.
+///
+/// Returns `None` for messages which have no MIDI 1.0 equivalent.
+///
+/// See the [module docs](crate::translation) for more info.
+pub fn downscale<B: Ump>(message: &ChannelVoice2<B>) -> Option<ChannelVoice1<[u32; 4]>> {
+    Some(match message {
+        ChannelVoice2::NoteOn(m) => {
+            if m.velocity() == 0 {
+                let mut translated = channel_voice1::NoteOff::<[u32; 4]>::new();
+                translated.set_group(m.group());
+                translated.set_channel(m.channel());
+                translated.set_note_number(m.note_number());
+                translated.into()
+            } else {
+                let mut translated = channel_voice1::NoteOn::<[u32; 4]>::new();
+                translated.set_group(m.group());
+                translated.set_channel(m.channel());
+                translated.set_note_number(m.note_number());
+                // soft notes must not turn into note offs
+                let velocity = scale_down(m.velocity().into(), 16, 7).max(1);
+                translated.set_velocity(u7::new(velocity as u8));
+                translated.into()
+            }
+        }
+        ChannelVoice2::NoteOff(m) => {
+            let mut translated = channel_voice1::NoteOff::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_note_number(m.note_number());
+            translated.set_velocity(u7::new(scale_down(m.velocity().into(), 16, 7) as u8));
+            translated.into()
+        }
+        ChannelVoice2::KeyPressure(m) => {
+            let mut translated = channel_voice1::KeyPressure::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_note_number(m.note_number());
+            translated.set_pressure(u7::new(scale_down(m.key_pressure_data(), 32, 7) as u8));
+            translated.into()
+        }
+        ChannelVoice2::ControlChange(m) => {
+            let mut translated = channel_voice1::ControlChange::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_control(m.control());
+            translated.set_control_data(u7::new(scale_down(m.control_change_data(), 32, 7) as u8));
+            translated.into()
+        }
+        ChannelVoice2::ProgramChange(m) => {
+            let mut translated = channel_voice1::ProgramChange::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_program(m.program());
+            translated.into()
+        }
+        ChannelVoice2::ChannelPressure(m) => {
+            let mut translated = channel_voice1::ChannelPressure::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_pressure(u7::new(scale_down(m.channel_pressure_data(), 32, 7) as u8));
+            translated.into()
+        }
+        ChannelVoice2::ChannelPitchBend(m) => {
+            let mut translated = channel_voice1::PitchBend::<[u32; 4]>::new();
+            translated.set_group(m.group());
+            translated.set_channel(m.channel());
+            translated.set_bend(u14::new(scale_down(m.pitch_bend_data(), 32, 14) as u16));
+            translated.into()
+        }
+        _ => return None,
+    })
+}
+
+/// Upscale a value to a wider range with the min-center-max algorithm
+/// from the Universal MIDI Packet specification.
+///
+/// The minimum, center and maximum values of the source range
+/// are mapped onto the minimum, center and maximum values of the destination range.
+///
+/// ```rust
+/// use midi2::translation::scale_up;
+///
+/// assert_eq!(scale_up(0x0, 7, 16), 0x0);
+/// assert_eq!(scale_up(0x40, 7, 16), 0x8000);
+/// assert_eq!(scale_up(0x7F, 7, 16), 0xFFFF);
+/// assert_eq!(scale_up(0x2000, 14, 32), 0x8000_0000);
+/// ```
+pub fn scale_up(value: u32, source_bits: u8, destination_bits: u8) -> u32 {
+    debug_assert!(1 < source_bits && source_bits <= destination_bits && destination_bits <= 32);
+
+    let scale_bits = destination_bits - source_bits;
+    let shifted = value << scale_bits;
+    if value <= 1 << (source_bits - 1) {
+        return shifted;
+    }
+
+    let repeat_bits = source_bits - 1;
+    let repeat_value = value & ((1 << repeat_bits) - 1);
+    let mut repeat_value = if scale_bits > repeat_bits {
+        repeat_value << (scale_bits - repeat_bits)
+    } else {
+        repeat_value >> (repeat_bits - scale_bits)
+    };
+
+    let mut scaled = shifted;
+    while repeat_value != 0 {
+        scaled |= repeat_value;
+        repeat_value >>= repeat_bits;
+    }
+    scaled
+}
+
+/// Downscale a value to a narrower range by discarding the least significant bits.
+///
+/// ```rust
+/// use midi2::translation::scale_down;
+///
+/// assert_eq!(scale_down(0xFFFF, 16, 7), 0x7F);
+/// assert_eq!(scale_down(0x8000_0000, 32, 14), 0x2000);
+/// ```
+pub fn scale_down(value: u32, source_bits: u8, destination_bits: u8) -> u32 {
+    debug_assert!(destination_bits <= source_bits && source_bits <= 32);
+    value >> (source_bits - destination_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn upscaled(data: u32) -> std::vec::Vec<u32> {
+        upscale(&ChannelVoice1::try_from(&[data][..]).unwrap())
+            .data()
+            .to_vec()
+    }
+
+    fn downscaled(data: &[u32]) -> Option<std::vec::Vec<u32>> {
+        downscale(&ChannelVoice2::try_from(data).unwrap()).map(|m| m.data().to_vec())
+    }
+
+    #[test]
+    fn scale_up_7_bit_to_16_bit() {
+        assert_eq!(scale_up(0x0, 7, 16), 0x0);
+        assert_eq!(scale_up(0x1, 7, 16), 0x200);
+        assert_eq!(scale_up(0x40, 7, 16), 0x8000);
+        assert_eq!(scale_up(0x41, 7, 16), 0x8208);
+        assert_eq!(scale_up(0x7F, 7, 16), 0xFFFF);
+    }
+
+    #[test]
+    fn scale_up_7_bit_to_32_bit() {
+        assert_eq!(scale_up(0x0, 7, 32), 0x0);
+        assert_eq!(scale_up(0x40, 7, 32), 0x8000_0000);
+        assert_eq!(scale_up(0x7F, 7, 32), 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn scale_up_14_bit_to_32_bit() {
+        assert_eq!(scale_up(0x0, 14, 32), 0x0);
+        assert_eq!(scale_up(0x2000, 14, 32), 0x8000_0000);
+        assert_eq!(scale_up(0x3FFF, 14, 32), 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn scale_round_trip() {
+        for v in 0..0x80 {
+            assert_eq!(scale_down(scale_up(v, 7, 32), 32, 7), v);
+        }
+        for v in 0..0x4000 {
+            assert_eq!(scale_down(scale_up(v, 14, 32), 32, 14), v);
+        }
+    }
+
+    #[test]
+    fn upscale_note_on() {
+        assert_eq!(upscaled(0x2393_3C7F), std::vec![0x4393_3C00, 0xFFFF_0000]);
+    }
+
+    #[test]
+    fn upscale_note_on_zero_velocity() {
+        assert_eq!(upscaled(0x2393_3C00), std::vec![0x4383_3C00, 0x8000_0000]);
+    }
+
+    #[test]
+    fn upscale_note_off() {
+        assert_eq!(upscaled(0x2383_3C40), std::vec![0x4383_3C00, 0x8000_0000]);
+    }
+
+    #[test]
+    fn upscale_key_pressure() {
+        assert_eq!(upscaled(0x25A1_3C40), std::vec![0x45A1_3C00, 0x8000_0000]);
+    }
+
+    #[test]
+    fn upscale_control_change() {
+        assert_eq!(upscaled(0x25B1_077F), std::vec![0x45B1_0700, 0xFFFF_FFFF]);
+    }
+
+    #[test]
+    fn upscale_program_change() {
+        assert_eq!(upscaled(0x25C1_0500), std::vec![0x45C1_0000, 0x0500_0000]);
+    }
+
+    #[test]
+    fn upscale_channel_pressure() {
+        assert_eq!(upscaled(0x25D1_4000), std::vec![0x45D1_0000, 0x8000_0000]);
+    }
+
+    #[test]
+    fn upscale_pitch_bend() {
+        assert_eq!(upscaled(0x25E1_0040), std::vec![0x45E1_0000, 0x8000_0000]);
+        assert_eq!(upscaled(0x25E1_7F7F), std::vec![0x45E1_0000, 0xFFFF_FFFF]);
+    }
+
+    #[test]
+    fn downscale_note_on() {
+        assert_eq!(
+            downscaled(&[0x4393_3C00, 0xFFFF_0000]),
+            Some(std::vec![0x2393_3C7F])
+        );
+    }
+
+    #[test]
+    fn downscale_note_on_zero_velocity() {
+        assert_eq!(
+            downscaled(&[0x4393_3C00, 0x0000_0000]),
+            Some(std::vec![0x2383_3C00])
+        );
+    }
+
+    #[test]
+    fn downscale_note_on_low_velocity() {
+        assert_eq!(
+            downscaled(&[0x4393_3C00, 0x01FF_0000]),
+            Some(std::vec![0x2393_3C01])
+        );
+    }
+
+    #[test]
+    fn downscale_note_off() {
+        assert_eq!(
+            downscaled(&[0x4383_3C00, 0x8000_0000]),
+            Some(std::vec![0x2383_3C40])
+        );
+    }
+
+    #[test]
+    fn downscale_key_pressure() {
+        assert_eq!(
+            downscaled(&[0x45A1_3C00, 0x8000_0000]),
+            Some(std::vec![0x25A1_3C40])
+        );
+    }
+
+    #[test]
+    fn downscale_control_change() {
+        assert_eq!(
+            downscaled(&[0x45B1_0700, 0xFFFF_FFFF]),
+            Some(std::vec![0x25B1_077F])
+        );
+    }
+
+    #[test]
+    fn downscale_program_change() {
+        assert_eq!(
+            downscaled(&[0x45C1_0001, 0x0500_0203]),
+            Some(std::vec![0x25C1_0500])
+        );
+    }
+
+    #[test]
+    fn downscale_channel_pressure() {
+        assert_eq!(
+            downscaled(&[0x45D1_0000, 0x8000_0000]),
+            Some(std::vec![0x25D1_4000])
+        );
+    }
+
+    #[test]
+    fn downscale_pitch_bend() {
+        assert_eq!(
+            downscaled(&[0x45E1_0000, 0x8000_0000]),
+            Some(std::vec![0x25E1_0040])
+        );
+    }
+
+    #[test]
+    fn downscale_untranslatable_message() {
+        assert_eq!(downscaled(&[0x4021_0102, 0x1234_5678]), None);
+    }
+}
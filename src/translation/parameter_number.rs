@@ -0,0 +1,619 @@
+use crate::{
+    buffer::Ump,
+    channel_voice1,
+    channel_voice2::{self, ChannelVoice2},
+    error::BufferOverflow,
+    translation::{scale_down, scale_up, upscale},
+    ux::{u14, u4, u7},
+    Channeled, Data, Grouped,
+};
+
+const CC_DATA_ENTRY_MSB: u8 = 6;
+const CC_DATA_ENTRY_LSB: u8 = 38;
+const CC_DATA_INCREMENT: u8 = 96;
+const CC_DATA_DECREMENT: u8 = 97;
+const CC_NRPN_LSB: u8 = 98;
+const CC_NRPN_MSB: u8 = 99;
+const CC_RPN_LSB: u8 = 100;
+const CC_RPN_MSB: u8 = 101;
+
+const NULL_PARAMETER_NUMBER: u7 = u7::MAX;
+
+// one 14 bit data entry step expressed in the 32 bit controller range
+const RELATIVE_STEP: i32 = 1 << 18;
+
+/// Aggregates MIDI 1.0 registered and non-registered parameter number
+/// control change sequences into single MIDI 2.0 controller messages.
+///
+/// The parameter number selected with CC 101 / 100 (RPN) or CC 99 / 98 (NRPN)
+/// is tracked separately for every group and channel.
+/// Data entry (CC 6 / 38) produces a
+/// [RegisteredController](crate::channel_voice2::RegisteredController) or
+/// [AssignableController](crate::channel_voice2::AssignableController),
+/// and data increment / decrement (CC 96 / 97) produces a
+/// [RelativeRegisteredController](crate::channel_voice2::RelativeRegisteredController) or
+/// [RelativeAssignableController](crate::channel_voice2::RelativeAssignableController)
+/// adjusting the value by a single 14 bit step.
+///
+/// By default the controller message is emitted when the data entry LSB arrives.
+/// Devices which only ever send the data entry MSB can be supported
+/// with [set_data_entry_msb_only](ParameterNumberAggregator::set_data_entry_msb_only),
+/// in which case the message is emitted on the MSB.
+///
+/// Selecting the null parameter number (0x7F / 0x7F) deselects the parameter,
+/// and any subsequent data entry is ignored.
+///
+/// Any other control change is upscaled to a MIDI 2.0
+/// [ControlChange](crate::channel_voice2::ControlChange).
+///
+/// ```rust
+/// use midi2::{prelude::*, translation::ParameterNumberAggregator};
+///
+/// let mut aggregator = ParameterNumberAggregator::new();
+///
+/// let mut translated = Vec::new();
+/// for data in [0x20B0_6500_u32, 0x20B0_6400, 0x20B0_0640, 0x20B0_2600] {
+///     let buffer = [data];
+///     let cc = channel_voice1::ControlChange::try_from(&buffer[..]).expect("Valid data");
+///     if let Some(message) = aggregator.upscale(&cc) {
+///         translated.push(message);
+///     }
+/// }
+///
+/// assert_eq!(translated.len(), 1);
+/// assert_eq!(translated[0].data(), &[0x4020_0000, 0x8000_0000]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterNumberAggregator {
+    state: [[ParameterState; 16]; 16],
+    data_entry_msb_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ParameterKind {
+    #[default]
+    Registered,
+    Assignable,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ParameterState {
+    kind: ParameterKind,
+    bank: Option<u7>,
+    index: Option<u7>,
+    data_msb: Option<u7>,
+}
+
+impl ParameterState {
+    fn select(&mut self, kind: ParameterKind, bank: Option<u7>, index: Option<u7>) {
+        if self.kind != kind {
+            *self = ParameterState {
+                kind,
+                ..Default::default()
+            };
+        }
+        if bank.is_some() {
+            self.bank = bank;
+        }
+        if index.is_some() {
+            self.index = index;
+        }
+        self.data_msb = None;
+    }
+
+    fn selected(&self) -> Option<(ParameterKind, u7, u7)> {
+        match (self.bank, self.index) {
+            (Some(bank), Some(index))
+                if bank == NULL_PARAMETER_NUMBER && index == NULL_PARAMETER_NUMBER =>
+            {
+                None
+            }
+            (Some(bank), Some(index)) => Some((self.kind, bank, index)),
+            _ => None,
+        }
+    }
+}
+
+impl core::default::Default for ParameterNumberAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParameterNumberAggregator {
+    pub fn new() -> Self {
+        ParameterNumberAggregator {
+            state: Default::default(),
+            data_entry_msb_only: false,
+        }
+    }
+
+    pub fn data_entry_msb_only(&self) -> bool {
+        self.data_entry_msb_only
+    }
+
+    pub fn set_data_entry_msb_only(&mut self, msb_only: bool) {
+        self.data_entry_msb_only = msb_only;
+    }
+
+    /// Forget the selected parameter numbers of every group and channel.
+    pub fn reset(&mut self) {
+        self.state = Default::default();
+    }
+
+    /// Feed the next MIDI 1.0 control change to the aggregator.
+    ///
+    /// Returns the MIDI 2.0 message completed by this control change, if any.
+    pub fn upscale<B: Ump>(
+        &mut self,
+        message: &channel_voice1::ControlChange<B>,
+    ) -> Option<ChannelVoice2<[u32; 4]>> {
+        let group = message.group();
+        let channel = message.channel();
+        let value = message.control_data();
+        let state = &mut self.state[usize::from(u8::from(group))][usize::from(u8::from(channel))];
+
+        match u8::from(message.control()) {
+            CC_RPN_MSB => state.select(ParameterKind::Registered, Some(value), None),
+            CC_RPN_LSB => state.select(ParameterKind::Registered, None, Some(value)),
+            CC_NRPN_MSB => state.select(ParameterKind::Assignable, Some(value), None),
+            CC_NRPN_LSB => state.select(ParameterKind::Assignable, None, Some(value)),
+            CC_DATA_ENTRY_MSB => {
+                let (kind, bank, index) = state.selected()?;
+                state.data_msb = Some(value);
+                if self.data_entry_msb_only {
+                    return Some(absolute_controller(
+                        kind,
+                        group,
+                        channel,
+                        bank,
+                        index,
+                        u14::new(u16::from(u8::from(value)) << 7),
+                    ));
+                }
+            }
+            CC_DATA_ENTRY_LSB => {
+                let (kind, bank, index) = state.selected()?;
+                let msb = state.data_msb?;
+                return Some(absolute_controller(
+                    kind,
+                    group,
+                    channel,
+                    bank,
+                    index,
+                    u14::new(u16::from(u8::from(msb)) << 7 | u16::from(u8::from(value))),
+                ));
+            }
+            CC_DATA_INCREMENT => {
+                let (kind, bank, index) = state.selected()?;
+                return Some(relative_controller(
+                    kind,
+                    group,
+                    channel,
+                    bank,
+                    index,
+                    RELATIVE_STEP,
+                ));
+            }
+            CC_DATA_DECREMENT => {
+                let (kind, bank, index) = state.selected()?;
+                return Some(relative_controller(
+                    kind,
+                    group,
+                    channel,
+                    bank,
+                    index,
+                    -RELATIVE_STEP,
+                ));
+            }
+            _ => {
+                let mut cc = channel_voice1::ControlChange::<[u32; 4]>::new();
+                cc.set_group(group);
+                cc.set_channel(channel);
+                cc.set_control(message.control());
+                cc.set_control_data(value);
+                return Some(upscale(&cc.into()));
+            }
+        }
+
+        None
+    }
+}
+
+fn absolute_controller(
+    kind: ParameterKind,
+    group: u4,
+    channel: u4,
+    bank: u7,
+    index: u7,
+    value: u14,
+) -> ChannelVoice2<[u32; 4]> {
+    let data = scale_up(u16::from(value).into(), 14, 32);
+    match kind {
+        ParameterKind::Registered => {
+            let mut message = channel_voice2::RegisteredController::<[u32; 4]>::new();
+            message.set_group(group);
+            message.set_channel(channel);
+            message.set_bank(bank);
+            message.set_index(index);
+            message.set_controller_data(data);
+            message.into()
+        }
+        ParameterKind::Assignable => {
+            let mut message = channel_voice2::AssignableController::<[u32; 4]>::new();
+            message.set_group(group);
+            message.set_channel(channel);
+            message.set_bank(bank);
+            message.set_index(index);
+            message.set_controller_data(data);
+            message.into()
+        }
+    }
+}
+
+fn relative_controller(
+    kind: ParameterKind,
+    group: u4,
+    channel: u4,
+    bank: u7,
+    index: u7,
+    delta: i32,
+) -> ChannelVoice2<[u32; 4]> {
+    match kind {
+        ParameterKind::Registered => {
+            let mut message = channel_voice2::RelativeRegisteredController::<[u32; 4]>::new();
+            message.set_group(group);
+            message.set_channel(channel);
+            message.set_bank(bank);
+            message.set_index(index);
+            message.set_controller_data(delta as u32);
+            message.into()
+        }
+        ParameterKind::Assignable => {
+            let mut message = channel_voice2::RelativeAssignableController::<[u32; 4]>::new();
+            message.set_group(group);
+            message.set_channel(channel);
+            message.set_bank(bank);
+            message.set_index(index);
+            message.set_controller_data(delta as u32);
+            message.into()
+        }
+    }
+}
+
+/// Expand a MIDI 2.0 registered or assignable controller message
+/// into the equivalent sequence of MIDI 1.0 control changes.
+///
+/// The control changes are written into the buffer as MIDI 1.0 channel voice
+/// UMP packets, and the number of words written is returned.
+///
+/// - Absolute controllers produce the parameter number selection
+///   followed by the data entry MSB and LSB.
+/// - Relative controllers produce the parameter number selection followed by
+///   a single data increment or decrement, according to the sign of the data.
+///   Relative controllers with zero data produce no data increment or decrement.
+///
+/// Any other message produces no control changes.
+///
+/// ```rust
+/// use midi2::{prelude::*, translation::downscale_parameter_number};
+///
+/// let message = channel_voice2::ChannelVoice2::try_from(&[0x4020_0000, 0x8000_0000][..])
+///     .expect("Valid data");
+///
+/// let mut buffer = [0x0; 4];
+/// assert_eq!(downscale_parameter_number(&message, &mut buffer), Ok(4));
+/// assert_eq!(buffer, [0x20B0_6500, 0x20B0_6400, 0x20B0_0640, 0x20B0_2600]);
+/// ```
+pub fn downscale_parameter_number<B: Ump>(
+    message: &ChannelVoice2<B>,
+    buffer: &mut [u32],
+) -> Result<usize, BufferOverflow> {
+    let (group, channel, kind, bank, index, data) = match message {
+        ChannelVoice2::RegisteredController(m) => (
+            m.group(),
+            m.channel(),
+            ParameterKind::Registered,
+            m.bank(),
+            m.index(),
+            ControllerData::Absolute(m.controller_data()),
+        ),
+        ChannelVoice2::AssignableController(m) => (
+            m.group(),
+            m.channel(),
+            ParameterKind::Assignable,
+            m.bank(),
+            m.index(),
+            ControllerData::Absolute(m.controller_data()),
+        ),
+        ChannelVoice2::RelativeRegisteredController(m) => (
+            m.group(),
+            m.channel(),
+            ParameterKind::Registered,
+            m.bank(),
+            m.index(),
+            ControllerData::Relative(m.controller_data() as i32),
+        ),
+        ChannelVoice2::RelativeAssignableController(m) => (
+            m.group(),
+            m.channel(),
+            ParameterKind::Assignable,
+            m.bank(),
+            m.index(),
+            ControllerData::Relative(m.controller_data() as i32),
+        ),
+        _ => return Ok(0),
+    };
+
+    let (msb_control, lsb_control) = match kind {
+        ParameterKind::Registered => (CC_RPN_MSB, CC_RPN_LSB),
+        ParameterKind::Assignable => (CC_NRPN_MSB, CC_NRPN_LSB),
+    };
+
+    let mut controls = [(0x0, u7::new(0x0)); 4];
+    controls[0] = (msb_control, bank);
+    controls[1] = (lsb_control, index);
+    let size = match data {
+        ControllerData::Absolute(value) => {
+            let value = scale_down(value, 32, 14);
+            controls[2] = (CC_DATA_ENTRY_MSB, u7::new((value >> 7) as u8));
+            controls[3] = (CC_DATA_ENTRY_LSB, u7::new((value & 0x7F) as u8));
+            4
+        }
+        ControllerData::Relative(0) => 2,
+        ControllerData::Relative(delta) if delta > 0 => {
+            controls[2] = (CC_DATA_INCREMENT, u7::new(0x0));
+            3
+        }
+        ControllerData::Relative(_) => {
+            controls[2] = (CC_DATA_DECREMENT, u7::new(0x0));
+            3
+        }
+    };
+
+    if buffer.len() < size {
+        return Err(BufferOverflow);
+    }
+
+    for ((control, value), word) in controls[..size].iter().zip(buffer.iter_mut()) {
+        let mut cc = channel_voice1::ControlChange::<[u32; 4]>::new();
+        cc.set_group(group);
+        cc.set_channel(channel);
+        cc.set_control(u7::new(*control));
+        cc.set_control_data(*value);
+        *word = cc.data()[0];
+    }
+
+    Ok(size)
+}
+
+enum ControllerData {
+    Absolute(u32),
+    Relative(i32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn aggregate(
+        aggregator: &mut ParameterNumberAggregator,
+        data: &[u32],
+    ) -> std::vec::Vec<std::vec::Vec<u32>> {
+        data.iter()
+            .filter_map(|d| {
+                aggregator.upscale(&channel_voice1::ControlChange::try_from(&[*d][..]).unwrap())
+            })
+            .map(|m| m.data().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn registered_controller() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[0x23B4_6500, 0x23B4_6402, 0x23B4_0640, 0x23B4_2600]
+            ),
+            std::vec![std::vec![0x4324_0002, 0x8000_0000]],
+        );
+    }
+
+    #[test]
+    fn assignable_controller() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[0x23B4_6301, 0x23B4_6202, 0x23B4_067F, 0x23B4_267F]
+            ),
+            std::vec![std::vec![0x4334_0102, 0xFFFF_FFFF]],
+        );
+    }
+
+    #[test]
+    fn data_entry_lsb_updates() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[
+                    0x20B0_6500,
+                    0x20B0_6400,
+                    0x20B0_0640,
+                    0x20B0_2600,
+                    0x20B0_2601
+                ]
+            ),
+            std::vec![
+                std::vec![0x4020_0000, 0x8000_0000],
+                std::vec![0x4020_0000, 0x8004_0020],
+            ],
+        );
+    }
+
+    #[test]
+    fn data_entry_msb_only() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        aggregator.set_data_entry_msb_only(true);
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[0x20B0_6500, 0x20B0_6400, 0x20B0_0640, 0x20B0_067F]
+            ),
+            std::vec![
+                std::vec![0x4020_0000, 0x8000_0000],
+                std::vec![0x4020_0000, 0xFE03_F01F],
+            ],
+        );
+    }
+
+    #[test]
+    fn increment_and_decrement() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[0x20B0_6500, 0x20B0_6401, 0x20B0_6000, 0x20B0_6100]
+            ),
+            std::vec![
+                std::vec![0x4040_0001, 0x0004_0000],
+                std::vec![0x4040_0001, 0xFFFC_0000],
+            ],
+        );
+    }
+
+    #[test]
+    fn null_parameter_number() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[
+                    0x20B0_6500,
+                    0x20B0_6400,
+                    0x20B0_657F,
+                    0x20B0_647F,
+                    0x20B0_0640,
+                    0x20B0_2600,
+                    0x20B0_6000,
+                ]
+            ),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn data_entry_without_parameter_number() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(&mut aggregator, &[0x20B0_0640, 0x20B0_2600]),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn state_per_group_and_channel() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[
+                    0x20B0_6500,
+                    0x20B0_6400,
+                    0x21B0_6501,
+                    0x21B0_6401,
+                    0x20B1_0640,
+                    0x20B1_2600,
+                    0x21B0_0640,
+                    0x21B0_2600,
+                ]
+            ),
+            std::vec![std::vec![0x4120_0101, 0x8000_0000]],
+        );
+    }
+
+    #[test]
+    fn switching_from_rpn_to_nrpn() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[
+                    0x20B0_6500,
+                    0x20B0_6400,
+                    0x20B0_6301,
+                    0x20B0_0640,
+                    0x20B0_2600
+                ]
+            ),
+            std::vec::Vec::<std::vec::Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn other_control_changes() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        assert_eq!(
+            aggregate(&mut aggregator, &[0x20B0_0740]),
+            std::vec![std::vec![0x40B0_0700, 0x8000_0000]],
+        );
+    }
+
+    #[test]
+    fn downscale_registered_controller() {
+        let message = ChannelVoice2::try_from(&[0x4324_0002_u32, 0x8004_0000][..]).unwrap();
+        let mut buffer = [0x0; 4];
+        assert_eq!(downscale_parameter_number(&message, &mut buffer), Ok(4));
+        assert_eq!(buffer, [0x23B4_6500, 0x23B4_6402, 0x23B4_0640, 0x23B4_2601]);
+    }
+
+    #[test]
+    fn downscale_assignable_controller() {
+        let message = ChannelVoice2::try_from(&[0x4334_0102_u32, 0xFFFF_FFFF][..]).unwrap();
+        let mut buffer = [0x0; 4];
+        assert_eq!(downscale_parameter_number(&message, &mut buffer), Ok(4));
+        assert_eq!(buffer, [0x23B4_6301, 0x23B4_6202, 0x23B4_067F, 0x23B4_267F]);
+    }
+
+    #[test]
+    fn downscale_relative_controllers() {
+        let mut buffer = [0x0; 4];
+        let increment = ChannelVoice2::try_from(&[0x4040_0001_u32, 0x0004_0000][..]).unwrap();
+        assert_eq!(downscale_parameter_number(&increment, &mut buffer), Ok(3));
+        assert_eq!(buffer[..3], [0x20B0_6500, 0x20B0_6401, 0x20B0_6000]);
+        let decrement = ChannelVoice2::try_from(&[0x4050_0001_u32, 0xFFFC_0000][..]).unwrap();
+        assert_eq!(downscale_parameter_number(&decrement, &mut buffer), Ok(3));
+        assert_eq!(buffer[..3], [0x20B0_6300, 0x20B0_6201, 0x20B0_6100]);
+    }
+
+    #[test]
+    fn downscale_buffer_too_small() {
+        let message = ChannelVoice2::try_from(&[0x4324_0002_u32, 0x8004_0000][..]).unwrap();
+        let mut buffer = [0x0; 3];
+        assert_eq!(
+            downscale_parameter_number(&message, &mut buffer),
+            Err(BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn downscale_other_message() {
+        let message = ChannelVoice2::try_from(&[0x40B0_0700_u32, 0x8000_0000][..]).unwrap();
+        let mut buffer = [0x0; 4];
+        assert_eq!(downscale_parameter_number(&message, &mut buffer), Ok(0));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut aggregator = ParameterNumberAggregator::new();
+        let sequence = [0x25B9_6312, 0x25B9_6234, 0x25B9_0656, 0x25B9_2678];
+        let translated = aggregate(&mut aggregator, &sequence);
+        let message = ChannelVoice2::try_from(&translated[0][..]).unwrap();
+        let mut buffer = [0x0; 4];
+        assert_eq!(downscale_parameter_number(&message, &mut buffer), Ok(4));
+        assert_eq!(buffer, sequence);
+    }
+}
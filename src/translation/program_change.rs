@@ -0,0 +1,321 @@
+use crate::{
+    buffer::Ump,
+    channel_voice1::{self, ChannelVoice1},
+    channel_voice2::{self, ChannelVoice2},
+    error::BufferOverflow,
+    translation::upscale,
+    ux::{u14, u7},
+    Channeled, Data, Grouped,
+};
+
+const CC_BANK_SELECT_MSB: u8 = 0;
+const CC_BANK_SELECT_LSB: u8 = 32;
+
+/// Combines MIDI 1.0 bank select control changes and program changes
+/// into single MIDI 2.0 program change messages.
+///
+/// The last bank select MSB (CC 0) and LSB (CC 32) are tracked separately
+/// for every group and channel, and are attached to every subsequent program
+/// change on that channel. A missing MSB or LSB is taken to be zero.
+/// Program changes on a channel without any bank select carry no bank.
+///
+/// Bank select control changes produce no message.
+/// Any other message is upscaled with [upscale](crate::translation::upscale).
+///
+/// ```rust
+/// use midi2::{prelude::*, translation::ProgramChangeAggregator};
+///
+/// let mut aggregator = ProgramChangeAggregator::new();
+///
+/// let mut translated = Vec::new();
+/// for data in [0x20B0_0001_u32, 0x20B0_2002, 0x20C0_0500] {
+///     let buffer = [data];
+///     let message = channel_voice1::ChannelVoice1::try_from(&buffer[..]).expect("Valid data");
+///     if let Some(message) = aggregator.upscale(&message) {
+///         translated.push(message);
+///     }
+/// }
+///
+/// let channel_voice2::ChannelVoice2::ProgramChange(program_change) = &translated[0] else {
+///     panic!("Expected a program change");
+/// };
+/// assert_eq!(translated.len(), 1);
+/// assert_eq!(program_change.program(), u7::new(0x05));
+/// assert_eq!(program_change.bank(), Some(u14::new(0x0082)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramChangeAggregator {
+    state: [[BankState; 16]; 16],
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct BankState {
+    msb: Option<u7>,
+    lsb: Option<u7>,
+}
+
+impl BankState {
+    fn bank(&self) -> Option<u14> {
+        if self.msb.is_none() && self.lsb.is_none() {
+            return None;
+        }
+        let msb = u16::from(u8::from(self.msb.unwrap_or_default()));
+        let lsb = u16::from(u8::from(self.lsb.unwrap_or_default()));
+        Some(u14::new(msb << 7 | lsb))
+    }
+}
+
+impl core::default::Default for ProgramChangeAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgramChangeAggregator {
+    pub fn new() -> Self {
+        ProgramChangeAggregator {
+            state: Default::default(),
+        }
+    }
+
+    /// Forget the selected banks of every group and channel.
+    pub fn reset(&mut self) {
+        self.state = Default::default();
+    }
+
+    /// Feed the next MIDI 1.0 channel voice message to the aggregator.
+    ///
+    /// Returns the MIDI 2.0 translation of the message, if any.
+    pub fn upscale<B: Ump>(
+        &mut self,
+        message: &ChannelVoice1<B>,
+    ) -> Option<ChannelVoice2<[u32; 4]>> {
+        let group = message.group();
+        let channel = message.channel();
+        let state = &mut self.state[usize::from(u8::from(group))][usize::from(u8::from(channel))];
+
+        match message {
+            ChannelVoice1::ControlChange(m) if u8::from(m.control()) == CC_BANK_SELECT_MSB => {
+                state.msb = Some(m.control_data());
+                None
+            }
+            ChannelVoice1::ControlChange(m) if u8::from(m.control()) == CC_BANK_SELECT_LSB => {
+                state.lsb = Some(m.control_data());
+                None
+            }
+            ChannelVoice1::ProgramChange(m) => {
+                let mut translated = channel_voice2::ProgramChange::<[u32; 4]>::new();
+                translated.set_group(group);
+                translated.set_channel(channel);
+                translated.set_program(m.program());
+                translated.set_bank(state.bank());
+                Some(translated.into())
+            }
+            _ => Some(upscale(message)),
+        }
+    }
+}
+
+/// Expand a MIDI 2.0 program change into the equivalent MIDI 1.0 messages.
+///
+/// A program change with a bank produces the bank select MSB and LSB
+/// control changes followed by the program change.
+/// A program change without a bank produces only the program change.
+///
+/// The messages are written into the buffer as MIDI 1.0 channel voice
+/// UMP packets, and the number of words written is returned.
+/// Any other message produces no messages.
+///
+/// ```rust
+/// use midi2::{prelude::*, translation::downscale_program_change};
+///
+/// let mut message = channel_voice2::ProgramChange::<[u32; 4]>::new();
+/// message.set_program(u7::new(0x05));
+/// message.set_bank(Some(u14::new(0x0082)));
+/// let message = channel_voice2::ChannelVoice2::from(message);
+///
+/// let mut buffer = [0x0; 3];
+/// assert_eq!(downscale_program_change(&message, &mut buffer), Ok(3));
+/// assert_eq!(buffer, [0x20B0_0001, 0x20B0_2002, 0x20C0_0500]);
+/// ```
+pub fn downscale_program_change<B: Ump>(
+    message: &ChannelVoice2<B>,
+    buffer: &mut [u32],
+) -> Result<usize, BufferOverflow> {
+    let ChannelVoice2::ProgramChange(message) = message else {
+        return Ok(0);
+    };
+
+    let size = if message.bank().is_some() { 3 } else { 1 };
+    if buffer.len() < size {
+        return Err(BufferOverflow);
+    }
+
+    if let Some(bank) = message.bank() {
+        let bank = u16::from(bank);
+        for (word, (control, value)) in buffer.iter_mut().zip([
+            (CC_BANK_SELECT_MSB, (bank >> 7) as u8),
+            (CC_BANK_SELECT_LSB, (bank & 0x7F) as u8),
+        ]) {
+            let mut cc = channel_voice1::ControlChange::<[u32; 4]>::new();
+            cc.set_group(message.group());
+            cc.set_channel(message.channel());
+            cc.set_control(u7::new(control));
+            cc.set_control_data(u7::new(value));
+            *word = cc.data()[0];
+        }
+    }
+
+    let mut program_change = channel_voice1::ProgramChange::<[u32; 4]>::new();
+    program_change.set_group(message.group());
+    program_change.set_channel(message.channel());
+    program_change.set_program(message.program());
+    buffer[size - 1] = program_change.data()[0];
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn aggregate(
+        aggregator: &mut ProgramChangeAggregator,
+        data: &[u32],
+    ) -> std::vec::Vec<ChannelVoice2<[u32; 4]>> {
+        data.iter()
+            .filter_map(|d| aggregator.upscale(&ChannelVoice1::try_from(&[*d][..]).unwrap()))
+            .collect()
+    }
+
+    fn program_change(
+        group: u8,
+        channel: u8,
+        program: u8,
+        bank: Option<u16>,
+    ) -> ChannelVoice2<[u32; 4]> {
+        use crate::ux::u4;
+        let mut message = channel_voice2::ProgramChange::<[u32; 4]>::new();
+        message.set_group(u4::new(group));
+        message.set_channel(u4::new(channel));
+        message.set_program(u7::new(program));
+        message.set_bank(bank.map(u14::new));
+        message.into()
+    }
+
+    #[test]
+    fn program_change_without_bank() {
+        let mut aggregator = ProgramChangeAggregator::new();
+        assert_eq!(
+            aggregate(&mut aggregator, &[0x23C4_0500]),
+            std::vec![program_change(0x3, 0x4, 0x05, None)],
+        );
+    }
+
+    #[test]
+    fn program_change_with_bank() {
+        let mut aggregator = ProgramChangeAggregator::new();
+        assert_eq!(
+            aggregate(&mut aggregator, &[0x23B4_0012, 0x23B4_2034, 0x23C4_0500]),
+            std::vec![program_change(0x3, 0x4, 0x05, Some(0x12 << 7 | 0x34))],
+        );
+    }
+
+    #[test]
+    fn bank_msb_only() {
+        let mut aggregator = ProgramChangeAggregator::new();
+        assert_eq!(
+            aggregate(&mut aggregator, &[0x23B4_0012, 0x23C4_0500]),
+            std::vec![program_change(0x3, 0x4, 0x05, Some(0x12 << 7))],
+        );
+    }
+
+    #[test]
+    fn bank_persists_across_program_changes() {
+        let mut aggregator = ProgramChangeAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[0x23B4_0012, 0x23B4_2034, 0x23C4_0500, 0x23C4_0600]
+            ),
+            std::vec![
+                program_change(0x3, 0x4, 0x05, Some(0x12 << 7 | 0x34)),
+                program_change(0x3, 0x4, 0x06, Some(0x12 << 7 | 0x34)),
+            ],
+        );
+    }
+
+    #[test]
+    fn bank_per_group_and_channel() {
+        let mut aggregator = ProgramChangeAggregator::new();
+        assert_eq!(
+            aggregate(
+                &mut aggregator,
+                &[0x23B4_0012, 0x23B4_2034, 0x23C5_0500, 0x24C4_0500]
+            ),
+            std::vec![
+                program_change(0x3, 0x5, 0x05, None),
+                program_change(0x4, 0x4, 0x05, None),
+            ],
+        );
+    }
+
+    #[test]
+    fn other_messages() {
+        let mut aggregator = ProgramChangeAggregator::new();
+        assert_eq!(
+            aggregate(&mut aggregator, &[0x2090_3C40, 0x20B0_0740])
+                .iter()
+                .map(|m| m.data().to_vec())
+                .collect::<std::vec::Vec<_>>(),
+            std::vec![
+                std::vec![0x4090_3C00, 0x8000_0000],
+                std::vec![0x40B0_0700, 0x8000_0000],
+            ],
+        );
+    }
+
+    #[test]
+    fn downscale_with_bank() {
+        let message = program_change(0x3, 0x4, 0x05, Some(0x12 << 7 | 0x34));
+        let mut buffer = [0x0; 3];
+        assert_eq!(downscale_program_change(&message, &mut buffer), Ok(3));
+        assert_eq!(buffer, [0x23B4_0012, 0x23B4_2034, 0x23C4_0500]);
+    }
+
+    #[test]
+    fn downscale_without_bank() {
+        let message = program_change(0x3, 0x4, 0x05, None);
+        let mut buffer = [0x0; 3];
+        assert_eq!(downscale_program_change(&message, &mut buffer), Ok(1));
+        assert_eq!(buffer[0], 0x23C4_0500);
+    }
+
+    #[test]
+    fn downscale_buffer_too_small() {
+        let message = program_change(0x3, 0x4, 0x05, Some(0x12 << 7 | 0x34));
+        let mut buffer = [0x0; 2];
+        assert_eq!(
+            downscale_program_change(&message, &mut buffer),
+            Err(BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn downscale_other_message() {
+        let message = ChannelVoice2::try_from(&[0x4090_3C00_u32, 0x8000_0000][..]).unwrap();
+        let mut buffer = [0x0; 3];
+        assert_eq!(downscale_program_change(&message, &mut buffer), Ok(0));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut aggregator = ProgramChangeAggregator::new();
+        let sequence = [0x25B9_0056, 0x25B9_2078, 0x25C9_7F00];
+        let translated = aggregate(&mut aggregator, &sequence);
+        let mut buffer = [0x0; 3];
+        assert_eq!(downscale_program_change(&translated[0], &mut buffer), Ok(3));
+        assert_eq!(buffer, sequence);
+    }
+}
@@ -5,28 +5,34 @@ use crate::{
 
 mod device_identity;
 mod end_of_clip;
+mod endpoint;
 mod endpoint_discovery;
 mod endpoint_info;
 mod endpoint_name;
 mod function_block_discovery;
 mod function_block_info;
+mod function_block_map;
 mod function_block_name;
 mod packet;
 mod product_instance_id;
+mod protocol;
 mod start_of_clip;
 mod stream_configuration_notification;
 mod stream_configuration_request;
 
 pub use device_identity::*;
 pub use end_of_clip::*;
+pub use endpoint::*;
 pub use endpoint_discovery::*;
 pub use endpoint_info::*;
 pub use endpoint_name::*;
 pub use function_block_discovery::*;
 pub use function_block_info::*;
+pub use function_block_map::*;
 pub use function_block_name::FunctionBlockName;
 pub use packet::{Format, Packet};
 pub use product_instance_id::*;
+pub use protocol::*;
 pub use start_of_clip::*;
 pub use stream_configuration_notification::*;
 pub use stream_configuration_request::*;
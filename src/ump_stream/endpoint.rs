@@ -0,0 +1,930 @@
+use crate::{
+    buffer::Ump,
+    error::BufferOverflow,
+    ump_stream::{
+        DeviceIdentity, Direction, EndpointDiscovery, EndpointInfo, EndpointName,
+        FunctionBlockDiscovery, FunctionBlockInfo, FunctionBlockName, Midi1Port, ProductInstanceId,
+        Protocol, StreamConfigurationNotification, UiHint, UmpStream,
+    },
+    ux::{u14, u4, u7},
+};
+
+/// The maximum number of function blocks of an endpoint.
+pub const MAX_FUNCTION_BLOCKS: usize = 32;
+/// The maximum length in bytes of the name of an endpoint.
+pub const MAX_ENDPOINT_NAME_SIZE: usize = 98;
+/// The maximum length in bytes of the product instance id of an endpoint.
+pub const MAX_PRODUCT_INSTANCE_ID_SIZE: usize = 42;
+/// The maximum length in bytes of the name of a function block.
+pub const MAX_FUNCTION_BLOCK_NAME_SIZE: usize = 91;
+/// The size in words of the largest text message, i.e. a name of maximum length.
+pub const MAX_TEXT_MESSAGE_SIZE: usize = 28;
+
+// function block discovery messages addressing every block
+const ALL_FUNCTION_BLOCKS: u8 = 0xFF;
+
+/// Text stored inline with a fixed capacity in bytes,
+/// such as the name of an endpoint or of a function block.
+///
+/// The text is always valid UTF-8.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BoundedText<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> BoundedText<N> {
+    pub const fn new() -> Self {
+        BoundedText {
+            bytes: [0x0; N],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(self.as_bytes()).expect("Text should be valid utf8")
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // collects received bytes, dropping whatever doesn't fit or isn't valid utf8
+    fn from_bytes_lossy<I: Iterator<Item = u8>>(bytes: I) -> Self {
+        let mut text = Self::new();
+        for b in bytes.take(N) {
+            text.bytes[text.len] = b;
+            text.len += 1;
+        }
+        if let Err(e) = core::str::from_utf8(text.as_bytes()) {
+            text.len = e.valid_up_to();
+        }
+        text
+    }
+}
+
+impl<const N: usize> core::default::Default for BoundedText<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for BoundedText<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a, const N: usize> core::convert::TryFrom<&'a str> for BoundedText<N> {
+    type Error = BufferOverflow;
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        if value.len() > N {
+            return Err(BufferOverflow);
+        }
+        let mut text = Self::new();
+        text.bytes[..value.len()].copy_from_slice(value.as_bytes());
+        text.len = value.len();
+        Ok(text)
+    }
+}
+
+/// The properties of a function block, as reported by a
+/// [FunctionBlockInfo] and [FunctionBlockName] message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FunctionBlockDescription {
+    pub active: bool,
+    pub first_group: u4,
+    pub number_of_groups_spanned: u8,
+    pub midi_ci_version: u8,
+    pub max_number_of_midi_ci_streams: u8,
+    pub ui_hint: UiHint,
+    pub midi1_port: Option<Midi1Port>,
+    pub direction: Direction,
+    pub name: BoundedText<MAX_FUNCTION_BLOCK_NAME_SIZE>,
+}
+
+/// The properties of a UMP endpoint, as reported during endpoint discovery.
+///
+/// Function blocks are numbered by their position in
+/// [function_blocks](EndpointDescription::function_blocks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointDescription {
+    pub ump_version_major: u8,
+    pub ump_version_minor: u8,
+    pub static_function_blocks: bool,
+    pub supports_midi1_protocol: bool,
+    pub supports_midi2_protocol: bool,
+    pub supports_receiving_jr_timestamps: bool,
+    pub supports_sending_jr_timestamps: bool,
+    pub device_manufacturer: [u7; 3],
+    pub device_family: u14,
+    pub device_family_model_number: u14,
+    pub software_version: [u7; 4],
+    pub name: BoundedText<MAX_ENDPOINT_NAME_SIZE>,
+    pub product_instance_id: BoundedText<MAX_PRODUCT_INSTANCE_ID_SIZE>,
+    function_blocks: [FunctionBlockDescription; MAX_FUNCTION_BLOCKS],
+    number_of_function_blocks: usize,
+}
+
+impl EndpointDescription {
+    /// A description of a UMP version 1.1 endpoint without any function blocks.
+    pub fn new() -> Self {
+        EndpointDescription {
+            ump_version_major: 0x1,
+            ump_version_minor: 0x1,
+            static_function_blocks: false,
+            supports_midi1_protocol: false,
+            supports_midi2_protocol: false,
+            supports_receiving_jr_timestamps: false,
+            supports_sending_jr_timestamps: false,
+            device_manufacturer: [u7::new(0x0); 3],
+            device_family: u14::new(0x0),
+            device_family_model_number: u14::new(0x0),
+            software_version: [u7::new(0x0); 4],
+            name: BoundedText::new(),
+            product_instance_id: BoundedText::new(),
+            function_blocks: [FunctionBlockDescription::default(); MAX_FUNCTION_BLOCKS],
+            number_of_function_blocks: 0,
+        }
+    }
+
+    pub fn function_blocks(&self) -> &[FunctionBlockDescription] {
+        &self.function_blocks[..self.number_of_function_blocks]
+    }
+
+    pub fn function_blocks_mut(&mut self) -> &mut [FunctionBlockDescription] {
+        &mut self.function_blocks[..self.number_of_function_blocks]
+    }
+
+    /// Add a function block, numbered after the existing blocks.
+    ///
+    /// Fails when the endpoint already has [MAX_FUNCTION_BLOCKS] blocks.
+    pub fn push_function_block(
+        &mut self,
+        function_block: FunctionBlockDescription,
+    ) -> Result<(), BufferOverflow> {
+        if self.number_of_function_blocks == MAX_FUNCTION_BLOCKS {
+            return Err(BufferOverflow);
+        }
+        self.function_blocks[self.number_of_function_blocks] = function_block;
+        self.number_of_function_blocks += 1;
+        Ok(())
+    }
+}
+
+impl core::default::Default for EndpointDescription {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The protocol and jitter reduction settings of a UMP stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamConfiguration {
+    pub protocol: Protocol,
+    pub receive_jr_timestamps: bool,
+    pub send_jr_timestamps: bool,
+}
+
+impl StreamConfiguration {
+    // the configuration of a stream before any negotiation
+    pub(crate) fn initial(supports_midi1_protocol: bool, supports_midi2_protocol: bool) -> Self {
+        StreamConfiguration {
+            protocol: if supports_midi2_protocol && !supports_midi1_protocol {
+                Protocol::Midi2
+            } else {
+                Protocol::Midi1
+            },
+            receive_jr_timestamps: false,
+            send_jr_timestamps: false,
+        }
+    }
+}
+
+/// The messages of the responder, either a single packet message or a name.
+pub type EndpointResponse = UmpStream<[u32; MAX_TEXT_MESSAGE_SIZE]>;
+
+/// Answers the discovery messages of a UMP endpoint from a static description.
+///
+/// Responses are generated lazily by the returned [EndpointResponses] iterator,
+/// in the order required by the specification. Names are returned as multi packet
+/// messages which can be sent with [Packets](crate::Packets).
+/// Names and product instance ids which are empty are not sent.
+///
+/// ```rust
+/// use midi2::{prelude::*, ump_stream::*};
+///
+/// let mut description = EndpointDescription::new();
+/// description.supports_midi2_protocol = true;
+/// description.name = "Synth".try_into().unwrap();
+/// description.push_function_block(FunctionBlockDescription {
+///     active: true,
+///     number_of_groups_spanned: 1,
+///     name: "Main".try_into().unwrap(),
+///     ..Default::default()
+/// }).unwrap();
+///
+/// let responder = EndpointResponder::new(description);
+///
+/// let mut discovery = EndpointDiscovery::<[u32; 4]>::new();
+/// discovery.set_request_endpoint_info(true);
+/// discovery.set_request_endpoint_name(true);
+///
+/// let mut responses = responder.respond(&UmpStream::from(discovery));
+/// assert!(matches!(responses.next(), Some(UmpStream::EndpointInfo(_))));
+/// assert!(matches!(responses.next(), Some(UmpStream::EndpointName(_))));
+/// assert_eq!(responses.next(), None);
+///
+/// let mut discovery = FunctionBlockDiscovery::<[u32; 4]>::new();
+/// discovery.set_function_block_number(0xFF);
+/// discovery.set_requesting_function_block_info(true);
+/// discovery.set_requesting_function_block_name(true);
+///
+/// let mut responses = responder.respond(&UmpStream::from(discovery));
+/// assert!(matches!(responses.next(), Some(UmpStream::FunctionBlockInfo(_))));
+/// assert!(matches!(responses.next(), Some(UmpStream::FunctionBlockName(_))));
+/// assert_eq!(responses.next(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointResponder {
+    description: EndpointDescription,
+    stream_configuration: StreamConfiguration,
+}
+
+impl EndpointResponder {
+    /// Create a responder for the described endpoint.
+    ///
+    /// The stream starts with the MIDI 1.0 protocol, unless the endpoint only
+    /// supports MIDI 2.0, and without jitter reduction timestamps.
+    pub fn new(description: EndpointDescription) -> Self {
+        let stream_configuration = StreamConfiguration::initial(
+            description.supports_midi1_protocol,
+            description.supports_midi2_protocol,
+        );
+        EndpointResponder {
+            description,
+            stream_configuration,
+        }
+    }
+
+    pub fn description(&self) -> &EndpointDescription {
+        &self.description
+    }
+
+    pub fn stream_configuration(&self) -> StreamConfiguration {
+        self.stream_configuration
+    }
+
+    pub fn set_stream_configuration(&mut self, stream_configuration: StreamConfiguration) {
+        self.stream_configuration = stream_configuration;
+    }
+
+    /// The responses to a discovery message.
+    ///
+    /// Messages other than endpoint and function block discovery,
+    /// and discovery of unknown function blocks, have no responses.
+    pub fn respond<B: Ump>(&self, message: &UmpStream<B>) -> EndpointResponses<'_> {
+        let mut responses = EndpointResponses {
+            responder: self,
+            endpoint_requests: 0,
+            function_blocks: 0..0,
+            function_block_info: false,
+            function_block_name: false,
+            function_block_info_sent: false,
+        };
+
+        match message {
+            UmpStream::EndpointDiscovery(discovery) => {
+                for (i, requested) in [
+                    discovery.request_endpoint_info(),
+                    discovery.request_device_identity(),
+                    discovery.request_endpoint_name(),
+                    discovery.request_product_instance_id(),
+                    discovery.request_stream_configuration(),
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    if requested {
+                        responses.endpoint_requests |= 1 << i;
+                    }
+                }
+            }
+            UmpStream::FunctionBlockDiscovery(discovery) => {
+                let number_of_function_blocks = self.description.function_blocks().len();
+                responses.function_blocks = match discovery.function_block_number() {
+                    ALL_FUNCTION_BLOCKS => 0..number_of_function_blocks,
+                    n if usize::from(n) < number_of_function_blocks => {
+                        usize::from(n)..usize::from(n) + 1
+                    }
+                    _ => 0..0,
+                };
+                responses.function_block_info = discovery.requesting_function_block_info();
+                responses.function_block_name = discovery.requesting_function_block_name();
+            }
+            _ => {}
+        }
+
+        responses
+    }
+
+    fn endpoint_info(&self) -> EndpointResponse {
+        let d = &self.description;
+        let mut message = EndpointInfo::<[u32; MAX_TEXT_MESSAGE_SIZE]>::new();
+        message.set_ump_version_major(d.ump_version_major);
+        message.set_ump_version_minor(d.ump_version_minor);
+        message.set_static_function_blocks(d.static_function_blocks);
+        message.set_supports_midi1_protocol(d.supports_midi1_protocol);
+        message.set_supports_midi2_protocol(d.supports_midi2_protocol);
+        message.set_supports_receiving_jr_timestamps(d.supports_receiving_jr_timestamps);
+        message.set_supports_sending_jr_timestamps(d.supports_sending_jr_timestamps);
+        message.set_number_of_function_blocks(u7::new(d.function_blocks().len() as u8));
+        message.into()
+    }
+
+    fn device_identity(&self) -> EndpointResponse {
+        let d = &self.description;
+        let mut message = DeviceIdentity::<[u32; MAX_TEXT_MESSAGE_SIZE]>::new();
+        message.set_device_manufacturer(d.device_manufacturer);
+        message.set_device_family(d.device_family);
+        message.set_device_family_model_number(d.device_family_model_number);
+        message.set_software_version(d.software_version);
+        message.into()
+    }
+
+    fn endpoint_name(&self) -> EndpointResponse {
+        let mut message = EndpointName::<[u32; MAX_TEXT_MESSAGE_SIZE]>::new();
+        message
+            .try_set_name(self.description.name.as_str())
+            .expect("Endpoint names should fit in the response buffer");
+        message.into()
+    }
+
+    fn product_instance_id(&self) -> EndpointResponse {
+        let mut message = ProductInstanceId::<[u32; MAX_TEXT_MESSAGE_SIZE]>::new();
+        message
+            .try_set_id(self.description.product_instance_id.as_str())
+            .expect("Product instance ids should fit in the response buffer");
+        message.into()
+    }
+
+    fn stream_configuration_notification(&self) -> EndpointResponse {
+        let configuration = &self.stream_configuration;
+        let mut message = StreamConfigurationNotification::<[u32; MAX_TEXT_MESSAGE_SIZE]>::new();
+        message.set_protocol(configuration.protocol.into());
+        message.set_receive_jr_timestamps(configuration.receive_jr_timestamps);
+        message.set_send_jr_timestamps(configuration.send_jr_timestamps);
+        message.into()
+    }
+
+    fn function_block_info(&self, index: usize) -> EndpointResponse {
+        let block = &self.description.function_blocks()[index];
+        let mut message = FunctionBlockInfo::<[u32; MAX_TEXT_MESSAGE_SIZE]>::new();
+        message.set_active(block.active);
+        message.set_function_block_number(u7::new(index as u8));
+        message.set_first_group(block.first_group);
+        message.set_number_of_groups_spanned(block.number_of_groups_spanned);
+        message.set_midi_ci_version(block.midi_ci_version);
+        message.set_max_number_of_midi_ci_streams(block.max_number_of_midi_ci_streams);
+        message.set_ui_hint(block.ui_hint);
+        message.set_midi1_port(block.midi1_port);
+        message.set_direction(block.direction);
+        message.into()
+    }
+
+    fn function_block_name(&self, index: usize) -> EndpointResponse {
+        let mut message = FunctionBlockName::<[u32; MAX_TEXT_MESSAGE_SIZE]>::new();
+        message.set_function_block(index as u8);
+        message
+            .try_set_name(self.description.function_blocks()[index].name.as_str())
+            .expect("Function block names should fit in the response buffer");
+        message.into()
+    }
+}
+
+/// The responses of an [EndpointResponder] to a discovery message.
+#[derive(Debug, Clone)]
+pub struct EndpointResponses<'a> {
+    responder: &'a EndpointResponder,
+    // a bit for each outstanding endpoint discovery request
+    endpoint_requests: u8,
+    function_blocks: core::ops::Range<usize>,
+    function_block_info: bool,
+    function_block_name: bool,
+    // whether the info of the first remaining function block has been sent
+    function_block_info_sent: bool,
+}
+
+impl<'a> core::iter::Iterator for EndpointResponses<'a> {
+    type Item = EndpointResponse;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let responder = self.responder;
+        let description = &responder.description;
+
+        while self.endpoint_requests != 0 {
+            let request = self.endpoint_requests.trailing_zeros();
+            self.endpoint_requests &= !(1 << request);
+            match request {
+                0 => return Some(responder.endpoint_info()),
+                1 => return Some(responder.device_identity()),
+                2 if !description.name.is_empty() => return Some(responder.endpoint_name()),
+                3 if !description.product_instance_id.is_empty() => {
+                    return Some(responder.product_instance_id())
+                }
+                4 => return Some(responder.stream_configuration_notification()),
+                _ => {}
+            }
+        }
+
+        while let Some(index) = self.function_blocks.clone().next() {
+            if self.function_block_info && !self.function_block_info_sent {
+                self.function_block_info_sent = true;
+                return Some(responder.function_block_info(index));
+            }
+            self.function_blocks.next();
+            self.function_block_info_sent = false;
+            if self.function_block_name && !description.function_blocks()[index].name.is_empty() {
+                return Some(responder.function_block_name(index));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> core::iter::FusedIterator for EndpointResponses<'a> {}
+
+/// Discovers the properties of a remote UMP endpoint.
+///
+/// Send the [discovery](EndpointClient::discovery) message and pass every
+/// UMP stream message received from the endpoint to [handle](EndpointClient::handle).
+/// Once the endpoint info arrives, the client asks for the function block discovery
+/// message to send next.
+///
+/// The discovery is complete once the endpoint info, the device identity,
+/// the stream configuration and the info of every function block have been received.
+/// Names and the product instance id are optional, as endpoints need not support them.
+///
+/// ```rust
+/// use midi2::{prelude::*, ump_stream::*};
+///
+/// let mut description = EndpointDescription::new();
+/// description.supports_midi1_protocol = true;
+/// description.name = "Keys".try_into().unwrap();
+/// description.push_function_block(FunctionBlockDescription::default()).unwrap();
+/// let responder = EndpointResponder::new(description.clone());
+///
+/// let mut client = EndpointClient::new();
+/// let mut requests = vec![UmpStream::from(client.discovery())];
+/// while let Some(request) = requests.pop() {
+///     for response in responder.respond(&request) {
+///         if let Some(request) = client.handle(&response) {
+///             requests.push(request.into());
+///         }
+///     }
+/// }
+///
+/// assert!(client.is_complete());
+/// assert_eq!(client.description(), &description);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointClient {
+    description: EndpointDescription,
+    stream_configuration: Option<StreamConfiguration>,
+    endpoint_info_received: bool,
+    device_identity_received: bool,
+    // a bit for each function block whose info has been received
+    function_block_info_received: u32,
+}
+
+impl EndpointClient {
+    pub fn new() -> Self {
+        EndpointClient {
+            description: EndpointDescription::new(),
+            stream_configuration: None,
+            endpoint_info_received: false,
+            device_identity_received: false,
+            function_block_info_received: 0,
+        }
+    }
+
+    /// The endpoint discovery message which starts the discovery,
+    /// requesting every property of the endpoint.
+    pub fn discovery(&self) -> EndpointDiscovery<[u32; 4]> {
+        let mut message = EndpointDiscovery::<[u32; 4]>::new();
+        message.set_ump_version_major(0x1);
+        message.set_ump_version_minor(0x1);
+        message.set_request_endpoint_info(true);
+        message.set_request_device_identity(true);
+        message.set_request_endpoint_name(true);
+        message.set_request_product_instance_id(true);
+        message.set_request_stream_configuration(true);
+        message
+    }
+
+    /// The function block discovery message requesting the info and the name
+    /// of every function block.
+    pub fn function_block_discovery(&self) -> FunctionBlockDiscovery<[u32; 4]> {
+        let mut message = FunctionBlockDiscovery::<[u32; 4]>::new();
+        message.set_function_block_number(ALL_FUNCTION_BLOCKS);
+        message.set_requesting_function_block_info(true);
+        message.set_requesting_function_block_name(true);
+        message
+    }
+
+    /// Record a message received from the endpoint.
+    ///
+    /// Returns the function block discovery message to send when the endpoint info
+    /// announces function blocks.
+    pub fn handle<B: Ump>(
+        &mut self,
+        message: &UmpStream<B>,
+    ) -> Option<FunctionBlockDiscovery<[u32; 4]>> {
+        let d = &mut self.description;
+        match message {
+            UmpStream::EndpointInfo(info) => {
+                d.ump_version_major = info.ump_version_major();
+                d.ump_version_minor = info.ump_version_minor();
+                d.static_function_blocks = info.static_function_blocks();
+                d.supports_midi1_protocol = info.supports_midi1_protocol();
+                d.supports_midi2_protocol = info.supports_midi2_protocol();
+                d.supports_receiving_jr_timestamps = info.supports_receiving_jr_timestamps();
+                d.supports_sending_jr_timestamps = info.supports_sending_jr_timestamps();
+                d.number_of_function_blocks =
+                    usize::from(u8::from(info.number_of_function_blocks()))
+                        .min(MAX_FUNCTION_BLOCKS);
+                self.endpoint_info_received = true;
+                if d.number_of_function_blocks != 0 {
+                    return Some(self.function_block_discovery());
+                }
+            }
+            UmpStream::DeviceIdentity(identity) => {
+                d.device_manufacturer = identity.device_manufacturer();
+                d.device_family = identity.device_family();
+                d.device_family_model_number = identity.device_family_model_number();
+                d.software_version = identity.software_version();
+                self.device_identity_received = true;
+            }
+            UmpStream::EndpointName(name) => {
+                d.name = BoundedText::from_bytes_lossy(name.name_bytes());
+            }
+            UmpStream::ProductInstanceId(id) => {
+                d.product_instance_id = BoundedText::from_bytes_lossy(id.id_bytes());
+            }
+            UmpStream::StreamConfigurationNotification(notification) => {
+                if let Ok(protocol) = Protocol::try_from(notification.protocol()) {
+                    self.stream_configuration = Some(StreamConfiguration {
+                        protocol,
+                        receive_jr_timestamps: notification.receive_jr_timestamps(),
+                        send_jr_timestamps: notification.send_jr_timestamps(),
+                    });
+                }
+            }
+            UmpStream::FunctionBlockInfo(info) => {
+                let index = usize::from(u8::from(info.function_block_number()));
+                if index < MAX_FUNCTION_BLOCKS {
+                    let block = &mut d.function_blocks[index];
+                    block.active = info.active();
+                    block.first_group = info.first_group();
+                    block.number_of_groups_spanned = info.number_of_groups_spanned();
+                    block.midi_ci_version = info.midi_ci_version();
+                    block.max_number_of_midi_ci_streams = info.max_number_of_midi_ci_streams();
+                    block.ui_hint = info.ui_hint();
+                    block.midi1_port = info.midi1_port();
+                    block.direction = info.direction();
+                    self.function_block_info_received |= 1 << index;
+                }
+            }
+            UmpStream::FunctionBlockName(name) => {
+                let index = usize::from(name.function_block());
+                if index < MAX_FUNCTION_BLOCKS {
+                    d.function_blocks[index].name =
+                        BoundedText::from_bytes_lossy(name.name_bytes());
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// The properties of the endpoint received so far.
+    pub fn description(&self) -> &EndpointDescription {
+        &self.description
+    }
+
+    /// The stream configuration of the endpoint, once notified.
+    pub fn stream_configuration(&self) -> Option<StreamConfiguration> {
+        self.stream_configuration
+    }
+
+    pub fn is_complete(&self) -> bool {
+        let number_of_function_blocks = self.description.number_of_function_blocks;
+        let function_blocks_mask = if number_of_function_blocks == MAX_FUNCTION_BLOCKS {
+            u32::MAX
+        } else {
+            (1 << number_of_function_blocks) - 1
+        };
+        self.endpoint_info_received
+            && self.device_identity_received
+            && self.stream_configuration.is_some()
+            && self.function_block_info_received & function_blocks_mask == function_blocks_mask
+    }
+
+    /// Forget everything received, e.g. to start over when the endpoint
+    /// reports changes to its function blocks.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl core::default::Default for EndpointClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Data, Packets, TryRebufferInto};
+    use pretty_assertions::assert_eq;
+    use std::vec::Vec;
+
+    fn description() -> EndpointDescription {
+        let mut description = EndpointDescription::new();
+        description.supports_midi1_protocol = true;
+        description.supports_midi2_protocol = true;
+        description.supports_receiving_jr_timestamps = true;
+        description.device_manufacturer = [u7::new(0x0), u7::new(0x21), u7::new(0x09)];
+        description.device_family = u14::new(0x1234);
+        description.device_family_model_number = u14::new(0x0567);
+        description.software_version = [u7::new(0x1), u7::new(0x2), u7::new(0x3), u7::new(0x4)];
+        description.name = "Gimme some signal 🔊 🙌".try_into().unwrap();
+        description.product_instance_id = "SN-0001".try_into().unwrap();
+        description
+            .push_function_block(FunctionBlockDescription {
+                active: true,
+                first_group: u4::new(0x0),
+                number_of_groups_spanned: 2,
+                ui_hint: UiHint::SenderReciever,
+                direction: Direction::Bidirectional,
+                name: "Main".try_into().unwrap(),
+                ..Default::default()
+            })
+            .unwrap();
+        description
+            .push_function_block(FunctionBlockDescription {
+                active: false,
+                first_group: u4::new(0x2),
+                number_of_groups_spanned: 1,
+                midi1_port: Some(Midi1Port::RestrictBandwidth),
+                direction: Direction::Output,
+                ..Default::default()
+            })
+            .unwrap();
+        description
+    }
+
+    fn endpoint_discovery(filter: u8) -> UmpStream<[u32; 4]> {
+        UmpStream::try_from(&[0xF000_0101, u32::from(filter)][..])
+            .unwrap()
+            .try_rebuffer_into()
+            .unwrap()
+    }
+
+    fn function_block_discovery(number: u8, filter: u8) -> UmpStream<[u32; 4]> {
+        UmpStream::try_from(&[0xF010_0000 | u32::from(number) << 8 | u32::from(filter)][..])
+            .unwrap()
+            .try_rebuffer_into()
+            .unwrap()
+    }
+
+    fn packets(responses: EndpointResponses) -> Vec<Vec<u32>> {
+        responses
+            .flat_map(|r| r.packets().map(|p| p.to_vec()).collect::<Vec<_>>())
+            .collect()
+    }
+
+    #[test]
+    fn text_too_long() {
+        assert_eq!(BoundedText::<3>::try_from("abcd"), Err(BufferOverflow));
+    }
+
+    #[test]
+    fn text_from_invalid_utf8() {
+        let text = BoundedText::<8>::from_bytes_lossy([b'a', 0xF0, 0x9F].into_iter());
+        assert_eq!(text.as_str(), "a");
+    }
+
+    #[test]
+    fn push_too_many_function_blocks() {
+        let mut description = EndpointDescription::new();
+        for _ in 0..MAX_FUNCTION_BLOCKS {
+            assert_eq!(
+                description.push_function_block(FunctionBlockDescription::default()),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            description.push_function_block(FunctionBlockDescription::default()),
+            Err(BufferOverflow)
+        );
+    }
+
+    #[test]
+    fn endpoint_info() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(
+            packets(responder.respond(&endpoint_discovery(0b0000_0001))),
+            std::vec![std::vec![0xF001_0101, 0x0200_0302, 0x0, 0x0]],
+        );
+    }
+
+    #[test]
+    fn device_identity() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(
+            packets(responder.respond(&endpoint_discovery(0b0000_0010))),
+            std::vec![std::vec![
+                0xF002_0000,
+                0x0000_2109,
+                0x3424_670A,
+                0x0102_0304
+            ]],
+        );
+    }
+
+    #[test]
+    fn endpoint_name_is_split_across_packets() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(
+            packets(responder.respond(&endpoint_discovery(0b0000_0100))),
+            std::vec![
+                std::vec![0xF403_4769, 0x6D6D_6520, 0x736F_6D65, 0x2073_6967],
+                std::vec![0xFC03_6E61, 0x6C20_F09F, 0x948A_20F0, 0x9F99_8C00],
+            ],
+        );
+    }
+
+    #[test]
+    fn stream_configuration() {
+        let mut responder = EndpointResponder::new(description());
+        responder.set_stream_configuration(StreamConfiguration {
+            protocol: Protocol::Midi2,
+            receive_jr_timestamps: true,
+            send_jr_timestamps: false,
+        });
+        assert_eq!(
+            packets(responder.respond(&endpoint_discovery(0b0001_0000))),
+            std::vec![std::vec![0xF006_0202, 0x0, 0x0, 0x0]],
+        );
+    }
+
+    #[test]
+    fn endpoint_responses_are_ordered() {
+        let responder = EndpointResponder::new(description());
+        let statuses: Vec<u32> = responder
+            .respond(&endpoint_discovery(0b0001_1111))
+            .map(|r| (r.data()[0] & 0x03FF_0000) >> 16)
+            .collect();
+        assert_eq!(statuses, std::vec![0x1, 0x2, 0x3, 0x4, 0x6]);
+    }
+
+    #[test]
+    fn empty_names_are_not_sent() {
+        let responder = EndpointResponder::new(EndpointDescription::new());
+        let statuses: Vec<u32> = responder
+            .respond(&endpoint_discovery(0b0000_1100))
+            .map(|r| (r.data()[0] & 0x03FF_0000) >> 16)
+            .collect();
+        assert_eq!(statuses, Vec::<u32>::new());
+    }
+
+    #[test]
+    fn single_function_block() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(
+            packets(responder.respond(&function_block_discovery(0x1, 0b11))),
+            std::vec![std::vec![0xF011_010A, 0x0201_0000, 0x0, 0x0]],
+        );
+    }
+
+    #[test]
+    fn all_function_blocks() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(
+            packets(responder.respond(&function_block_discovery(0xFF, 0b11))),
+            std::vec![
+                std::vec![0xF011_8033, 0x0002_0000, 0x0, 0x0],
+                std::vec![0xF012_004D, 0x6169_6E00, 0x0, 0x0],
+                std::vec![0xF011_010A, 0x0201_0000, 0x0, 0x0],
+            ],
+        );
+    }
+
+    #[test]
+    fn function_block_names_only() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(
+            packets(responder.respond(&function_block_discovery(0xFF, 0b01))),
+            std::vec![std::vec![0xF012_004D, 0x6169_6E00, 0x0, 0x0]],
+        );
+    }
+
+    #[test]
+    fn unknown_function_block() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(
+            packets(responder.respond(&function_block_discovery(0x2, 0b11))),
+            Vec::<Vec<u32>>::new(),
+        );
+    }
+
+    #[test]
+    fn default_protocol() {
+        let responder = EndpointResponder::new(description());
+        assert_eq!(responder.stream_configuration().protocol, Protocol::Midi1);
+
+        let mut description = description();
+        description.supports_midi1_protocol = false;
+        let responder = EndpointResponder::new(description);
+        assert_eq!(responder.stream_configuration().protocol, Protocol::Midi2);
+    }
+
+    #[test]
+    fn client_discovery() {
+        let client = EndpointClient::new();
+        assert_eq!(client.discovery().data(), &[0xF000_0101, 0x0000_001F],);
+    }
+
+    #[test]
+    fn client_requests_function_blocks() {
+        let responder = EndpointResponder::new(description());
+        let mut client = EndpointClient::new();
+        let requests: Vec<Vec<u32>> = responder
+            .respond(&client.discovery().into())
+            .filter_map(|response| client.handle(&response))
+            .map(|request| request.data().to_vec())
+            .collect();
+        assert_eq!(requests, std::vec![std::vec![0xF010_FF03]]);
+        assert!(!client.is_complete());
+        assert_eq!(
+            client.description().name.as_str(),
+            "Gimme some signal 🔊 🙌"
+        );
+        assert_eq!(client.description().product_instance_id.as_str(), "SN-0001");
+    }
+
+    #[test]
+    fn client_builds_description() {
+        let mut responder = EndpointResponder::new(description());
+        let configuration = StreamConfiguration {
+            protocol: Protocol::Midi2,
+            receive_jr_timestamps: false,
+            send_jr_timestamps: true,
+        };
+        responder.set_stream_configuration(configuration);
+        let mut client = EndpointClient::new();
+
+        for response in responder.respond(&client.discovery().into()) {
+            if let Some(request) = client.handle(&response) {
+                for response in responder.respond(&request.into()) {
+                    assert_eq!(client.handle(&response), None);
+                }
+            }
+        }
+
+        assert!(client.is_complete());
+        assert_eq!(client.description(), &description());
+        assert_eq!(client.stream_configuration(), Some(configuration));
+    }
+
+    #[test]
+    fn client_without_function_blocks() {
+        let mut description = description();
+        description.number_of_function_blocks = 0;
+        let responder = EndpointResponder::new(description.clone());
+        let mut client = EndpointClient::new();
+        for response in responder.respond(&client.discovery().into()) {
+            assert_eq!(client.handle(&response), None);
+        }
+        assert!(client.is_complete());
+        assert_eq!(client.description().function_blocks(), &[]);
+    }
+
+    #[test]
+    fn client_reset() {
+        let responder = EndpointResponder::new(description());
+        let mut client = EndpointClient::new();
+        for response in responder.respond(&client.discovery().into()) {
+            client.handle(&response);
+        }
+        client.reset();
+        assert_eq!(client, EndpointClient::new());
+    }
+}
@@ -0,0 +1,489 @@
+use crate::{
+    buffer::{BufferMut, Ump},
+    error::InvalidData,
+    traits::Grouped,
+    ump_stream::{
+        Direction, EndpointDescription, FunctionBlockDescription, FunctionBlockInfo,
+        MAX_FUNCTION_BLOCKS,
+    },
+    ux::{u4, u7},
+};
+
+const NUMBER_OF_GROUPS: u8 = 16;
+
+/// The function blocks of an endpoint, indexed by function block number,
+/// used to find which function block owns a group.
+///
+/// The map is built from the [FunctionBlockInfo] messages of the endpoint.
+/// Every function block must span at least one group, within the 16 groups
+/// of the endpoint, and no two function blocks may claim the same group in
+/// the same direction. Bidirectional function blocks claim their groups in
+/// both directions.
+///
+/// Directions are seen from the function block: messages sent to the endpoint
+/// are received by an [Input](Direction::Input) block and messages received from
+/// the endpoint are sent by an [Output](Direction::Output) block.
+///
+/// ```rust
+/// use midi2::{prelude::*, channel_voice2::NoteOn, ump_stream::*};
+///
+/// let mut map = FunctionBlockMap::new();
+/// let mut info = FunctionBlockInfo::<[u32; 4]>::new();
+/// info.set_active(true);
+/// info.set_function_block_number(u7::new(0x0));
+/// info.set_first_group(u4::new(0x4));
+/// info.set_number_of_groups_spanned(2);
+/// info.set_direction(Direction::Input);
+/// map.insert(&info).unwrap();
+///
+/// let (number, _) = map.find(u4::new(0x5), Direction::Input).unwrap();
+/// assert_eq!(number, u7::new(0x0));
+/// assert_eq!(map.find(u4::new(0x5), Direction::Output), None);
+///
+/// // route the second group of the block to the first block of another endpoint
+/// let mut target = FunctionBlockMap::new();
+/// info.set_first_group(u4::new(0x0));
+/// target.insert(&info).unwrap();
+///
+/// let mut message = NoteOn::<[u32; 4]>::new();
+/// message.set_group(u4::new(0x5));
+/// map.remap(&mut message, Direction::Input, &target, u7::new(0x0)).unwrap();
+/// assert_eq!(message.group(), u4::new(0x1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionBlockMap {
+    function_blocks: [Option<FunctionBlockDescription>; MAX_FUNCTION_BLOCKS],
+}
+
+impl FunctionBlockMap {
+    pub fn new() -> Self {
+        FunctionBlockMap {
+            function_blocks: [None; MAX_FUNCTION_BLOCKS],
+        }
+    }
+
+    /// Add the function block described by the message,
+    /// replacing a previous description of the same function block.
+    pub fn insert<B: Ump>(&mut self, info: &FunctionBlockInfo<B>) -> Result<(), InvalidData> {
+        self.insert_function_block(
+            info.function_block_number(),
+            FunctionBlockDescription {
+                active: info.active(),
+                first_group: info.first_group(),
+                number_of_groups_spanned: info.number_of_groups_spanned(),
+                midi_ci_version: info.midi_ci_version(),
+                max_number_of_midi_ci_streams: info.max_number_of_midi_ci_streams(),
+                ui_hint: info.ui_hint(),
+                midi1_port: info.midi1_port(),
+                direction: info.direction(),
+                name: Default::default(),
+            },
+        )
+    }
+
+    /// Add a function block,
+    /// replacing a previous description of the same function block.
+    ///
+    /// The map is left unchanged when the function block is invalid.
+    pub fn insert_function_block(
+        &mut self,
+        number: u7,
+        function_block: FunctionBlockDescription,
+    ) -> Result<(), InvalidData> {
+        let index = usize::from(u8::from(number));
+        if index >= MAX_FUNCTION_BLOCKS {
+            return Err(InvalidData("Function block number out of range"));
+        }
+        // the span comes from a peer, so check it before doing any arithmetic with it
+        let spanned = function_block.number_of_groups_spanned;
+        if spanned == 0
+            || spanned > NUMBER_OF_GROUPS
+            || u16::from(u8::from(function_block.first_group)) + u16::from(spanned)
+                > u16::from(NUMBER_OF_GROUPS)
+        {
+            return Err(InvalidData("Function block groups out of range"));
+        }
+        if self.function_blocks().any(|(other_number, other)| {
+            other_number != number
+                && directions_overlap(other.direction, function_block.direction)
+                && spans_overlap(other, &function_block)
+        }) {
+            return Err(InvalidData("Function block groups overlap another block"));
+        }
+        self.function_blocks[index] = Some(function_block);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, number: u7) -> Option<FunctionBlockDescription> {
+        self.function_blocks
+            .get_mut(usize::from(u8::from(number)))
+            .and_then(Option::take)
+    }
+
+    pub fn function_block(&self, number: u7) -> Option<&FunctionBlockDescription> {
+        self.function_blocks
+            .get(usize::from(u8::from(number)))
+            .and_then(Option::as_ref)
+    }
+
+    /// The function blocks of the map with their numbers, in ascending order.
+    pub fn function_blocks(&self) -> impl Iterator<Item = (u7, &FunctionBlockDescription)> {
+        self.function_blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, function_block)| {
+                function_block
+                    .as_ref()
+                    .map(|function_block| (u7::new(i as u8), function_block))
+            })
+    }
+
+    /// The active function block which owns the group in the given direction.
+    pub fn find(&self, group: u4, direction: Direction) -> Option<(u7, &FunctionBlockDescription)> {
+        self.function_blocks().find(|(_, function_block)| {
+            function_block.active
+                && directions_overlap(function_block.direction, direction)
+                && contains(function_block, group)
+        })
+    }
+
+    /// Move a message from its function block in this map to the
+    /// target function block of another endpoint.
+    ///
+    /// The message keeps its offset from the first group of the function block,
+    /// so the second group of the source block maps to the second group of the target.
+    /// Fails when no active function block owns the group of the message, or
+    /// when the target function block is missing or spans too few groups.
+    pub fn remap<B: Ump + BufferMut, M: Grouped<B>>(
+        &self,
+        message: &mut M,
+        direction: Direction,
+        target: &FunctionBlockMap,
+        target_number: u7,
+    ) -> Result<(), InvalidData> {
+        let group = self.remap_group(message.group(), direction, target, target_number)?;
+        message.set_group(group);
+        Ok(())
+    }
+
+    /// The group of the target function block corresponding to the group
+    /// of this map. See [remap](FunctionBlockMap::remap).
+    pub fn remap_group(
+        &self,
+        group: u4,
+        direction: Direction,
+        target: &FunctionBlockMap,
+        target_number: u7,
+    ) -> Result<u4, InvalidData> {
+        let Some((_, source)) = self.find(group, direction) else {
+            return Err(InvalidData("No function block owns the group"));
+        };
+        let Some(destination) = target.function_block(target_number) else {
+            return Err(InvalidData("Unknown target function block"));
+        };
+        let offset = u8::from(group) - u8::from(source.first_group);
+        if offset >= destination.number_of_groups_spanned {
+            return Err(InvalidData(
+                "Group out of range of the target function block",
+            ));
+        }
+        Ok(u4::new(u8::from(destination.first_group) + offset))
+    }
+}
+
+impl core::default::Default for FunctionBlockMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::convert::TryFrom<&EndpointDescription> for FunctionBlockMap {
+    type Error = InvalidData;
+    fn try_from(description: &EndpointDescription) -> Result<Self, Self::Error> {
+        let mut map = FunctionBlockMap::new();
+        for (i, function_block) in description.function_blocks().iter().enumerate() {
+            map.insert_function_block(u7::new(i as u8), *function_block)?;
+        }
+        Ok(map)
+    }
+}
+
+fn directions_overlap(lhs: Direction, rhs: Direction) -> bool {
+    lhs == rhs || lhs == Direction::Bidirectional || rhs == Direction::Bidirectional
+}
+
+fn contains(function_block: &FunctionBlockDescription, group: u4) -> bool {
+    let first = u8::from(function_block.first_group);
+    (first..first + function_block.number_of_groups_spanned).contains(&u8::from(group))
+}
+
+fn spans_overlap(lhs: &FunctionBlockDescription, rhs: &FunctionBlockDescription) -> bool {
+    let lhs_first = u8::from(lhs.first_group);
+    let rhs_first = u8::from(rhs.first_group);
+    lhs_first < rhs_first + rhs.number_of_groups_spanned
+        && rhs_first < lhs_first + lhs.number_of_groups_spanned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn function_block(
+        first_group: u8,
+        spanned: u8,
+        direction: Direction,
+    ) -> FunctionBlockDescription {
+        FunctionBlockDescription {
+            active: true,
+            first_group: u4::new(first_group),
+            number_of_groups_spanned: spanned,
+            direction,
+            ..Default::default()
+        }
+    }
+
+    fn map() -> FunctionBlockMap {
+        let mut map = FunctionBlockMap::new();
+        map.insert_function_block(
+            u7::new(0x0),
+            function_block(0x0, 4, Direction::Bidirectional),
+        )
+        .unwrap();
+        map.insert_function_block(u7::new(0x1), function_block(0x4, 2, Direction::Input))
+            .unwrap();
+        map.insert_function_block(u7::new(0x2), function_block(0x4, 1, Direction::Output))
+            .unwrap();
+        map
+    }
+
+    #[test]
+    fn insert_info() {
+        let mut message = FunctionBlockInfo::<[u32; 4]>::new();
+        message.set_active(true);
+        message.set_function_block_number(u7::new(0x3));
+        message.set_first_group(u4::new(0xA));
+        message.set_number_of_groups_spanned(3);
+        message.set_direction(Direction::Output);
+        let mut map = FunctionBlockMap::new();
+        assert_eq!(map.insert(&message), Ok(()));
+        assert_eq!(
+            map.function_block(u7::new(0x3)),
+            Some(&function_block(0xA, 3, Direction::Output))
+        );
+    }
+
+    #[test]
+    fn groups_out_of_range() {
+        let mut map = FunctionBlockMap::new();
+        assert_eq!(
+            map.insert_function_block(u7::new(0x0), function_block(0xE, 3, Direction::Input)),
+            Err(InvalidData("Function block groups out of range")),
+        );
+        assert_eq!(map, FunctionBlockMap::new());
+    }
+
+    #[test]
+    fn too_many_groups_spanned() {
+        let mut message = FunctionBlockInfo::<[u32; 4]>::new();
+        message.set_active(true);
+        message.set_first_group(u4::new(0x1));
+        message.set_number_of_groups_spanned(0xFF);
+        let mut map = FunctionBlockMap::new();
+        assert_eq!(
+            map.insert(&message),
+            Err(InvalidData("Function block groups out of range")),
+        );
+        assert_eq!(map, FunctionBlockMap::new());
+        assert_eq!(map.find(u4::new(0x0), Direction::Input), None);
+    }
+
+    #[test]
+    fn no_groups_spanned() {
+        let mut map = FunctionBlockMap::new();
+        assert_eq!(
+            map.insert_function_block(u7::new(0x0), function_block(0x0, 0, Direction::Input)),
+            Err(InvalidData("Function block groups out of range")),
+        );
+    }
+
+    #[test]
+    fn number_out_of_range() {
+        let mut map = FunctionBlockMap::new();
+        assert_eq!(
+            map.insert_function_block(u7::new(0x20), function_block(0x0, 1, Direction::Input)),
+            Err(InvalidData("Function block number out of range")),
+        );
+    }
+
+    #[test]
+    fn overlapping_groups() {
+        let mut map = map();
+        assert_eq!(
+            map.insert_function_block(u7::new(0x3), function_block(0x5, 2, Direction::Input)),
+            Err(InvalidData("Function block groups overlap another block")),
+        );
+        assert_eq!(
+            map.insert_function_block(u7::new(0x3), function_block(0x3, 1, Direction::Output)),
+            Err(InvalidData("Function block groups overlap another block")),
+        );
+        assert_eq!(map, self::map());
+    }
+
+    #[test]
+    fn overlapping_groups_in_other_direction() {
+        let mut map = map();
+        assert_eq!(
+            map.insert_function_block(u7::new(0x3), function_block(0x5, 2, Direction::Output)),
+            Ok(()),
+        );
+    }
+
+    #[test]
+    fn replace_function_block() {
+        let mut map = map();
+        assert_eq!(
+            map.insert_function_block(u7::new(0x1), function_block(0x5, 3, Direction::Input)),
+            Ok(()),
+        );
+        assert_eq!(
+            map.function_block(u7::new(0x1)),
+            Some(&function_block(0x5, 3, Direction::Input))
+        );
+    }
+
+    #[test]
+    fn remove_function_block() {
+        let mut map = map();
+        assert_eq!(
+            map.remove(u7::new(0x1)),
+            Some(function_block(0x4, 2, Direction::Input))
+        );
+        assert_eq!(map.find(u4::new(0x4), Direction::Input), None);
+    }
+
+    #[test]
+    fn find() {
+        let map = map();
+        assert_eq!(
+            map.find(u4::new(0x2), Direction::Input).map(|(n, _)| n),
+            Some(u7::new(0x0))
+        );
+        assert_eq!(
+            map.find(u4::new(0x2), Direction::Output).map(|(n, _)| n),
+            Some(u7::new(0x0))
+        );
+        assert_eq!(
+            map.find(u4::new(0x5), Direction::Input).map(|(n, _)| n),
+            Some(u7::new(0x1))
+        );
+        assert_eq!(
+            map.find(u4::new(0x4), Direction::Output).map(|(n, _)| n),
+            Some(u7::new(0x2))
+        );
+        assert_eq!(map.find(u4::new(0x5), Direction::Output), None);
+        assert_eq!(map.find(u4::new(0xF), Direction::Input), None);
+    }
+
+    #[test]
+    fn find_ignores_inactive_function_blocks() {
+        let mut map = map();
+        let mut inactive = function_block(0x4, 2, Direction::Input);
+        inactive.active = false;
+        map.insert_function_block(u7::new(0x1), inactive).unwrap();
+        assert_eq!(map.find(u4::new(0x4), Direction::Input), None);
+    }
+
+    #[test]
+    fn remap_group() {
+        let mut target = FunctionBlockMap::new();
+        target
+            .insert_function_block(u7::new(0x5), function_block(0x8, 2, Direction::Input))
+            .unwrap();
+        assert_eq!(
+            map().remap_group(u4::new(0x5), Direction::Input, &target, u7::new(0x5)),
+            Ok(u4::new(0x9)),
+        );
+    }
+
+    #[test]
+    fn remap_group_out_of_target_range() {
+        let mut target = FunctionBlockMap::new();
+        target
+            .insert_function_block(u7::new(0x5), function_block(0x8, 2, Direction::Input))
+            .unwrap();
+        assert_eq!(
+            map().remap_group(u4::new(0x3), Direction::Input, &target, u7::new(0x5)),
+            Err(InvalidData(
+                "Group out of range of the target function block"
+            )),
+        );
+    }
+
+    #[test]
+    fn remap_unowned_group() {
+        assert_eq!(
+            map().remap_group(u4::new(0xC), Direction::Input, &map(), u7::new(0x0)),
+            Err(InvalidData("No function block owns the group")),
+        );
+    }
+
+    #[test]
+    fn remap_unknown_target() {
+        assert_eq!(
+            map().remap_group(u4::new(0x0), Direction::Input, &map(), u7::new(0x9)),
+            Err(InvalidData("Unknown target function block")),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn remap_message() {
+        use crate::channel_voice2::NoteOn;
+
+        let mut message = NoteOn::<[u32; 4]>::new();
+        message.set_group(u4::new(0x2));
+        assert_eq!(
+            map().remap(&mut message, Direction::Output, &map(), u7::new(0x2)),
+            Err(InvalidData(
+                "Group out of range of the target function block"
+            )),
+        );
+        assert_eq!(
+            map().remap(&mut message, Direction::Output, &map(), u7::new(0x1)),
+            Err(InvalidData(
+                "Group out of range of the target function block"
+            )),
+        );
+        message.set_group(u4::new(0x1));
+        assert_eq!(
+            map().remap(&mut message, Direction::Output, &map(), u7::new(0x1)),
+            Ok(())
+        );
+        assert_eq!(message.group(), u4::new(0x5));
+    }
+
+    #[test]
+    fn from_endpoint_description() {
+        let mut description = EndpointDescription::new();
+        description
+            .push_function_block(function_block(0x0, 2, Direction::Input))
+            .unwrap();
+        description
+            .push_function_block(function_block(0x1, 2, Direction::Output))
+            .unwrap();
+        let map = FunctionBlockMap::try_from(&description).unwrap();
+        assert_eq!(
+            map.find(u4::new(0x2), Direction::Output).map(|(n, _)| n),
+            Some(u7::new(0x1))
+        );
+
+        description
+            .push_function_block(function_block(0x1, 1, Direction::Bidirectional))
+            .unwrap();
+        assert_eq!(
+            FunctionBlockMap::try_from(&description),
+            Err(InvalidData("Function block groups overlap another block")),
+        );
+    }
+}
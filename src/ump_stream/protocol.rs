@@ -0,0 +1,291 @@
+use crate::{
+    buffer::Ump,
+    error::InvalidData,
+    ump_stream::{
+        EndpointInfo, StreamConfiguration, StreamConfigurationNotification,
+        StreamConfigurationRequest,
+    },
+};
+
+/// The protocol of the channel voice messages of a UMP stream,
+/// as carried by the stream configuration messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Midi1,
+    Midi2,
+}
+
+impl core::convert::TryFrom<u8> for Protocol {
+    type Error = InvalidData;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x1 => Ok(Protocol::Midi1),
+            0x2 => Ok(Protocol::Midi2),
+            _ => Err(InvalidData("Couldn't interpret protocol field")),
+        }
+    }
+}
+
+impl core::convert::From<Protocol> for u8 {
+    fn from(value: Protocol) -> Self {
+        match value {
+            Protocol::Midi1 => 0x1,
+            Protocol::Midi2 => 0x2,
+        }
+    }
+}
+
+impl Protocol {
+    /// The format in which outgoing channel voice messages should be sent
+    /// when the stream uses this protocol.
+    pub fn translation_mode(&self) -> TranslationMode {
+        match self {
+            Protocol::Midi1 => TranslationMode::Midi1ChannelVoice,
+            Protocol::Midi2 => TranslationMode::Midi2ChannelVoice,
+        }
+    }
+}
+
+/// The channel voice messages to use for outgoing traffic.
+///
+/// Messages in the other format should be translated before being sent,
+/// e.g. [channel_voice2](crate::channel_voice2) messages are converted to
+/// [channel_voice1](crate::channel_voice1) messages on a MIDI 1.0 stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TranslationMode {
+    /// UMP MIDI 1.0 channel voice messages (message type 0x2).
+    Midi1ChannelVoice,
+    /// UMP MIDI 2.0 channel voice messages (message type 0x4).
+    Midi2ChannelVoice,
+}
+
+/// Negotiates the stream configuration of an endpoint.
+///
+/// The negotiator is created from the capabilities the endpoint reports in
+/// its [EndpointInfo]. Each [StreamConfigurationRequest] is checked against these
+/// capabilities: a supported protocol is accepted, while an unknown or unsupported
+/// protocol leaves the current one in place. Jitter reduction timestamps are
+/// only enabled in the directions which the endpoint supports.
+///
+/// The resulting [StreamConfigurationNotification] should be sent back
+/// to the requester, and outgoing channel voice messages should follow the
+/// [translation_mode](ProtocolNegotiator::translation_mode) from then on.
+///
+/// ```rust
+/// use midi2::{prelude::*, ump_stream::*};
+///
+/// let mut info = EndpointInfo::<[u32; 4]>::new();
+/// info.set_supports_midi1_protocol(true);
+/// info.set_supports_midi2_protocol(true);
+///
+/// let mut negotiator = ProtocolNegotiator::new(&info);
+/// assert_eq!(negotiator.translation_mode(), TranslationMode::Midi1ChannelVoice);
+///
+/// let mut request = StreamConfigurationRequest::<[u32; 4]>::new();
+/// request.set_protocol(Protocol::Midi2.into());
+/// request.set_receive_jr_timestamps(true);
+///
+/// let notification = negotiator.negotiate(&request);
+/// assert_eq!(notification.protocol(), 0x2);
+/// // the endpoint doesn't support jitter reduction timestamps
+/// assert_eq!(notification.receive_jr_timestamps(), false);
+/// assert_eq!(negotiator.translation_mode(), TranslationMode::Midi2ChannelVoice);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolNegotiator {
+    supports_midi1_protocol: bool,
+    supports_midi2_protocol: bool,
+    supports_receiving_jr_timestamps: bool,
+    supports_sending_jr_timestamps: bool,
+    configuration: StreamConfiguration,
+}
+
+impl ProtocolNegotiator {
+    /// Create a negotiator for an endpoint with the given capabilities.
+    ///
+    /// The stream starts with the MIDI 1.0 protocol, unless the endpoint only
+    /// supports MIDI 2.0, and without jitter reduction timestamps.
+    pub fn new<B: Ump>(info: &EndpointInfo<B>) -> Self {
+        let supports_midi1_protocol = info.supports_midi1_protocol();
+        let supports_midi2_protocol = info.supports_midi2_protocol();
+        ProtocolNegotiator {
+            supports_midi1_protocol,
+            supports_midi2_protocol,
+            supports_receiving_jr_timestamps: info.supports_receiving_jr_timestamps(),
+            supports_sending_jr_timestamps: info.supports_sending_jr_timestamps(),
+            configuration: StreamConfiguration::initial(
+                supports_midi1_protocol,
+                supports_midi2_protocol,
+            ),
+        }
+    }
+
+    /// The current configuration of the stream.
+    pub fn configuration(&self) -> StreamConfiguration {
+        self.configuration
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.configuration.protocol
+    }
+
+    /// The format of outgoing channel voice messages
+    /// under the current configuration.
+    pub fn translation_mode(&self) -> TranslationMode {
+        self.configuration.protocol.translation_mode()
+    }
+
+    pub fn supports(&self, protocol: Protocol) -> bool {
+        match protocol {
+            Protocol::Midi1 => self.supports_midi1_protocol,
+            Protocol::Midi2 => self.supports_midi2_protocol,
+        }
+    }
+
+    /// Apply a request to the configuration of the stream and
+    /// return the notification of the accepted configuration.
+    pub fn negotiate<B: Ump>(
+        &mut self,
+        request: &StreamConfigurationRequest<B>,
+    ) -> StreamConfigurationNotification<[u32; 4]> {
+        if let Ok(protocol) = Protocol::try_from(request.protocol()) {
+            if self.supports(protocol) {
+                self.configuration.protocol = protocol;
+            }
+        }
+        self.configuration.receive_jr_timestamps =
+            request.receive_jr_timestamps() && self.supports_receiving_jr_timestamps;
+        self.configuration.send_jr_timestamps =
+            request.send_jr_timestamps() && self.supports_sending_jr_timestamps;
+        self.notification()
+    }
+
+    /// The notification of the current configuration.
+    pub fn notification(&self) -> StreamConfigurationNotification<[u32; 4]> {
+        let mut message = StreamConfigurationNotification::<[u32; 4]>::new();
+        message.set_protocol(self.configuration.protocol.into());
+        message.set_receive_jr_timestamps(self.configuration.receive_jr_timestamps);
+        message.set_send_jr_timestamps(self.configuration.send_jr_timestamps);
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn info(midi1: bool, midi2: bool, receive_jr: bool, send_jr: bool) -> EndpointInfo<[u32; 4]> {
+        let mut info = EndpointInfo::<[u32; 4]>::new();
+        info.set_supports_midi1_protocol(midi1);
+        info.set_supports_midi2_protocol(midi2);
+        info.set_supports_receiving_jr_timestamps(receive_jr);
+        info.set_supports_sending_jr_timestamps(send_jr);
+        info
+    }
+
+    fn request(
+        protocol: u8,
+        receive_jr: bool,
+        send_jr: bool,
+    ) -> StreamConfigurationRequest<[u32; 4]> {
+        let mut request = StreamConfigurationRequest::<[u32; 4]>::new();
+        request.set_protocol(protocol);
+        request.set_receive_jr_timestamps(receive_jr);
+        request.set_send_jr_timestamps(send_jr);
+        request
+    }
+
+    #[test]
+    fn protocol_from_u8() {
+        assert_eq!(Protocol::try_from(0x1), Ok(Protocol::Midi1));
+        assert_eq!(Protocol::try_from(0x2), Ok(Protocol::Midi2));
+        assert_eq!(
+            Protocol::try_from(0x3),
+            Err(InvalidData("Couldn't interpret protocol field"))
+        );
+    }
+
+    #[test]
+    fn protocol_into_u8() {
+        assert_eq!(u8::from(Protocol::Midi1), 0x1);
+        assert_eq!(u8::from(Protocol::Midi2), 0x2);
+    }
+
+    #[test]
+    fn initial_protocol() {
+        assert_eq!(
+            ProtocolNegotiator::new(&info(true, true, false, false)).protocol(),
+            Protocol::Midi1
+        );
+        assert_eq!(
+            ProtocolNegotiator::new(&info(false, true, false, false)).protocol(),
+            Protocol::Midi2
+        );
+    }
+
+    #[test]
+    fn accept_supported_protocol() {
+        let mut negotiator = ProtocolNegotiator::new(&info(true, true, false, false));
+        assert_eq!(
+            negotiator.negotiate(&request(0x2, false, false)).data(),
+            &[0xF006_0200],
+        );
+        assert_eq!(negotiator.protocol(), Protocol::Midi2);
+        assert_eq!(
+            negotiator.translation_mode(),
+            TranslationMode::Midi2ChannelVoice
+        );
+    }
+
+    #[test]
+    fn reject_unsupported_protocol() {
+        let mut negotiator = ProtocolNegotiator::new(&info(true, false, false, false));
+        assert_eq!(
+            negotiator.negotiate(&request(0x2, false, false)).data(),
+            &[0xF006_0100],
+        );
+        assert_eq!(
+            negotiator.translation_mode(),
+            TranslationMode::Midi1ChannelVoice
+        );
+    }
+
+    #[test]
+    fn reject_unknown_protocol() {
+        let mut negotiator = ProtocolNegotiator::new(&info(true, true, false, false));
+        negotiator.negotiate(&request(0x2, false, false));
+        assert_eq!(
+            negotiator.negotiate(&request(0x7, false, false)).data(),
+            &[0xF006_0200],
+        );
+    }
+
+    #[test]
+    fn accept_supported_jr_timestamps() {
+        let mut negotiator = ProtocolNegotiator::new(&info(true, false, true, false));
+        assert_eq!(
+            negotiator.negotiate(&request(0x1, true, true)).data(),
+            &[0xF006_0102],
+        );
+        assert_eq!(
+            negotiator.configuration(),
+            StreamConfiguration {
+                protocol: Protocol::Midi1,
+                receive_jr_timestamps: true,
+                send_jr_timestamps: false,
+            }
+        );
+    }
+
+    #[test]
+    fn disable_jr_timestamps() {
+        let mut negotiator = ProtocolNegotiator::new(&info(true, false, true, true));
+        negotiator.negotiate(&request(0x1, true, true));
+        assert_eq!(
+            negotiator.negotiate(&request(0x1, false, false)).data(),
+            &[0xF006_0100],
+        );
+    }
+}
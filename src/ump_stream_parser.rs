@@ -0,0 +1,349 @@
+use crate::{
+    detail::{helpers, BitOps},
+    error::InvalidData,
+    UmpMessage,
+};
+
+/// Reads [UmpMessage]s out of a raw stream of UMP words.
+///
+/// The size of each packet is derived from the message type nibble of its
+/// first word. Messages spanning multiple packets (sysex7, sysex8, flex data
+/// and ump stream messages) are grouped together from their start packet
+/// up to and including their end packet. The packets of such a message
+/// are expected to be contiguous in the stream.
+///
+/// Messages which fail to parse are yielded as errors and skipped over,
+/// so that parsing can continue with the rest of the stream.
+///
+/// A message which is cut off by the end of the buffer is not consumed.
+/// Iteration stops at that point and [consumed](UmpStreamParser::consumed)
+/// reports how many words were read, so that the caller can keep the
+/// remaining words until more data arrives.
+///
+/// ## Basic Usage
+///
+/// ```rust
+/// use midi2::prelude::*;
+///
+/// let words = [
+///     0x4090_3C00, 0xFFFF_0000, // channel voice 2 note on
+///     0x3016_0001, 0x0203_0405, // sysex7 start
+///     0x3032_0607, 0x0000_0000, // sysex7 end
+///     0x2080_3C00, // channel voice 1 note off
+///     0x4080_3C00, // truncated channel voice 2 note off
+/// ];
+///
+/// let mut parser = UmpStreamParser::new(&words[..]);
+///
+/// assert!(matches!(parser.next(), Some(Ok(UmpMessage::ChannelVoice2(_)))));
+/// assert!(matches!(parser.next(), Some(Ok(UmpMessage::Sysex7(_)))));
+/// assert!(matches!(parser.next(), Some(Ok(UmpMessage::ChannelVoice1(_)))));
+/// assert_eq!(parser.next(), None);
+///
+/// assert_eq!(parser.consumed(), 7);
+/// assert_eq!(parser.remaining(), &[0x4080_3C00][..]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UmpStreamParser<'a> {
+    buffer: &'a [u32],
+    position: usize,
+}
+
+impl<'a> UmpStreamParser<'a> {
+    pub fn new(buffer: &'a [u32]) -> Self {
+        UmpStreamParser {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// The number of words which have been read from the stream so far.
+    pub fn consumed(&self) -> usize {
+        self.position
+    }
+
+    /// The words of the stream which have not yet been read.
+    pub fn remaining(&self) -> &'a [u32] {
+        &self.buffer[self.position..]
+    }
+}
+
+impl<'a> core::iter::Iterator for UmpStreamParser<'a> {
+    type Item = Result<UmpMessage<&'a [u32]>, InvalidData>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.remaining();
+        let ump_type = remaining.first()?.nibble(0);
+        let stride = packet_size(u8::from(ump_type));
+
+        if remaining.len() < stride {
+            return None;
+        }
+
+        let size = match format(&remaining[..stride]) {
+            None | Some(Format::Complete) => stride,
+            Some(Format::Continue) | Some(Format::End) => {
+                self.position += stride;
+                return Some(Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_BEGIN)));
+            }
+            Some(Format::Start) => {
+                let mut size = stride;
+                loop {
+                    if remaining.get(size)?.nibble(0) != ump_type {
+                        self.position += size;
+                        return Some(Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_END)));
+                    }
+                    match format(remaining.get(size..size + stride)?) {
+                        Some(Format::Continue) => size += stride,
+                        Some(Format::End) => break size + stride,
+                        _ => {
+                            self.position += size;
+                            return Some(Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_END)));
+                        }
+                    }
+                }
+            }
+        };
+
+        self.position += size;
+        Some(UmpMessage::try_from(&remaining[..size]))
+    }
+}
+
+impl<'a> core::iter::FusedIterator for UmpStreamParser<'a> {}
+
+/// The number of words in a packet of the given ump message type.
+pub(crate) fn packet_size(ump_type: u8) -> usize {
+    match ump_type {
+        0x0 | 0x1 | 0x2 | 0x6 | 0x7 => 1,
+        0x3 | 0x4 | 0x8 | 0x9 | 0xA => 2,
+        0xB | 0xC => 3,
+        0x5 | 0xD | 0xE | 0xF => 4,
+        _ => unreachable!(),
+    }
+}
+
+pub(crate) enum Format {
+    Complete,
+    Start,
+    Continue,
+    End,
+}
+
+// None for packets which are not part of a multi packet message
+pub(crate) fn format(packet: &[u32]) -> Option<Format> {
+    let format = match u8::from(packet[0].nibble(0)) {
+        // sysex7 and sysex8
+        0x3 | 0x5 => u8::from(packet[0].nibble(2)),
+        // flex data
+        0xD => u8::from(packet[0].crumb(4)),
+        // ump stream
+        0xF => u8::from(packet[0].crumb(2)),
+        _ => return None,
+    };
+    match format {
+        0x0 => Some(Format::Complete),
+        0x1 => Some(Format::Start),
+        0x2 => Some(Format::Continue),
+        0x3 => Some(Format::End),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty() {
+        let mut parser = UmpStreamParser::new(&[]);
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "channel-voice1", feature = "utility"))]
+    fn single_packet_messages() {
+        let buffer = [0x2090_3C7F, 0x0010_1234, 0x2080_3C00];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[0..1]).unwrap()))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[1..2]).unwrap()))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[2..3]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn multi_packet_sysex7() {
+        let buffer = [
+            0x3016_0001,
+            0x0203_0405,
+            0x3026_0607,
+            0x0809_0A0B,
+            0x3032_0C0D,
+            0x0000_0000,
+            0x3002_0E0F,
+            0x0000_0000,
+        ];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[0..6]).unwrap()))
+        );
+        assert_eq!(parser.consumed(), 6);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[6..8]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "sysex8")]
+    fn multi_packet_sysex8() {
+        let buffer = [
+            0x501E_0000,
+            0x0102_0304,
+            0x0506_0708,
+            0x090A_0B0C,
+            0x5034_000D,
+            0x0E0F_0000,
+            0x0000_0000,
+            0x0000_0000,
+        ];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[..]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "flex-data")]
+    fn multi_packet_flex_data() {
+        let buffer = [
+            0xD050_0101,
+            0x5368_6164,
+            0x6F77_7320,
+            0x6F66_2074,
+            0xD090_0101,
+            0x6865_2046,
+            0x6F72_676F,
+            0x7474_656E,
+            0xD0D0_0101,
+            0x2043_6174,
+            0x6865_6472,
+            0x616C_0000,
+        ];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[..]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 12);
+    }
+
+    #[test]
+    #[cfg(feature = "ump-stream")]
+    fn multi_packet_ump_stream() {
+        let buffer = [
+            0xF403_4769,
+            0x6D6D_6520,
+            0x736F_6D65,
+            0x2073_6967,
+            0xFC03_6E61,
+            0x6C00_0000,
+            0x0000_0000,
+            0x0000_0000,
+        ];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[..]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 8);
+    }
+
+    #[test]
+    #[cfg(feature = "channel-voice2")]
+    fn truncated_packet() {
+        let buffer = [0x4090_3C00, 0xFFFF_0000, 0x4080_3C00];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[0..2]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 2);
+        assert_eq!(parser.remaining(), &[0x4080_3C00][..]);
+    }
+
+    #[test]
+    #[cfg(feature = "sysex7")]
+    fn truncated_multi_packet_message() {
+        let buffer = [0x3016_0001, 0x0203_0405, 0x3026_0607, 0x0809_0A0B];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(parser.next(), None);
+        assert_eq!(parser.consumed(), 0);
+        assert_eq!(parser.remaining(), &buffer[..]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "channel-voice1"))]
+    fn unexpected_continue_packet() {
+        let buffer = [0x3026_0607, 0x0809_0A0B, 0x2080_3C00];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_BEGIN)))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[2..3]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "sysex7", feature = "channel-voice1"))]
+    fn interrupted_multi_packet_message() {
+        let buffer = [0x3016_0001, 0x0203_0405, 0x2080_3C00];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert_eq!(
+            parser.next(),
+            Some(Err(InvalidData(helpers::ERR_SYSEX_EXPECTED_END)))
+        );
+        assert_eq!(parser.consumed(), 2);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(UmpMessage::try_from(&buffer[2..3]).unwrap()))
+        );
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn reserved_message_type() {
+        let buffer = [0xB000_0000, 0x0, 0x0];
+        let mut parser = UmpStreamParser::new(&buffer[..]);
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert_eq!(parser.consumed(), 3);
+        assert_eq!(parser.next(), None);
+    }
+}
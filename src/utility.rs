@@ -128,6 +128,7 @@ pub(crate) const UMP_MESSAGE_TYPE: u8 = 0x0;
 pub use clock::Clock;
 pub use delta_clockstamp::DeltaClockstamp;
 pub use delta_clockstamp_tpq::DeltaClockstampTpq;
+pub use jitter_reduction::{JitterReductionReceiver, JitterReductionSender, JR_TICKS_PER_SECOND};
 pub use no_op::NoOp;
 pub use packet::Packet;
 pub use timestamp::Timestamp;
@@ -218,6 +219,7 @@ fn status<U: crate::buffer::Unit>(buffer: &[U]) -> u8 {
     }
     .into()
 }
+mod jitter_reduction;
 mod packet;
 
 #[cfg(test)]
@@ -0,0 +1,370 @@
+use crate::{
+    buffer::Ump,
+    utility::{Clock, Timestamp},
+};
+
+/// The rate of the jitter reduction clock, in ticks per second.
+///
+/// One tick lasts 32 microseconds.
+pub const JR_TICKS_PER_SECOND: u32 = 31_250;
+
+/// Generates the jitter reduction messages of a sending endpoint.
+///
+/// Times are read from a monotonic tick source running at [JR_TICKS_PER_SECOND].
+/// The sender should emit a JR clock periodically, by calling
+/// [poll_clock](JitterReductionSender::poll_clock) regularly,
+/// and prepend a JR timestamp to each outgoing message.
+/// The time data of the messages is the tick count truncated to 16 bits.
+///
+/// Only endpoints which declare
+/// [supports_sending_jr_timestamps](crate::ump_stream::EndpointInfo::supports_sending_jr_timestamps)
+/// should send JR timestamps.
+///
+/// ```rust
+/// use midi2::utility::JitterReductionSender;
+///
+/// let mut sender = JitterReductionSender::new();
+///
+/// // the first poll always emits a clock
+/// let clock = sender.poll_clock(0x1_0010).unwrap();
+/// assert_eq!(clock.time_data(), 0x0010);
+/// assert_eq!(sender.poll_clock(0x1_0020), None);
+///
+/// let timestamp = sender.timestamp(0x1_0030);
+/// assert_eq!(timestamp.time_data(), 0x0030);
+///
+/// let clock = sender.poll_clock(0x1_0010 + JitterReductionSender::DEFAULT_CLOCK_INTERVAL as u64);
+/// assert!(clock.is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JitterReductionSender {
+    clock_interval: u32,
+    last_clock: Option<u64>,
+}
+
+impl JitterReductionSender {
+    /// The interval between JR clocks recommended by the specification: 250 milliseconds.
+    pub const DEFAULT_CLOCK_INTERVAL: u32 = JR_TICKS_PER_SECOND / 4;
+
+    /// Create a sender which emits a JR clock every 250 milliseconds.
+    pub fn new() -> Self {
+        Self::new_with_clock_interval(Self::DEFAULT_CLOCK_INTERVAL)
+    }
+
+    /// Create a sender which emits a JR clock at the given interval, in ticks.
+    pub fn new_with_clock_interval(clock_interval: u32) -> Self {
+        JitterReductionSender {
+            clock_interval,
+            last_clock: None,
+        }
+    }
+
+    pub fn clock_interval(&self) -> u32 {
+        self.clock_interval
+    }
+
+    /// The time of the most recently emitted JR clock.
+    pub fn last_clock(&self) -> Option<u64> {
+        self.last_clock
+    }
+
+    /// Emit a JR clock if none has been emitted yet,
+    /// or if the clock interval has elapsed since the previous one.
+    pub fn poll_clock(&mut self, now: u64) -> Option<Clock<[u32; 1]>> {
+        match self.last_clock {
+            Some(last) if now.saturating_sub(last) < u64::from(self.clock_interval) => None,
+            _ => Some(self.clock(now)),
+        }
+    }
+
+    /// Emit a JR clock for the current time, regardless of the clock interval.
+    pub fn clock(&mut self, now: u64) -> Clock<[u32; 1]> {
+        self.last_clock = Some(now);
+        let mut clock = Clock::<[u32; 1]>::new();
+        clock.set_time_data(truncate(now));
+        clock
+    }
+
+    /// A JR timestamp for a message sent at the current time.
+    pub fn timestamp(&self, now: u64) -> Timestamp<[u32; 1]> {
+        let mut timestamp = Timestamp::<[u32; 1]>::new();
+        timestamp.set_time_data(truncate(now));
+        timestamp
+    }
+
+    /// Forget the previous JR clock, so that the next poll emits a clock immediately.
+    pub fn reset(&mut self) {
+        self.last_clock = None;
+    }
+}
+
+impl core::default::Default for JitterReductionSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interprets the jitter reduction messages received from a sending endpoint.
+///
+/// Times are read from a local monotonic tick source running at [JR_TICKS_PER_SECOND].
+///
+/// The 16 bit time of each JR clock is extended into a continuous sender time,
+/// which handles the rollover of the clock every ~2.1 seconds. JR clocks must therefore
+/// arrive less than 65536 ticks apart.
+/// Each JR clock also measures the offset between the sender time and the local time.
+/// The receiver keeps the smallest offset measured, i.e. the clock least delayed by jitter,
+/// while letting the offset rise by at most one tick per clock to follow any drift
+/// between the two tick sources.
+///
+/// JR timestamps are then converted into local delivery deadlines:
+/// the sender time of the timestamp, moved into local time by the offset,
+/// plus a fixed delay which should cover the expected jitter of the transport.
+///
+/// ```rust
+/// use midi2::{prelude::*, utility::{JitterReductionReceiver, JitterReductionSender}};
+///
+/// let mut sender = JitterReductionSender::new();
+/// let mut receiver = JitterReductionReceiver::new_with_delay(100);
+///
+/// // the clock takes 20 ticks to arrive
+/// let clock = sender.poll_clock(0xFFF0).unwrap();
+/// receiver.receive_clock(&clock, 0x1_0000 + 0xFFF0 + 20);
+///
+/// // the sender's clock rolls over before the timestamp
+/// let timestamp = sender.timestamp(0x1_0010);
+/// assert_eq!(
+///     receiver.deadline(&timestamp),
+///     Some(0x1_0000 + 0x1_0010 + 20 + 100),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JitterReductionReceiver {
+    delay: u32,
+    sender_clock: Option<SenderClock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SenderClock {
+    // the 16 bit time data of the most recent clock
+    time_data: u16,
+    // the continuous sender time of the most recent clock
+    time: u64,
+    // local time minus sender time
+    offset: i64,
+}
+
+impl JitterReductionReceiver {
+    /// Create a receiver which delivers messages at their converted time, without delay.
+    pub fn new() -> Self {
+        Self::new_with_delay(0)
+    }
+
+    /// Create a receiver which delivers messages the given number of ticks
+    /// after their converted time.
+    pub fn new_with_delay(delay: u32) -> Self {
+        JitterReductionReceiver {
+            delay,
+            sender_clock: None,
+        }
+    }
+
+    pub fn delay(&self) -> u32 {
+        self.delay
+    }
+
+    pub fn set_delay(&mut self, delay: u32) {
+        self.delay = delay;
+    }
+
+    /// The continuous sender time of the most recent JR clock.
+    pub fn sender_time(&self) -> Option<u64> {
+        self.sender_clock.as_ref().map(|clock| clock.time)
+    }
+
+    /// The offset from the sender time to the local time, in ticks.
+    pub fn offset(&self) -> Option<i64> {
+        self.sender_clock.as_ref().map(|clock| clock.offset)
+    }
+
+    /// Track a JR clock received at the given local time.
+    pub fn receive_clock<B: Ump>(&mut self, clock: &Clock<B>, now: u64) {
+        let time_data = clock.time_data();
+        let sender_clock = match self.sender_clock.take() {
+            None => SenderClock {
+                time_data,
+                time: u64::from(time_data),
+                offset: now as i64 - i64::from(time_data),
+            },
+            Some(previous) => {
+                let time = previous.time + u64::from(time_data.wrapping_sub(previous.time_data));
+                SenderClock {
+                    time_data,
+                    time,
+                    offset: (now as i64 - time as i64).min(previous.offset + 1),
+                }
+            }
+        };
+        self.sender_clock = Some(sender_clock);
+    }
+
+    /// The local time at which to deliver the message following the JR timestamp.
+    ///
+    /// Returns `None` until the first JR clock has been received.
+    /// Timestamps are expected within half a rollover period (~1 second)
+    /// of the most recent JR clock.
+    pub fn deadline<B: Ump>(&self, timestamp: &Timestamp<B>) -> Option<u64> {
+        let clock = self.sender_clock.as_ref()?;
+        let difference = i64::from(timestamp.time_data().wrapping_sub(clock.time_data) as i16);
+        let deadline = clock.time as i64 + difference + clock.offset + i64::from(self.delay);
+        Some(deadline.max(0) as u64)
+    }
+
+    /// Forget the sender clock, e.g. when the sender restarts.
+    pub fn reset(&mut self) {
+        self.sender_clock = None;
+    }
+}
+
+impl core::default::Default for JitterReductionReceiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn truncate(ticks: u64) -> u16 {
+    (ticks & 0xFFFF) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use pretty_assertions::assert_eq;
+
+    fn clock(time_data: u16) -> Clock<[u32; 1]> {
+        let mut clock = Clock::<[u32; 1]>::new();
+        clock.set_time_data(time_data);
+        clock
+    }
+
+    fn timestamp(time_data: u16) -> Timestamp<[u32; 1]> {
+        let mut timestamp = Timestamp::<[u32; 1]>::new();
+        timestamp.set_time_data(time_data);
+        timestamp
+    }
+
+    #[test]
+    fn sender_clock_interval() {
+        let mut sender = JitterReductionSender::new_with_clock_interval(100);
+        assert_eq!(sender.poll_clock(1000).unwrap().data(), &[0x0010_03E8]);
+        assert_eq!(sender.poll_clock(1099), None);
+        assert_eq!(sender.poll_clock(1100).unwrap().data(), &[0x0010_044C]);
+        assert_eq!(sender.last_clock(), Some(1100));
+    }
+
+    #[test]
+    fn sender_reset() {
+        let mut sender = JitterReductionSender::new();
+        assert!(sender.poll_clock(0).is_some());
+        assert_eq!(sender.poll_clock(1), None);
+        sender.reset();
+        assert!(sender.poll_clock(1).is_some());
+    }
+
+    #[test]
+    fn sender_timestamp() {
+        let sender = JitterReductionSender::new();
+        assert_eq!(sender.timestamp(0x3_1234).data(), &[0x0020_1234]);
+    }
+
+    #[test]
+    fn default_clock_interval() {
+        assert_eq!(JitterReductionSender::DEFAULT_CLOCK_INTERVAL, 7812);
+    }
+
+    #[test]
+    fn deadline_before_first_clock() {
+        let receiver = JitterReductionReceiver::new();
+        assert_eq!(receiver.deadline(&timestamp(0x1234)), None);
+    }
+
+    #[test]
+    fn deadline() {
+        let mut receiver = JitterReductionReceiver::new();
+        receiver.receive_clock(&clock(0x1000), 50_000);
+        assert_eq!(receiver.offset(), Some(50_000 - 0x1000));
+        assert_eq!(receiver.deadline(&timestamp(0x1100)), Some(50_000 + 0x100));
+    }
+
+    #[test]
+    fn deadline_with_delay() {
+        let mut receiver = JitterReductionReceiver::new_with_delay(30);
+        receiver.receive_clock(&clock(0x1000), 50_000);
+        assert_eq!(
+            receiver.deadline(&timestamp(0x1100)),
+            Some(50_000 + 0x100 + 30)
+        );
+    }
+
+    #[test]
+    fn timestamp_before_clock() {
+        let mut receiver = JitterReductionReceiver::new();
+        receiver.receive_clock(&clock(0x0010), 50_000);
+        assert_eq!(receiver.deadline(&timestamp(0xFFF0)), Some(50_000 - 0x20));
+    }
+
+    #[test]
+    fn clock_rollover() {
+        let mut receiver = JitterReductionReceiver::new();
+        receiver.receive_clock(&clock(0xF000), 100_000);
+        receiver.receive_clock(&clock(0x0800), 100_000 + 0x1800);
+        assert_eq!(receiver.sender_time(), Some(0x1_0800));
+        assert_eq!(receiver.offset(), Some(100_000 - 0xF000));
+        assert_eq!(
+            receiver.deadline(&timestamp(0x0900)),
+            Some(100_000 + 0x1900)
+        );
+    }
+
+    #[test]
+    fn late_clocks_do_not_move_the_offset() {
+        let mut receiver = JitterReductionReceiver::new();
+        receiver.receive_clock(&clock(0x1000), 10_000);
+        receiver.receive_clock(&clock(0x2000), 10_000 + 0x1000 + 50);
+        assert_eq!(receiver.offset(), Some(10_000 - 0x1000 + 1));
+    }
+
+    #[test]
+    fn early_clocks_lower_the_offset() {
+        let mut receiver = JitterReductionReceiver::new();
+        receiver.receive_clock(&clock(0x1000), 10_000);
+        receiver.receive_clock(&clock(0x2000), 10_000 + 0x1000 - 50);
+        assert_eq!(receiver.offset(), Some(10_000 - 0x1000 - 50));
+    }
+
+    #[test]
+    fn receiver_reset() {
+        let mut receiver = JitterReductionReceiver::new();
+        receiver.receive_clock(&clock(0x1000), 10_000);
+        receiver.reset();
+        assert_eq!(receiver.sender_time(), None);
+        assert_eq!(receiver.deadline(&timestamp(0x1000)), None);
+    }
+
+    #[test]
+    fn sender_to_receiver() {
+        let mut sender = JitterReductionSender::new();
+        let mut receiver = JitterReductionReceiver::new_with_delay(10);
+        let latency = 5;
+        let start = 0x12_3456;
+
+        for i in 0..20 {
+            let now = start + i * 4000;
+            if let Some(clock) = sender.poll_clock(now) {
+                receiver.receive_clock(&clock, now + latency);
+            }
+            let deadline = receiver.deadline(&sender.timestamp(now)).unwrap();
+            assert_eq!(deadline, now + latency + 10);
+        }
+    }
+}